@@ -0,0 +1,79 @@
+// reusable scratch space for repeated analyze runs (watch loops, editors)
+use std::collections::HashSet;
+use crate::core::classify::AnalysisOptions;
+use crate::core::graph::ReflexionGraph;
+use crate::core::state::EdgeState;
+use crate::core::store::GraphStore;
+use crate::core::types::EdgeId;
+
+//intermediate collections of one analyze run. empty by default, so the throwaway one of a
+//plain analyze allocates no more than before; one kept in an Analyzer only grows.
+#[derive(Debug, Default)]
+pub(crate) struct Scratch {
+    pub(crate) ids: Vec<EdgeId>, //edge list of the current pass
+    pub(crate) matches: Vec<(usize, usize, EdgeId)>, //spec edge candidates of one propagated edge
+    pub(crate) verdicts: Vec<(EdgeId, EdgeState)>, //impl edge -> state of its propagated edge
+    pub(crate) support_sets: Vec<HashSet<EdgeId>>, //emptied propagation_table entries, refilled by propagate and lift
+}
+
+impl Scratch {
+    pub(crate) fn recycle(&mut self, mut support: HashSet<EdgeId>) {
+        support.clear();
+        self.support_sets.push(support);
+    }
+}
+
+//analyze for hot loops: keeps the edge lists, candidate vectors and propagation_table
+//sets of one run for the next, so a steady-state rerun over a similar graph mostly
+//reuses capacity instead of allocating. results are exactly those of graph.analyze.
+#[derive(Debug, Default)]
+pub struct Analyzer {
+    scratch: Scratch,
+}
+
+impl Analyzer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn analyze<S: GraphStore>(&mut self, graph: &mut ReflexionGraph<S>, options: &AnalysisOptions) {
+        graph.run_analysis_with(options, None, &|_| true, &|_| true, &mut self.scratch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Analyzer;
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn analyzer_matches_analyze_across_reruns() {
+        let mut g = ReflexionGraph::new();
+        let sys = g.add_node(Node::new("Sys", SubgraphKind::Architecture, None)).unwrap();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, Some(sys))).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, Some(sys))).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(sys, sys, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let ia = g.add_node(Node::new("a", SubgraphKind::Implementation, None)).unwrap();
+        let ib = g.add_node(Node::new("b", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(ia, a).unwrap();
+        g.set_mapping(ib, b).unwrap();
+        g.add_edge(Edge::new(ia, ib, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        let options = AnalysisOptions::default();
+        let mut analyzer = Analyzer::new();
+        for round in 0..3 {
+            if round == 2 {
+                g.add_edge(Edge::new(ib, ia, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+            }
+            let mut plain = g.clone();
+            plain.analyze(&options);
+            analyzer.analyze(&mut g, &options);
+            assert!(g.is_analyzed());
+            assert_eq!(g.to_canonical(), plain.to_canonical());
+            assert_eq!(g.ambiguous_convergences(), plain.ambiguous_convergences());
+        }
+    }
+}
@@ -0,0 +1,144 @@
+// validated edge construction for user-built graphs
+use crate::core::types::{Counter, EdgeId, EdgeKind, NodeId, SubgraphKind};
+use crate::core::graph::{Edge, GraphError, ReflexionGraph};
+use crate::core::store::GraphStore;
+
+//add_edge only checks that the endpoints exist: the analysis passes use it to synthesize
+//Propagated edges. the builder is the checked path for hand-made models: on add() it
+//rejects blank kinds, missing endpoints, and endpoints outside the edge's side of the
+//model (Implementation edges join impl nodes, Architecture and Propagated edges join
+//arch nodes) with SubgraphMismatch.
+pub struct EdgeBuilder<'g, S: GraphStore> {
+    graph: &'g mut ReflexionGraph<S>,
+    edge: Edge,
+}
+
+impl<S: GraphStore> ReflexionGraph<S> {
+    pub fn build_edge(&mut self, from: NodeId, to: NodeId, kind: EdgeKind, subgraph: SubgraphKind) -> EdgeBuilder<'_, S> {
+        EdgeBuilder { graph: self, edge: Edge::new(from, to, kind, subgraph) }
+    }
+}
+
+impl<S: GraphStore> EdgeBuilder<'_, S> {
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.edge = self.edge.with_description(description);
+        self
+    }
+
+    pub fn confidence(mut self, confidence: f32) -> Self {
+        self.edge = self.edge.with_confidence(confidence);
+        self
+    }
+
+    pub fn min_support(mut self, min: Counter) -> Self {
+        self.edge = self.edge.with_min_support(min);
+        self
+    }
+
+    pub fn max_support(mut self, max: Counter) -> Self {
+        self.edge = self.edge.with_max_support(max);
+        self
+    }
+
+    pub fn variant(mut self, variant: impl Into<String>) -> Self {
+        self.edge = self.edge.with_variant(variant);
+        self
+    }
+
+    pub fn symmetric(mut self) -> Self {
+        self.edge = self.edge.with_symmetric();
+        self
+    }
+
+    pub fn test_only(mut self) -> Self {
+        self.edge = self.edge.with_test_only();
+        self
+    }
+
+    pub fn add(self) -> Result<EdgeId, GraphError> {
+        let Self { graph, edge } = self;
+        if edge.kind.is_blank() {
+            return Err(GraphError::InvalidEdgeKind);
+        }
+
+        let side = match edge.subgraph {
+            SubgraphKind::Implementation => SubgraphKind::Implementation,
+            SubgraphKind::Architecture | SubgraphKind::Propagated => SubgraphKind::Architecture,
+        };
+        for node in [edge.from, edge.to] {
+            let found = graph.node_subgraph(node)?;
+            if found != side {
+                return Err(GraphError::SubgraphMismatch { edge_subgraph: edge.subgraph, node, found });
+            }
+        }
+
+        graph.add_edge(edge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::graph::{GraphError, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, NodeId, SubgraphKind};
+
+    fn model() -> (ReflexionGraph, NodeId, NodeId, NodeId) {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        (g, a, b, i)
+    }
+
+    #[test]
+    fn builder_adds_consistent_edges_with_options() {
+        let (mut g, a, b, i) = model();
+        let spec = g
+            .build_edge(a, b, EdgeKind::calls(), SubgraphKind::Architecture)
+            .min_support(2)
+            .description("A uses B")
+            .add()
+            .unwrap();
+        assert_eq!(g.edge(spec).unwrap().min_support(), Some(2));
+        assert!(g.build_edge(a, b, EdgeKind::calls(), SubgraphKind::Propagated).add().is_ok());
+        assert!(g.build_edge(i, i, EdgeKind::calls(), SubgraphKind::Implementation).add().is_ok());
+    }
+
+    #[test]
+    fn builder_rejects_propagated_edge_touching_impl_node() {
+        let (mut g, a, _, i) = model();
+        let err = g.build_edge(a, i, EdgeKind::calls(), SubgraphKind::Propagated).add().unwrap_err();
+        assert_eq!(err, GraphError::SubgraphMismatch {
+            edge_subgraph: SubgraphKind::Propagated,
+            node: i,
+            found: SubgraphKind::Implementation,
+        });
+        assert!(g.edges_in_subgraph(SubgraphKind::Propagated).is_empty());
+    }
+
+    #[test]
+    fn builder_rejects_edges_across_sides() {
+        let (mut g, a, _, i) = model();
+        assert_eq!(
+            g.build_edge(i, a, EdgeKind::calls(), SubgraphKind::Architecture).add().unwrap_err(),
+            GraphError::SubgraphMismatch { edge_subgraph: SubgraphKind::Architecture, node: i, found: SubgraphKind::Implementation },
+        );
+        assert_eq!(
+            g.build_edge(i, a, EdgeKind::calls(), SubgraphKind::Implementation).add().unwrap_err(),
+            GraphError::SubgraphMismatch { edge_subgraph: SubgraphKind::Implementation, node: a, found: SubgraphKind::Architecture },
+        );
+    }
+
+    #[test]
+    fn builder_rejects_missing_endpoints_and_blank_kinds() {
+        let (mut g, a, _, _) = model();
+        assert_eq!(
+            g.build_edge(a, 99, EdgeKind::calls(), SubgraphKind::Architecture).add().unwrap_err(),
+            GraphError::NodeNotFound(99),
+        );
+        assert_eq!(
+            g.build_edge(a, a, EdgeKind::from("  "), SubgraphKind::Architecture).add().unwrap_err(),
+            GraphError::InvalidEdgeKind,
+        );
+        assert!(g.edges().next().is_none());
+    }
+}
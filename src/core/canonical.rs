@@ -0,0 +1,216 @@
+// canonical text form for snapshot tests
+use std::fmt::Write;
+use crate::core::graph::ReflexionGraph;
+
+//64-bit FNV-1a: tiny, and unlike std's DefaultHasher its output is fixed forever,
+//so stored fingerprints stay comparable across builds and toolchains
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &b in bytes {
+        hash ^= u64::from(b);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+impl ReflexionGraph {
+    //one sorted line per edge: subgraph, endpoint names, kind, state and counter
+    fn canonical_edge_lines(&self) -> Vec<String> {
+        let mut edges: Vec<String> = self
+            .store
+            .edges
+            .values()
+            .map(|e| {
+                format!(
+                    "{:?} {} -> {} [{}] state={:?} counter={}",
+                    e.subgraph,
+                    self.node_name(e.from),
+                    self.node_name(e.to),
+                    e.kind,
+                    e.state,
+                    e.counter
+                )
+            })
+            .collect();
+        edges.sort();
+        edges
+    }
+
+    //hash of the analysis result (every edge's state and counter, keyed by names) for
+    //"nothing changed, skip the report" checks. independent of insertion order and ids,
+    //so equivalent inputs give the same value in every run.
+    pub fn result_fingerprint(&self) -> u64 {
+        fnv1a(self.canonical_edge_lines().join("\n").as_bytes())
+    }
+
+    //same model built independently: equal nodes by (name, subgraph, kind, parent name)
+    //and equal edges by (from name, to name, kind, subgraph, state), as multisets.
+    //ids, insertion order, counters and mappings are not compared.
+    pub fn structurally_eq(&self, other: &Self) -> bool {
+        fn node_keys(g: &ReflexionGraph) -> Vec<(String, String, String, Option<String>)> {
+            let mut keys: Vec<_> = g
+                .store
+                .nodes
+                .values()
+                .map(|n| {
+                    let parent = n.parent.map(|p| g.node_name(p).to_string());
+                    (n.name.clone(), format!("{:?}", n.subgraph), format!("{:?}", n.kind), parent)
+                })
+                .collect();
+            keys.sort();
+            keys
+        }
+        fn edge_keys(g: &ReflexionGraph) -> Vec<(&str, &str, &str, String, String)> {
+            let mut keys: Vec<_> = g
+                .store
+                .edges
+                .values()
+                .map(|e| (g.node_name(e.from), g.node_name(e.to), e.kind.as_str(), format!("{:?}", e.subgraph), format!("{:?}", e.state)))
+                .collect();
+            keys.sort();
+            keys
+        }
+        node_keys(self) == node_keys(other) && edge_keys(self) == edge_keys(other)
+    }
+
+    //stable textual form of the whole graph for checked-in snapshots.
+    //everything is keyed by name, never by id, and every section is sorted,
+    //so the output only depends on the model and the analysis result,
+    //not on insertion order or the ids handed out by add_node/add_edge.
+    pub fn to_canonical(&self) -> String {
+        let mut nodes: Vec<String> = self
+            .store
+            .nodes
+            .values()
+            .map(|n| {
+                let parent = n.parent.map(|p| self.node_name(p)).unwrap_or("-");
+                format!("{:?} {} parent={}", n.subgraph, n.name, parent)
+            })
+            .collect();
+
+        let edges = self.canonical_edge_lines();
+
+        let mut mappings: Vec<String> = self
+            .maps_to
+            .iter()
+            .map(|(&i, &a)| format!("{} => {}", self.node_name(i), self.node_name(a)))
+            .collect();
+
+        nodes.sort();
+        mappings.sort();
+
+        let mut out = String::new();
+        for (title, lines) in [("nodes", &nodes), ("edges", &edges), ("mappings", &mappings)] {
+            writeln!(out, "{}:", title).expect("writing to a String cannot fail");
+            for line in lines {
+                writeln!(out, "  {}", line).expect("writing to a String cannot fail");
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn canonical_form_is_independent_of_insertion_order() {
+        let mut g1 = ReflexionGraph::new();
+        let a = g1.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g1.add_node(Node::new("B", SubgraphKind::Architecture, Some(a))).unwrap();
+        let i = g1.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        g1.add_edge(Edge::new(a, b, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+        g1.set_mapping(i, b).unwrap();
+
+        //same model, built in a different order (so every id differs)
+        let mut g2 = ReflexionGraph::new();
+        let i = g2.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let a = g2.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g2.add_node(Node::new("B", SubgraphKind::Architecture, Some(a))).unwrap();
+        g2.set_mapping(i, b).unwrap();
+        g2.add_edge(Edge::new(a, b, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+
+        assert_eq!(g1.to_canonical(), g2.to_canonical());
+    }
+
+    #[test]
+    fn canonical_form_lists_states_counters_and_mappings() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, Some(a))).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+        g.set_mapping(i, b).unwrap();
+        g.init_states();
+
+        let expected = "\
+nodes:
+  Architecture A parent=-
+  Architecture B parent=A
+  Implementation i parent=-
+edges:
+  Architecture A -> B [depends_on] state=Specified counter=0
+mappings:
+  i => B
+";
+        assert_eq!(g.to_canonical(), expected);
+    }
+
+    #[test]
+    fn result_fingerprint_tracks_results_not_ids() {
+        use crate::core::classify::AnalysisOptions;
+
+        let build = |pad: bool| {
+            let mut g = ReflexionGraph::new();
+            if pad {
+                g.add_node(Node::new("pad", SubgraphKind::Implementation, None)).unwrap();
+            }
+            let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+            let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+            let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+            let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+            g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+            g.set_mapping(i, a).unwrap();
+            g.set_mapping(j, b).unwrap();
+            (g, i, j)
+        };
+
+        let (mut g1, _, _) = build(false);
+        let (mut g2, i, j) = build(true);
+        g1.analyze(&AnalysisOptions::default());
+        g2.analyze(&AnalysisOptions::default());
+        assert_eq!(g1.result_fingerprint(), g2.result_fingerprint());
+
+        g2.add_edge(Edge::new(i, j, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g2.analyze(&AnalysisOptions::default());
+        assert_ne!(g1.result_fingerprint(), g2.result_fingerprint());
+    }
+
+    #[test]
+    fn structurally_eq_ignores_ids_but_not_content() {
+        let build = |extra_first: bool| {
+            let mut g = ReflexionGraph::new();
+            if extra_first {
+                let tmp = g.add_node(Node::new("tmp", SubgraphKind::Implementation, None)).unwrap();
+                g.remove_node(tmp).unwrap();
+            }
+            let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+            let b = g.add_node(Node::new("B", SubgraphKind::Architecture, Some(a))).unwrap();
+            g.add_edge(Edge::new(b, a, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+            g
+        };
+        let (g1, g2) = (build(false), build(true));
+        assert_ne!(g1.filter_nodes(|_| true), g2.filter_nodes(|_| true));
+        assert!(g1.structurally_eq(&g2));
+
+        let mut g3 = build(false);
+        g3.add_edge(Edge::new(1, 2, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        assert!(!g1.structurally_eq(&g3));
+
+        let mut g4 = build(false);
+        g4.add_node(Node::new("A", SubgraphKind::Implementation, None)).unwrap();
+        assert!(!g1.structurally_eq(&g4));
+    }
+}
@@ -1 +1,562 @@
 // classification logic
+use std::collections::HashSet;
+use crate::core::types::{EdgeId, EdgeKind, NodeId, SubgraphKind};
+use crate::core::graph::{Edge, GraphError, ReflexionGraph};
+use crate::core::state::EdgeState;
+use crate::core::analyzer::Scratch;
+use crate::core::incremental::spec_verdict;
+use crate::core::store::GraphStore;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnalysisOptions {
+    //impl edges whose confidence is below this are ignored (left Undefined), so weak
+    //runtime traces can't create divergences. edges without a confidence always count.
+    //default 0.0: every edge is considered.
+    pub min_confidence: f32,
+    //treat Proposed mappings as missing, so only reviewed mappings drive the verdicts.
+    //default false: proposed mappings count like confirmed ones.
+    pub ignore_proposed: bool,
+    //unmapped impl nodes inherit the mapping of their nearest mapped ancestor
+    //(see resolve_mapping). default false: only explicit mappings count.
+    pub inherit_mappings: bool,
+    //how many containment levels above the impl nodes an impl edge may be propagated,
+    //counting the arch nodes its endpoints map onto as level 1 and each arch parent the
+    //spec match climbs to (see matching_spec_edges) as one more, on either endpoint.
+    //0 = no propagation: impl edges stay Undefined, nothing is synthesized, the
+    //propagation_table stays empty and every spec edge is Absent. 1 = propagated edges
+    //between the mapped arch nodes only count for spec edges declared right there; they
+    //get table entries, a spec edge one level up doesn't and they turn Divergent.
+    //n = spec edges up to n - 1 levels above the mapped nodes take the support (and the
+    //table entry). default usize::MAX: climb to the roots.
+    pub max_propagation_levels: usize,
+    //leave impl edges tagged with_test_only out of the run (Undefined, like low-confidence
+    //edges), so dependencies of test code can't fail a CI gate. default false: they count
+    //like production edges, and test_divergences tells which divergences only they cause.
+    pub exclude_test_only: bool,
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        Self {
+            min_confidence: 0.0,
+            ignore_proposed: false,
+            inherit_mappings: false,
+            max_propagation_levels: usize::MAX,
+            exclude_test_only: false,
+        }
+    }
+}
+
+impl AnalysisOptions {
+    //whether an impl edge takes part in the run at all
+    pub(crate) fn considers(&self, edge: &Edge) -> bool {
+        self.max_propagation_levels > 0
+            && edge.confidence.is_none_or(|c| c >= self.min_confidence) && !(self.exclude_test_only && edge.test_only)
+    }
+}
+
+impl<S: GraphStore> ReflexionGraph<S> {
+    //edge kinds that express structure rather than dependency (default: contains).
+    //analyze neither propagates impl edges of these kinds (they stay Undefined) nor
+    //judges spec edges of these kinds (they stay Specified), so they never show up as
+    //convergences, divergences or absences.
+    pub fn set_structural_kinds(&mut self, kinds: &[EdgeKind]) {
+        self.structural_kinds = kinds.iter().cloned().collect();
+        self.invalidate();
+    }
+
+    pub fn is_structural(&self, kind: &EdgeKind) -> bool {
+        self.structural_kinds.contains(kind)
+    }
+
+    //inject ground truth from an external tool (e.g. a test that exercised the dependency):
+    //the spec or impl edge `from -> to` of `kind` gets `state` now and keeps it through
+    //every later analyze. precedence: a pinned state always wins over the computed one,
+    //for that edge only. the edge still propagates/counts as usual, so pinning an impl
+    //edge doesn't change the verdict of the spec edge it realizes.
+    pub fn assert_edge_state(&mut self, from: NodeId, to: NodeId, kind: &EdgeKind, state: EdgeState) -> Result<EdgeId, GraphError> {
+        let subgraph = self.node_subgraph(from)?;
+        self.node_subgraph(to)?;
+        let found = match subgraph {
+            SubgraphKind::Implementation => self
+                .store
+                .out_edges(from, SubgraphKind::Implementation)
+                .iter()
+                .copied()
+                .find(|&eid| self.edge_at(eid).to == to && self.edge_at(eid).kind == *kind),
+            _ => self.find_arch_edge(from, to, kind, SubgraphKind::Architecture),
+        };
+        let eid = found.ok_or(GraphError::NoEdgeBetween { from, to })?;
+
+        let e = self.store.get_edge_mut(eid).expect("found above");
+        e.pinned = Some(state);
+        e.state = state;
+        self.invalidate();
+        Ok(eid)
+    }
+
+    //drop a pin again; the next analyze computes the edge's state. returns the old pin.
+    pub fn unpin_edge_state(&mut self, edge: EdgeId) -> Result<Option<EdgeState>, GraphError> {
+        let e = self.store.get_edge_mut(edge).ok_or(GraphError::EdgeNotFound(edge))?;
+        let old = e.pinned.take();
+        if old.is_some() {
+            self.invalidate();
+        }
+        Ok(old)
+    }
+
+    fn apply_pinned_states(&mut self, scratch: &mut Scratch) {
+        scratch.ids.clear();
+        scratch.ids.extend(self.store.iter_edges().filter(|e| e.pinned.is_some()).map(|e| e.id));
+        for &eid in &scratch.ids {
+            let e = self.store.get_edge_mut(eid).expect("listed above");
+            e.state = e.pinned.expect("filtered above");
+        }
+    }
+
+    //final verdicts after propagate + lift:
+    // - spec edges: supported -> Convergent, no support -> Absent (structural kinds, spec
+    //   edges rejected by `judge` and spec edges of other variants skipped, they stay Specified)
+    // - multiplicity: a supported spec edge whose counter is outside [min_support,
+    //   max_support] is a MultiplicityViolation; an unsupported one with min_support 0
+    //   is AllowedAbsent (optional relation). no bounds = unconstrained
+    // - impl edges take the state of the propagated edge they were counted on
+    pub(crate) fn classify(&mut self, judge: &dyn Fn(&Edge) -> bool, scratch: &mut Scratch) {
+        self.edges_in_subgraph_into(SubgraphKind::Architecture, &mut scratch.ids);
+        for &eid in &scratch.ids {
+            let e = self.edge_at(eid);
+            if self.is_structural(&e.kind) || !judge(e) || !self.in_active_variant(e) {
+                continue;
+            }
+            let e = self.store.get_edge_mut(eid).expect("listed above");
+            e.state = spec_verdict(e);
+        }
+
+        scratch.verdicts.clear();
+        scratch.verdicts.extend(
+            self.propagation_table
+                .iter()
+                .filter(|(prop, _)| self.edge_at(**prop).subgraph == SubgraphKind::Propagated)
+                .flat_map(|(prop, impls)| {
+                    let state = self.edge_at(*prop).state;
+                    impls.iter().map(move |&i| (i, state))
+                }),
+        );
+
+        for &(eid, state) in &scratch.verdicts {
+            self.store.get_edge_mut(eid).expect("impl edge in propagation table").state = state;
+        }
+    }
+
+    //"would this dependency be allowed?" without adding it: the state an impl edge of
+    //`kind` from `from` to `to` would get under the current spec (and variant). impl
+    //endpoints are lifted like in the last analyze (Unmapped if one has no mapping), arch
+    //endpoints are taken as is. Undefined for unknown nodes and structural kinds. the
+    //spec's multiplicity bounds are not applied, they depend on the rest of the code.
+    pub fn classify_hypothetical(&self, from: NodeId, to: NodeId, kind: &EdgeKind) -> EdgeState {
+        let lift = |n: NodeId| match self.subgraph_of_node(n)? {
+            SubgraphKind::Implementation => Some(self.lift_target(n, &self.last_options)),
+            _ => Some(Some(n)),
+        };
+        let (Some(from), Some(to)) = (lift(from), lift(to)) else {
+            return EdgeState::Undefined;
+        };
+        if self.is_structural(kind) {
+            return EdgeState::Undefined;
+        }
+        let (Some(from), Some(to)) = (from, to) else {
+            return EdgeState::Unmapped;
+        };
+        EdgeState::classify(!self.matching_spec_edges(from, to, kind).is_empty(), from == to)
+    }
+
+    //full reflexion run. propagated edges of a previous run are dropped and all states
+    //reset, then: propagate (impl -> arch space) -> lift (onto the spec) -> classify ->
+    //encapsulation (see set_api_boundary), pinned states applied last.
+    pub fn analyze(&mut self, options: &AnalysisOptions) {
+        self.run_analysis(options, None, &|_| true, &|_| true);
+    }
+
+    //sharded analysis: analyze with `options`, but only impl edges originating from
+    //`impl_nodes` are lifted and classified, all other impl edges stay Undefined. spec
+    //edge verdicts only reflect the support found in that subset, so an edge realized by
+    //another shard shows up as Absent here. the shard isn't remembered: reanalyze_edge
+    //counts whatever edge it is given.
+    pub fn analyze_subset(&mut self, impl_nodes: &HashSet<NodeId>, options: &AnalysisOptions) {
+        self.run_analysis(options, None, &|e| impl_nodes.contains(&e.from), &|_| true);
+    }
+
+    //coarse view over a few relations only (e.g. just depends_on), analyzed with
+    //`options`: impl edges of other kinds are not propagated and stay Undefined, spec
+    //edges of other kinds are not judged and stay Specified. propagated edges therefore
+    //only exist for the selected kinds, and a spec edge can only be realized by impl edges
+    //of its own kind as usual. the filter is remembered until the next run, so
+    //reanalyze_edge leaves edges of other kinds Undefined too.
+    pub fn analyze_kinds(&mut self, kinds: &HashSet<EdgeKind>, options: &AnalysisOptions) {
+        let selected = |e: &Edge| kinds.contains(&e.kind);
+        self.run_analysis(options, None, &selected, &selected);
+        self.analyzed_kinds = Some(kinds.clone());
+    }
+
+    //whether the kind filter of the last run (see analyze_kinds) lets edges of `kind` in
+    pub(crate) fn in_analyzed_kinds(&self, kind: &EdgeKind) -> bool {
+        self.analyzed_kinds.as_ref().is_none_or(|kinds| kinds.contains(kind))
+    }
+
+    //`variant` picks the architecture variant to run against (None = every spec edge),
+    //`include` the impl edges to propagate, `judge` the spec edges to classify
+    pub(crate) fn run_analysis(
+        &mut self,
+        options: &AnalysisOptions,
+        variant: Option<&str>,
+        include: &dyn Fn(&Edge) -> bool,
+        judge: &dyn Fn(&Edge) -> bool,
+    ) {
+        self.run_analysis_with(options, variant, include, judge, &mut Scratch::default());
+    }
+
+    //run_analysis with the intermediate collections taken from (and left in) `scratch`
+    pub(crate) fn run_analysis_with(
+        &mut self,
+        options: &AnalysisOptions,
+        variant: Option<&str>,
+        include: &dyn Fn(&Edge) -> bool,
+        judge: &dyn Fn(&Edge) -> bool,
+        scratch: &mut Scratch,
+    ) {
+        self.active_variant = variant.map(str::to_string);
+        self.analyzed_kinds = None;
+        self.clear_propagated_edges_with(scratch);
+        self.init_states_with(scratch);
+        self.propagate(options, include, scratch);
+        self.lift(scratch);
+        self.classify(judge, scratch);
+        self.mark_encapsulation_violations();
+        self.apply_pinned_states(scratch);
+        self.set_analyzed();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AnalysisOptions;
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, NodeId, SubgraphKind};
+    use crate::core::state::EdgeState;
+
+    //arch: App -> Infra (depends_on), impl: app(App), infra(Infra), util(unmapped)
+    fn layered() -> (ReflexionGraph, NodeId, NodeId, NodeId) {
+        let mut g = ReflexionGraph::new();
+        let app_m = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let infra_m = g.add_node(Node::new("Infra", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(app_m, infra_m, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+
+        let app = g.add_node(Node::new("app", SubgraphKind::Implementation, None)).unwrap();
+        let infra = g.add_node(Node::new("infra", SubgraphKind::Implementation, None)).unwrap();
+        let util = g.add_node(Node::new("util", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(app, app_m).unwrap();
+        g.set_mapping(infra, infra_m).unwrap();
+        (g, app, infra, util)
+    }
+
+    #[test]
+    fn analyze_classifies_convergent_divergent_unmapped() {
+        let (mut g, app, infra, util) = layered();
+        let ok = g.add_edge(Edge::new(app, infra, EdgeKind::depends_on(), SubgraphKind::Implementation)).unwrap();
+        let back = g.add_edge(Edge::new(infra, app, EdgeKind::depends_on(), SubgraphKind::Implementation)).unwrap();
+        let lost = g.add_edge(Edge::new(app, util, EdgeKind::depends_on(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+
+        assert_eq!(g.edge(ok).unwrap().state(), EdgeState::Convergent);
+        assert_eq!(g.edge(back).unwrap().state(), EdgeState::Divergent);
+        assert_eq!(g.edge(lost).unwrap().state(), EdgeState::Unmapped);
+
+        let spec = g.edges_in_subgraph(SubgraphKind::Architecture)[0];
+        assert_eq!(g.edge(spec).unwrap().state(), EdgeState::Convergent);
+        assert_eq!(g.edge(spec).unwrap().counter(), 1);
+    }
+
+    #[test]
+    fn analyze_marks_unsupported_spec_edges_absent_and_is_repeatable() {
+        let (mut g, _, _, _) = layered();
+
+        g.analyze(&AnalysisOptions::default());
+        g.analyze(&AnalysisOptions::default());
+
+        let spec = g.edges_in_subgraph(SubgraphKind::Architecture)[0];
+        assert_eq!(g.edge(spec).unwrap().state(), EdgeState::Absent);
+        assert!(g.edges_in_subgraph(SubgraphKind::Propagated).is_empty());
+    }
+
+    #[test]
+    fn analyze_lifts_to_spec_edges_on_ancestors() {
+        let mut g = ReflexionGraph::new();
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let svc = g.add_node(Node::new("Svc", SubgraphKind::Architecture, Some(app))).unwrap();
+        let infra = g.add_node(Node::new("Infra", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, Some(infra))).unwrap();
+        let spec = g.add_edge(Edge::new(app, infra, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+
+        let s = g.add_node(Node::new("s", SubgraphKind::Implementation, None)).unwrap();
+        let d = g.add_node(Node::new("d", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(s, svc).unwrap();
+        g.set_mapping(d, db).unwrap();
+        let e = g.add_edge(Edge::new(s, d, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+
+        assert_eq!(g.edge(e).unwrap().state(), EdgeState::Convergent);
+        assert_eq!(g.edge(spec).unwrap().state(), EdgeState::Convergent);
+        assert!(g.propagation_table[&spec].contains(&e));
+    }
+
+    #[test]
+    fn analyze_ignores_impl_edges_below_confidence_threshold() {
+        let (mut g, app, infra, _) = layered();
+        let weak = g
+            .add_edge(Edge::new(infra, app, EdgeKind::depends_on(), SubgraphKind::Implementation).with_confidence(0.2))
+            .unwrap();
+        let strong = g
+            .add_edge(Edge::new(app, infra, EdgeKind::depends_on(), SubgraphKind::Implementation).with_confidence(0.9))
+            .unwrap();
+
+        //default: all edges count, so the weak trace diverges
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.edge(weak).unwrap().state(), EdgeState::Divergent);
+
+        g.analyze(&AnalysisOptions { min_confidence: 0.5, ..Default::default() });
+        assert_eq!(g.edge(weak).unwrap().state(), EdgeState::Undefined);
+        assert_eq!(g.edge(strong).unwrap().state(), EdgeState::Convergent);
+        assert!(g.edges_in_subgraph(SubgraphKind::Propagated).iter().all(|&p| g.edge(p).unwrap().state() != EdgeState::Divergent));
+    }
+
+    #[test]
+    fn test_only_edges_are_listed_apart_or_excluded() {
+        let (mut g, app, infra, _) = layered();
+        let fixture = g
+            .add_edge(Edge::new(infra, app, EdgeKind::depends_on(), SubgraphKind::Implementation).with_test_only())
+            .unwrap();
+        let prod = g.add_edge(Edge::new(infra, app, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        //default: test edges count like production ones, but are told apart
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.edge(fixture).unwrap().state(), EdgeState::Divergent);
+        let test_only = g.test_divergences().unwrap();
+        assert_eq!(test_only.len(), 1);
+        assert!(g.supporting_impl_edges(test_only[0]).unwrap().contains(&fixture));
+        //plus the unrealized spec edge App -> Infra
+        assert_eq!(g.violations().unwrap().len(), 3);
+
+        g.analyze(&AnalysisOptions { exclude_test_only: true, ..Default::default() });
+        assert_eq!(g.edge(fixture).unwrap().state(), EdgeState::Undefined);
+        assert_eq!(g.edge(prod).unwrap().state(), EdgeState::Divergent);
+        assert!(g.test_divergences().unwrap().is_empty());
+        assert_eq!(g.violations().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn analyze_subset_only_classifies_edges_from_the_shard() {
+        let (mut g, app, infra, _) = layered();
+        let mine = g.add_edge(Edge::new(app, infra, EdgeKind::depends_on(), SubgraphKind::Implementation)).unwrap();
+        let theirs = g.add_edge(Edge::new(infra, app, EdgeKind::depends_on(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze_subset(&[app].into_iter().collect(), &AnalysisOptions::default());
+        assert_eq!(g.edge(mine).unwrap().state(), EdgeState::Convergent);
+        assert_eq!(g.edge(theirs).unwrap().state(), EdgeState::Undefined);
+        assert!(g.violations().unwrap().is_empty());
+
+        //the other shard doesn't see app's support
+        g.analyze_subset(&[infra].into_iter().collect(), &AnalysisOptions::default());
+        assert_eq!(g.edge(mine).unwrap().state(), EdgeState::Undefined);
+        assert_eq!(g.edge(theirs).unwrap().state(), EdgeState::Divergent);
+        assert_eq!(g.absent_edges().unwrap().len(), 1);
+
+        //the shard runs with the given options, and later queries see them
+        let strict = AnalysisOptions { min_confidence: 0.5, ..Default::default() };
+        let weak = g.add_edge(Edge::new(app, infra, EdgeKind::calls(), SubgraphKind::Implementation).with_confidence(0.1)).unwrap();
+        g.analyze_subset(&[app].into_iter().collect(), &strict);
+        assert_eq!(g.edge(weak).unwrap().state(), EdgeState::Undefined);
+        assert_eq!(g.edge(mine).unwrap().state(), EdgeState::Convergent);
+        assert_eq!(g.last_options, strict);
+        assert_eq!(g.reanalyze_edge(weak).unwrap(), EdgeState::Undefined);
+    }
+
+    #[test]
+    fn structural_kinds_are_not_classified() {
+        let (mut g, app, infra, _) = layered();
+        let arch: Vec<_> = g.edges_in_subgraph(SubgraphKind::Architecture);
+        let spec = g.edge(arch[0]).unwrap();
+        let (app_m, infra_m) = (spec.from(), spec.to());
+        let spec_contains = g.add_edge(Edge::new(app_m, infra_m, EdgeKind::contains(), SubgraphKind::Architecture)).unwrap();
+        let code_contains = g.add_edge(Edge::new(infra, app, EdgeKind::contains(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.edge(spec_contains).unwrap().state(), EdgeState::Specified);
+        assert_eq!(g.edge(code_contains).unwrap().state(), EdgeState::Undefined);
+        assert_eq!(g.absent_edges().unwrap(), vec![arch[0]]);
+
+        //opting back in makes contains an ordinary dependency kind
+        g.set_structural_kinds(&[]);
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.edge(spec_contains).unwrap().state(), EdgeState::Absent);
+        assert_eq!(g.edge(code_contains).unwrap().state(), EdgeState::Divergent);
+    }
+
+    #[test]
+    fn analyze_can_ignore_proposed_mappings() {
+        let (mut g, app, _, util) = layered();
+        let arch = g.edges_in_subgraph(SubgraphKind::Architecture)[0];
+        let infra_m = g.edge(arch).unwrap().to();
+        g.propose_mapping(util, infra_m).unwrap();
+        let guess = g.add_edge(Edge::new(app, util, EdgeKind::depends_on(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.edge(guess).unwrap().state(), EdgeState::Convergent);
+
+        g.analyze(&AnalysisOptions { ignore_proposed: true, ..Default::default() });
+        assert_eq!(g.edge(guess).unwrap().state(), EdgeState::Unmapped);
+        assert_eq!(g.unmapped_edges(), vec![(guess, util)]);
+    }
+
+    #[test]
+    fn analyze_kinds_only_judges_the_selected_relations() {
+        let (mut g, app, infra, _) = layered();
+        let arch = g.edges_in_subgraph(SubgraphKind::Architecture)[0];
+        let (app_m, infra_m) = (g.edge(arch).unwrap().from(), g.edge(arch).unwrap().to());
+        let spec_calls = g.add_edge(Edge::new(app_m, infra_m, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let dep = g.add_edge(Edge::new(app, infra, EdgeKind::depends_on(), SubgraphKind::Implementation)).unwrap();
+        let call = g.add_edge(Edge::new(infra, app, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze_kinds(&[EdgeKind::depends_on()].into_iter().collect(), &AnalysisOptions::default());
+
+        assert_eq!(g.edge(dep).unwrap().state(), EdgeState::Convergent);
+        assert_eq!(g.edge(arch).unwrap().state(), EdgeState::Convergent);
+        assert_eq!(g.edge(call).unwrap().state(), EdgeState::Undefined);
+        assert_eq!(g.edge(spec_calls).unwrap().state(), EdgeState::Specified);
+        assert!(g.violations().unwrap().is_empty());
+        assert!(g.edges_in_subgraph(SubgraphKind::Propagated).iter().all(|&p| g.edge(p).unwrap().kind() == &EdgeKind::depends_on()));
+
+        //incremental recounts keep to the filter until a full run drops it
+        let late = g.add_edge(Edge::new(app, infra, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        assert_eq!(g.reanalyze_edge(late).unwrap(), EdgeState::Undefined);
+        assert_eq!(g.edge(spec_calls).unwrap().state(), EdgeState::Specified);
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.reanalyze_edge(late).unwrap(), EdgeState::Convergent);
+    }
+
+    #[test]
+    fn asserted_edge_state_survives_analyze() {
+        use crate::core::graph::GraphError;
+
+        let (mut g, app, infra, _) = layered();
+        let arch = g.edges_in_subgraph(SubgraphKind::Architecture)[0];
+        let (app_m, infra_m) = (g.edge(arch).unwrap().from(), g.edge(arch).unwrap().to());
+
+        //known from an integration test, even though the extractor missed it
+        let pinned = g.assert_edge_state(app_m, infra_m, &EdgeKind::depends_on(), EdgeState::Convergent).unwrap();
+        assert_eq!(pinned, arch);
+        let back = g.add_edge(Edge::new(infra, app, EdgeKind::depends_on(), SubgraphKind::Implementation)).unwrap();
+        g.assert_edge_state(infra, app, &EdgeKind::depends_on(), EdgeState::Allowed).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.edge(arch).unwrap().state(), EdgeState::Convergent);
+        assert_eq!(g.edge(back).unwrap().state(), EdgeState::Allowed);
+        assert_eq!(g.edge(arch).unwrap().counter(), 0);
+
+        assert_eq!(g.unpin_edge_state(arch).unwrap(), Some(EdgeState::Convergent));
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.edge(arch).unwrap().state(), EdgeState::Absent);
+
+        assert_eq!(
+            g.assert_edge_state(app_m, infra_m, &EdgeKind::calls(), EdgeState::Convergent).unwrap_err(),
+            GraphError::NoEdgeBetween { from: app_m, to: infra_m }
+        );
+    }
+
+    #[test]
+    fn analyze_can_inherit_mappings_from_packages() {
+        let (mut g, app, _, _) = layered();
+        let arch = g.edges_in_subgraph(SubgraphKind::Architecture)[0];
+        let infra_m = g.edge(arch).unwrap().to();
+
+        //infra.db.pool: only the top-level package is mapped, the middle one is proposed
+        let pkg = g.add_node(Node::new("infra", SubgraphKind::Implementation, None)).unwrap();
+        let db = g.add_node(Node::new("infra.db", SubgraphKind::Implementation, Some(pkg))).unwrap();
+        let pool = g.add_node(Node::new("infra.db.pool", SubgraphKind::Implementation, Some(db))).unwrap();
+        g.set_mapping(pkg, infra_m).unwrap();
+        let e = g.add_edge(Edge::new(app, pool, EdgeKind::depends_on(), SubgraphKind::Implementation)).unwrap();
+
+        assert_eq!(g.resolve_mapping(pool), Some(infra_m));
+        assert_eq!(g.resolve_mapping(app), g.get_arch_node(app).unwrap());
+
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.edge(e).unwrap().state(), EdgeState::Unmapped);
+
+        let inherit = AnalysisOptions { inherit_mappings: true, ..Default::default() };
+        g.analyze(&inherit);
+        assert_eq!(g.edge(e).unwrap().state(), EdgeState::Convergent);
+        assert!(g.unmapped_edges().is_empty());
+
+        //the nearest ancestor wins, also over a higher one
+        let other = g.add_node(Node::new("Other", SubgraphKind::Architecture, None)).unwrap();
+        g.propose_mapping(db, other).unwrap();
+        g.analyze(&inherit);
+        assert_eq!(g.edge(e).unwrap().state(), EdgeState::Divergent);
+        g.analyze(&AnalysisOptions { ignore_proposed: true, ..inherit });
+        assert_eq!(g.edge(e).unwrap().state(), EdgeState::Convergent);
+    }
+
+    #[test]
+    fn support_bounds_turn_into_multiplicity_violations() {
+        let mut g = ReflexionGraph::new();
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        let log = g.add_node(Node::new("Log", SubgraphKind::Architecture, None)).unwrap();
+        //at most one gateway into Db, logging is optional
+        let gateway = g
+            .add_edge(Edge::new(app, db, EdgeKind::calls(), SubgraphKind::Architecture).with_max_support(1))
+            .unwrap();
+        let logging = g
+            .add_edge(Edge::new(app, log, EdgeKind::calls(), SubgraphKind::Architecture).with_min_support(0))
+            .unwrap();
+
+        let a = g.add_node(Node::new("a", SubgraphKind::Implementation, None)).unwrap();
+        let d = g.add_node(Node::new("d", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(a, app).unwrap();
+        g.set_mapping(d, db).unwrap();
+        g.add_edge(Edge::new(a, d, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.edge(gateway).unwrap().state(), EdgeState::Convergent);
+        assert_eq!(g.edge(logging).unwrap().state(), EdgeState::AllowedAbsent);
+        assert!(g.violations().unwrap().is_empty());
+
+        g.add_edge(Edge::new(a, d, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.edge(gateway).unwrap().state(), EdgeState::MultiplicityViolation);
+        assert_eq!(g.violations().unwrap(), vec![gateway]);
+    }
+
+    #[test]
+    fn classify_hypothetical_predicts_the_verdict_without_adding_the_edge() {
+        let (mut g, app, infra, util) = layered();
+        let spec = g.edges_in_subgraph(SubgraphKind::Architecture)[0];
+        let (app_m, infra_m) = (g.edge(spec).unwrap().from(), g.edge(spec).unwrap().to());
+        let dep = EdgeKind::depends_on();
+
+        assert_eq!(g.classify_hypothetical(app, infra, &dep), EdgeState::Convergent);
+        assert_eq!(g.classify_hypothetical(infra, app, &dep), EdgeState::Divergent);
+        assert_eq!(g.classify_hypothetical(app, app, &dep), EdgeState::Allowed);
+        assert_eq!(g.classify_hypothetical(app, util, &dep), EdgeState::Unmapped);
+        assert_eq!(g.classify_hypothetical(app_m, infra_m, &dep), EdgeState::Convergent);
+        assert_eq!(g.classify_hypothetical(app, infra, &EdgeKind::calls()), EdgeState::Divergent);
+        assert_eq!(g.classify_hypothetical(app, infra, &EdgeKind::contains()), EdgeState::Undefined);
+        assert_eq!(g.classify_hypothetical(app, 999, &dep), EdgeState::Undefined);
+
+        //same answer as the real analysis
+        let e = g.add_edge(Edge::new(infra, app, dep.clone(), SubgraphKind::Implementation)).unwrap();
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.edge(e).unwrap().state(), EdgeState::Divergent);
+    }
+}
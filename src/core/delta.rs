@@ -1 +1,202 @@
 // incremental diffs
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::core::graph::{Edge, GraphError, ReflexionGraph};
+use crate::core::types::{Counter, EdgeId, SubgraphKind};
+
+const BASELINE_HEADER: &str = "# reflexion baseline v1";
+
+//violations that appeared / disappeared relative to a baseline, as name-keyed lines
+//("<state> <from> -> <to> [<kind>]"), both sorted
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BaselineResult {
+    pub new: Vec<String>,
+    pub resolved: Vec<String>,
+}
+
+impl BaselineResult {
+    //nothing new: the CI gate passes (resolved violations are good news, not failures)
+    pub fn is_clean(&self) -> bool {
+        self.new.is_empty()
+    }
+}
+
+impl ReflexionGraph {
+    //violations keyed by names instead of ids, so two graphs built independently
+    //(e.g. two extractor runs) can be compared. NotAnalyzed like violations().
+    pub fn violation_keys(&self) -> Result<BTreeSet<String>, GraphError> {
+        Ok(self
+            .violations()?
+            .into_iter()
+            .map(|eid| {
+                let e = &self.store.edges[&eid];
+                let what = match e.subgraph {
+                    SubgraphKind::Implementation => "EncapsulationBreach".to_string(),
+                    _ => format!("{:?}", e.state),
+                };
+                format!("{} {} -> {} [{}]", what, self.node_name(e.from), self.node_name(e.to), e.kind)
+            })
+            .collect())
+    }
+
+    fn diff_violation_keys(&self, baseline: &BTreeSet<String>) -> Result<BaselineResult, GraphError> {
+        let current = self.violation_keys()?;
+        Ok(BaselineResult {
+            new: current.difference(baseline).cloned().collect(),
+            resolved: baseline.difference(&current).cloned().collect(),
+        })
+    }
+
+    //name-based diff of this graph's violations against an older analyzed graph.
+    //NotAnalyzed when either side is stale.
+    pub fn diff_violations(&self, baseline: &ReflexionGraph) -> Result<BaselineResult, GraphError> {
+        self.diff_violation_keys(&baseline.violation_keys()?)
+    }
+
+    //support trend between two runs: spec and propagated edges of this graph whose counter
+    //differs from the baseline's edge with the same endpoint names, kind and subgraph, as
+    //(edge, baseline counter, current counter), sorted by edge. an edge the baseline lacks
+    //counts as 0 there (a new module dependency); edges that disappeared have no id here
+    //and are left to diff_violations.
+    pub fn counter_deltas(&self, baseline: &ReflexionGraph) -> Vec<(EdgeId, Counter, Counter)> {
+        fn key<'g>(g: &'g ReflexionGraph, e: &'g Edge) -> (SubgraphKind, &'g str, &'g str, &'g str) {
+            (e.subgraph, g.node_name(e.from), g.node_name(e.to), e.kind.as_str())
+        }
+        let counted = |e: &&Edge| e.subgraph != SubgraphKind::Implementation;
+
+        let mut before: HashMap<(SubgraphKind, &str, &str, &str), Counter> = HashMap::new();
+        for e in baseline.store.edges.values().filter(counted) {
+            *before.entry(key(baseline, e)).or_default() += e.counter;
+        }
+        let mut out: Vec<(EdgeId, Counter, Counter)> = self
+            .store
+            .edges
+            .values()
+            .filter(counted)
+            .map(|e| (e.id, before.get(&key(self, e)).copied().unwrap_or(0), e.counter))
+            .filter(|&(_, old, new)| old != new)
+            .collect();
+        out.sort_unstable();
+        out
+    }
+
+    //store the current violations as the accepted "first run" baseline. a stale graph is
+    //refused with an io::Error wrapping NotAnalyzed, before the file is touched.
+    pub fn write_baseline(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let keys = self.violation_keys().map_err(io::Error::other)?;
+        let mut out = String::from(BASELINE_HEADER);
+        out.push('\n');
+        for key in keys {
+            out.push_str(&key);
+            out.push('\n');
+        }
+        fs::write(path, out)
+    }
+
+    //compare the current violations against a file written by write_baseline (NotAnalyzed
+    //wrapped like in write_baseline, also before the file is read)
+    pub fn check_against_baseline(&self, path: impl AsRef<Path>) -> io::Result<BaselineResult> {
+        self.require_analyzed().map_err(io::Error::other)?;
+        let text = fs::read_to_string(path)?;
+        let baseline: BTreeSet<String> = text
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        self.diff_violation_keys(&baseline).map_err(io::Error::other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, NodeId, SubgraphKind};
+
+    //App -> Db specified; impl nodes app(App) and db(Db)
+    fn model() -> (ReflexionGraph, NodeId, NodeId) {
+        let mut g = ReflexionGraph::new();
+        let app_m = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let db_m = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(app_m, db_m, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let app = g.add_node(Node::new("app", SubgraphKind::Implementation, None)).unwrap();
+        let db = g.add_node(Node::new("db", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(app, app_m).unwrap();
+        g.set_mapping(db, db_m).unwrap();
+        (g, app, db)
+    }
+
+    #[test]
+    fn diff_violations_reports_new_and_resolved_by_name() {
+        let (mut old, _, _) = model();
+        old.analyze(&AnalysisOptions::default());
+
+        let (mut now, app, db) = model();
+        now.add_edge(Edge::new(app, db, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        now.add_edge(Edge::new(db, app, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        now.analyze(&AnalysisOptions::default());
+
+        let diff = now.diff_violations(&old).unwrap();
+        assert_eq!(diff.new, vec!["Divergent Db -> App [calls]".to_string()]);
+        assert_eq!(diff.resolved, vec!["Absent App -> Db [calls]".to_string()]);
+        assert!(!diff.is_clean());
+    }
+
+    #[test]
+    fn baseline_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("reflexion-baseline-{}.txt", std::process::id()));
+
+        let (mut g, app, db) = model();
+        g.add_edge(Edge::new(db, app, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        //stale: refused before anything is written
+        assert!(g.write_baseline(&path).is_err());
+        assert!(!path.exists());
+        g.analyze(&AnalysisOptions::default());
+        g.write_baseline(&path).unwrap();
+
+        //unchanged -> clean
+        assert_eq!(g.check_against_baseline(&path).unwrap(), Default::default());
+
+        //implementing the missing dependency resolves the absence
+        g.add_edge(Edge::new(app, db, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.analyze(&AnalysisOptions::default());
+        let result = g.check_against_baseline(&path).unwrap();
+        assert!(result.is_clean());
+        assert_eq!(result.resolved, vec!["Absent App -> Db [calls]".to_string()]);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(g.check_against_baseline(&path).is_err());
+    }
+
+    #[test]
+    fn counter_deltas_match_edges_by_name() {
+        let (mut old, app, db) = model();
+        old.add_edge(Edge::new(app, db, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        old.analyze(&AnalysisOptions::default());
+
+        //rebuilt with padding, so every id differs
+        let mut now = ReflexionGraph::new();
+        now.add_node(Node::new("pad", SubgraphKind::Implementation, None)).unwrap();
+        let app_m = now.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let db_m = now.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        let spec = now.add_edge(Edge::new(app_m, db_m, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let app = now.add_node(Node::new("app", SubgraphKind::Implementation, None)).unwrap();
+        let db = now.add_node(Node::new("db", SubgraphKind::Implementation, None)).unwrap();
+        now.set_mapping(app, app_m).unwrap();
+        now.set_mapping(db, db_m).unwrap();
+        for (from, to) in [(app, db), (app, db), (db, app)] {
+            now.add_edge(Edge::new(from, to, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        }
+        now.analyze(&AnalysisOptions::default());
+
+        let propagated = |from| now.edges().find(|e| e.subgraph() == SubgraphKind::Propagated && e.from() == from).unwrap().id();
+        //the spec edge and its propagated edge strengthened, Db -> App is new
+        let mut expected = vec![(spec, 1, 2), (propagated(app_m), 1, 2), (propagated(db_m), 0, 1)];
+        expected.sort_unstable();
+        assert_eq!(now.counter_deltas(&old), expected);
+        assert!(now.counter_deltas(&now).is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+// public-API boundaries: encapsulation on top of the containment hierarchy
+use crate::core::types::{EdgeId, NodeId, SubgraphKind};
+use crate::core::graph::{GraphError, ReflexionGraph};
+use crate::core::state::EdgeState;
+use crate::core::store::GraphStore;
+
+impl<S: GraphStore> ReflexionGraph<S> {
+    //declare an arch node the public API of its parent module. once a module has such a
+    //child, code outside the module may only depend on it through its API children (or on
+    //the module itself); everything else inside is private. see encapsulation_violations.
+    pub fn set_api_boundary(&mut self, arch_node: NodeId) -> Result<(), GraphError> {
+        self.expect_arch_node(arch_node)?;
+        self.api_boundaries.insert(arch_node);
+        self.invalidate();
+        Ok(())
+    }
+
+    pub fn is_api_boundary(&self, arch_node: NodeId) -> bool {
+        self.api_boundaries.contains(&arch_node)
+    }
+
+    //impl edges that reach inside an encapsulated module from outside it, past its API:
+    //the target lifts to a descendant of a module with API children, the source lifts to
+    //somewhere outside that module, and the target's branch below the module is not an
+    //API node. holds for every encapsulated module on the way down, so a nested module
+    //has to be entered through its own API too. mappings are resolved as in the last
+    //analyze, structural kinds are skipped. sorted ids. analyze marks these edges
+    //Divergent even when their module-level dependency is specified.
+    pub fn encapsulation_violations(&self) -> Vec<EdgeId> {
+        if self.api_boundaries.is_empty() {
+            return Vec::new();
+        }
+        self.filter_edges(|e| {
+            if e.subgraph != SubgraphKind::Implementation || self.is_structural(&e.kind) {
+                return false;
+            }
+            let (Some(from), Some(to)) = (self.lift_target(e.from, &self.last_options), self.lift_target(e.to, &self.last_options)) else {
+                return false;
+            };
+            let sources = self.self_and_ancestors(from);
+            //target path from the top, paired with the node one level further down
+            let mut path = self.self_and_ancestors(to);
+            path.reverse();
+            path.windows(2).any(|pair| {
+                let (module, branch) = (pair[0], pair[1]);
+                !sources.contains(&module) && self.has_api(module) && !self.api_boundaries.contains(&branch)
+            })
+        })
+    }
+
+    fn has_api(&self, module: NodeId) -> bool {
+        self.node_at(module).children.iter().any(|c| self.api_boundaries.contains(c))
+    }
+
+    //the breaches the last analyze marked Divergent that no Divergent module dependency
+    //already reports: the module-level edge is specified or allowed, so without them the
+    //violation list would not show the breach at all. part of violations(). sorted ids.
+    pub(crate) fn encapsulation_breaches(&self) -> Vec<EdgeId> {
+        let mut ids = self.encapsulation_violations();
+        ids.retain(|&eid| {
+            self.edge_at(eid).state == EdgeState::Divergent
+                && self.lift_edge(eid).is_none_or(|(from, to, kind)| {
+                    self.find_arch_edge(from, to, &kind, SubgraphKind::Propagated)
+                        .is_none_or(|prop| self.edge_at(prop).state != EdgeState::Divergent)
+                })
+        });
+        ids
+    }
+
+    //analysis pass after classify: encapsulation breaches among the classified impl edges
+    //become Divergent
+    pub(crate) fn mark_encapsulation_violations(&mut self) {
+        for eid in self.encapsulation_violations() {
+            let e = self.store.get_edge_mut(eid).expect("listed above");
+            if !matches!(e.state, EdgeState::Undefined | EdgeState::Unmapped) {
+                e.state = EdgeState::Divergent;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::{Edge, GraphError, Node, ReflexionGraph};
+    use crate::core::state::EdgeState;
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn edges_into_private_parts_of_a_module_diverge() {
+        let mut g = ReflexionGraph::new();
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        let api = g.add_node(Node::new("Api", SubgraphKind::Architecture, Some(db))).unwrap();
+        let pool = g.add_node(Node::new("Pool", SubgraphKind::Architecture, Some(db))).unwrap();
+        g.add_edge(Edge::new(app, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let mut imp = |name: &str, arch| {
+            let n = g.add_node(Node::new(name, SubgraphKind::Implementation, None)).unwrap();
+            g.set_mapping(n, arch).unwrap();
+            n
+        };
+        let (a, q, p) = (imp("a", app), imp("query", api), imp("pool", pool));
+        let through_api = g.add_edge(Edge::new(a, q, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        let past_api = g.add_edge(Edge::new(a, p, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        let inside = g.add_edge(Edge::new(q, p, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.edge(past_api).unwrap().state(), EdgeState::Convergent);
+        assert!(g.encapsulation_violations().is_empty());
+
+        g.set_api_boundary(api).unwrap();
+        assert_eq!(g.set_api_boundary(a), Err(GraphError::WrongSubgraph { node: a, expected: SubgraphKind::Architecture, found: SubgraphKind::Implementation }));
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.encapsulation_violations(), vec![past_api]);
+        assert_eq!(g.edge(past_api).unwrap().state(), EdgeState::Divergent);
+        assert_eq!(g.edge(through_api).unwrap().state(), EdgeState::Convergent);
+        //Api -> Pool stays inside Db: an ordinary (unspecified) dependency, not a breach
+        assert!(!g.encapsulation_violations().contains(&inside));
+    }
+
+    #[test]
+    fn breaches_are_violations_and_fail_the_ci_gate() {
+        let mut g = ReflexionGraph::new();
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        let api = g.add_node(Node::new("Api", SubgraphKind::Architecture, Some(db))).unwrap();
+        let pool = g.add_node(Node::new("Pool", SubgraphKind::Architecture, Some(db))).unwrap();
+        g.add_edge(Edge::new(app, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let a = g.add_node(Node::new("a", SubgraphKind::Implementation, None)).unwrap();
+        let p = g.add_node(Node::new("pool", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(a, app).unwrap();
+        g.set_mapping(p, pool).unwrap();
+        let past_api = g.add_edge(Edge::new(a, p, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+        assert!(g.violations().unwrap().is_empty());
+        assert!(!g.has_errors().unwrap());
+
+        g.set_api_boundary(api).unwrap();
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.violations().unwrap(), vec![past_api]);
+        assert!(g.has_errors().unwrap());
+        assert!(g.violation_keys().unwrap().contains("EncapsulationBreach a -> pool [calls]"));
+        assert!(g.to_junit().contains(r#"<testcase classname="App" name="a -&gt; pool [calls]">"#));
+        assert!(g.to_junit().contains(r#"<failure type="EncapsulationBreach""#));
+    }
+}
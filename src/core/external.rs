@@ -0,0 +1,69 @@
+// impl nodes implemented outside this graph (other repos, third-party services)
+use crate::core::types::{EdgeId, NodeId, SubgraphKind};
+use crate::core::graph::ReflexionGraph;
+use crate::core::store::GraphStore;
+
+impl<S: GraphStore> ReflexionGraph<S> {
+    //a node is external when it or one of its ancestors was built with_external(), so
+    //marking a package covers everything under it
+    pub fn is_external_node(&self, node: NodeId) -> bool {
+        self.self_and_ancestors(node).iter().any(|n| self.store.get_node(*n).is_some_and(|n| n.external))
+    }
+
+    //impl edges the last analyze set aside as cross-repo references: every endpoint
+    //without a mapping is external, so instead of Unmapped the edge is Allowed and not
+    //propagated. an external node that does have a mapping (e.g. onto a placeholder arch
+    //node for the other service) is lifted like any other node, and its edges are
+    //classified against the spec as usual. sorted by id.
+    pub fn external_dependencies(&self) -> Vec<EdgeId> {
+        let mut out: Vec<EdgeId> = self
+            .store
+            .iter_edges()
+            .filter(|e| e.subgraph == SubgraphKind::Implementation && self.is_external_edge(e.from, e.to))
+            .map(|e| e.id)
+            .collect();
+        out.sort_unstable();
+        out
+    }
+
+    //some endpoint is unmapped (resolved as in the last analyze), and all unmapped ones are external
+    pub(crate) fn is_external_edge(&self, from: NodeId, to: NodeId) -> bool {
+        let unmapped: Vec<NodeId> = [from, to].into_iter().filter(|&n| self.lift_target(n, &self.last_options).is_none()).collect();
+        !unmapped.is_empty() && unmapped.iter().all(|&n| self.is_external_node(n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::state::EdgeState;
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn edges_to_unmapped_external_nodes_are_allowed_not_unmapped() {
+        let mut g = ReflexionGraph::new();
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let payments = g.add_node(Node::new("Payments", SubgraphKind::Architecture, None)).unwrap();
+        let main = g.add_node(Node::new("main", SubgraphKind::Implementation, None)).unwrap();
+        let remote = g.add_node(Node::new("remote", SubgraphKind::Implementation, None).with_external()).unwrap();
+        let client = g.add_node(Node::new("remote::client", SubgraphKind::Implementation, Some(remote))).unwrap();
+        let stub = g.add_node(Node::new("payments_api", SubgraphKind::Implementation, None).with_external()).unwrap();
+        let lost = g.add_node(Node::new("lost", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(main, app).unwrap();
+        g.set_mapping(stub, payments).unwrap();
+
+        let cross = g.add_edge(Edge::new(main, client, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        let mapped = g.add_edge(Edge::new(main, stub, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        let half = g.add_edge(Edge::new(lost, client, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.analyze(&AnalysisOptions::default());
+
+        assert!(g.is_external_node(client));
+        assert_eq!(g.edge(cross).unwrap().state(), EdgeState::Allowed);
+        //mapped external endpoints are classified as usual: no spec edge App -> Payments
+        assert_eq!(g.edge(mapped).unwrap().state(), EdgeState::Divergent);
+        //`lost` is a plain unmapped node, so this one still needs a mapping
+        assert_eq!(g.edge(half).unwrap().state(), EdgeState::Unmapped);
+        assert_eq!(g.external_dependencies(), vec![cross]);
+    }
+}
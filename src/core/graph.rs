@@ -1,16 +1,30 @@
 // nodes, edges, IR 
-use std::collections::{HashMap, HashSet};
-use std::fmt::{self, write};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 use crate::core::types::{NodeId, EdgeId, Counter, SubgraphKind, EdgeKind, NodeKind};
-use crate::core::state::EdgeState;
-
+use crate::core::state::{EdgeState, Phase, Severity};
+use crate::core::classify::AnalysisOptions;
+use crate::core::analyzer::Scratch;
+use crate::core::store::{GraphStore, HashMapStore};
+#[cfg(feature = "regex")]
+use crate::core::pattern::KindPattern;
+
+//variants only carry ids/kinds so the error stays Copy + Eq and tests can assert_eq! on it.
+//a variant that needs a String would cost Copy for the whole enum: avoid, or document it here.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GraphError {
     ParentNotFound(NodeId),
     NodeNotFound(NodeId),
+    EdgeNotFound(EdgeId),
     WrongSubgraph { node: NodeId, expected: SubgraphKind, found: SubgraphKind },
     MappingAlreadyExists { impl_node: NodeId, old_arch: NodeId, new_arch: NodeId },
     ImplNodeAlreadyMapped(NodeId),
+    InvalidEdgeKind,
+    HierarchyCycle { child: NodeId, parent: NodeId },
+    NoEdgeBetween { from: NodeId, to: NodeId }, //no edge of the requested kind between the two nodes
+    SubgraphMismatch { edge_subgraph: SubgraphKind, node: NodeId, found: SubgraphKind }, //endpoint on the wrong side for the edge
+    NotAnalyzed, //result query before analyze, or after a change made the results stale
+    PhaseTooEarly { required: Phase, actual: Phase }, //require_phase on a graph not that far along
 }
 
 impl fmt::Display for GraphError {
@@ -24,6 +38,10 @@ impl fmt::Display for GraphError {
                 write!(f, "Node not found (node id = {})", id)
             }
 
+            GraphError::EdgeNotFound(id) => {
+                write!(f, "Edge not found (edge id = {})", id)
+            }
+
             GraphError::WrongSubgraph { node, expected, found } => {
                 write!(
                     f,
@@ -51,75 +69,512 @@ impl fmt::Display for GraphError {
                     impl_node
                 )
             }
+
+            GraphError::InvalidEdgeKind => {
+                write!(f, "Edge kind is empty or whitespace only")
+            }
+
+            GraphError::HierarchyCycle { child, parent } => {
+                write!(
+                    f,
+                    "Moving node {} under node {} would create a containment cycle",
+                    child, parent
+                )
+            }
+
+            GraphError::NoEdgeBetween { from, to } => {
+                write!(f, "No edge of the requested kind from node {} to node {}", from, to)
+            }
+
+            GraphError::SubgraphMismatch { edge_subgraph, node, found } => {
+                write!(
+                    f,
+                    "A {:?} edge cannot have endpoint {}, which is in {:?}",
+                    edge_subgraph, node, found
+                )
+            }
+
+            GraphError::NotAnalyzed => {
+                write!(f, "No analysis results: the graph was not analyzed since it was built or last changed")
+            }
+
+            GraphError::PhaseTooEarly { required, actual } => {
+                write!(f, "The graph is {:?}, but this needs at least {:?}", actual, required)
+            }
         }
     }
 }
 
 impl std::error::Error for GraphError{} 
 
+#[derive(Debug, Clone)]
 pub struct Node {
-    id: NodeId,
-    name: String,
-    subgraph: SubgraphKind,
-    parent: Option<NodeId>,
-    children: Vec<NodeId>,
+    pub(crate) id: NodeId,
+    pub(crate) name: String,
+    pub(crate) subgraph: SubgraphKind,
+    pub(crate) parent: Option<NodeId>,
+    pub(crate) children: Vec<NodeId>,
+    pub(crate) description: Option<String>, //what the module/element is for
+    pub(crate) kind: Option<NodeKind>, //what sort of element (service, class, ...), if known
+    pub(crate) external: bool, //implemented elsewhere (another repo), see external_dependencies
+    pub(crate) location: Option<String>, //source file the element was extracted from, if known
 }
 
 
 impl Node {
-    pub(crate) fn new(name: impl Into<String>, subgraph: SubgraphKind, parent: Option<NodeId>) -> Self {
+    pub fn new(name: impl Into<String>, subgraph: SubgraphKind, parent: Option<NodeId>) -> Self {
         Self {
             id: 0, // overwritten by add_node
             name: name.into(),
             subgraph,
             parent,
             children: vec![],
+            description: None,
+            kind: None,
+            external: false,
+            location: None,
         }
     }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_kind(mut self, kind: NodeKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    //mark as implemented outside this graph: unmapped, its edges are Allowed instead of Unmapped
+    pub fn with_external(mut self) -> Self {
+        self.external = true;
+        self
+    }
+
+    pub fn with_location(mut self, file: impl Into<String>) -> Self {
+        self.location = Some(file.into());
+        self
+    }
+
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn subgraph(&self) -> SubgraphKind {
+        self.subgraph
+    }
+
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn kind(&self) -> Option<&NodeKind> {
+        self.kind.as_ref()
+    }
+
+    pub fn is_external(&self) -> bool {
+        self.external
+    }
+
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
 }
 
+#[derive(Debug, Clone)]
 pub struct Edge {
-    id: EdgeId,
-    from: NodeId,
-    to: NodeId,
-    kind: EdgeKind,
-    subgraph: SubgraphKind,
-    state: EdgeState,
-    counter: Counter,
+    pub(crate) id: EdgeId,
+    pub(crate) from: NodeId,
+    pub(crate) to: NodeId,
+    pub(crate) kind: EdgeKind,
+    pub(crate) subgraph: SubgraphKind,
+    pub(crate) state: EdgeState,
+    pub(crate) counter: Counter,
+    pub(crate) confidence: Option<f32>, //None = static fact, Some(c) = sampled from dynamic analysis
+    pub(crate) description: Option<String>, //e.g. why a dependency is allowed
+    pub(crate) pinned: Option<EdgeState>, //externally known state, wins over analyze (assert_edge_state)
+    pub(crate) min_support: Option<Counter>, //spec edges: fewest impl edges allowed to realize it
+    pub(crate) max_support: Option<Counter>, //spec edges: most impl edges allowed to realize it
+    pub(crate) variant: Option<String>, //spec edges: architecture variant ("current", "target"), None = all
+    pub(crate) first_seen: Option<u64>, //tick of the first run that observed the edge (observe_edge)
+    pub(crate) last_seen: Option<u64>, //tick of the latest run that observed it
+    pub(crate) symmetric: bool, //spec edges: realized by impl dependencies in either direction
+    pub(crate) test_only: bool, //impl edges: the dependency only exists in test code
 }
 
-pub struct ReflexionGraph {
-    nodes: HashMap<NodeId, Node>,
-    edges: HashMap<EdgeId, Edge>,
-    impl_out: HashMap<NodeId, Vec<EdgeId>>,
-    arch_out: HashMap<NodeId, Vec<EdgeId>>,
-    pub maps_to: HashMap<NodeId, NodeId>,
-    propagation_table: HashMap<EdgeId, HashSet<EdgeId>>, //arc/propagated edge -> impl edges
-    next_node_id: NodeId,
-    next_edge_id: EdgeId,
+impl Edge {
+    pub fn new(from: NodeId, to: NodeId, kind: EdgeKind, subgraph: SubgraphKind) -> Self {
+        Self {
+            id: 0, // overwritten by add_edge
+            from,
+            to,
+            kind,
+            subgraph,
+            state: EdgeState::Undefined,
+            counter: 0,
+            confidence: None,
+            pinned: None,
+            min_support: None,
+            max_support: None,
+            variant: None,
+            first_seen: None,
+            last_seen: None,
+            symmetric: false,
+            test_only: false,
+            description: None,
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    //confidence in [0, 1] for edges observed in runtime traces instead of static analysis
+    pub fn with_confidence(mut self, confidence: f32) -> Self {
+        self.confidence = Some(confidence);
+        self
+    }
+
+    //multiplicity constraint for spec edges ("at least n implementations"). see classify.
+    pub fn with_min_support(mut self, min: Counter) -> Self {
+        self.min_support = Some(min);
+        self
+    }
+
+    //multiplicity constraint for spec edges ("at most n implementations"). see classify.
+    pub fn with_max_support(mut self, max: Counter) -> Self {
+        self.max_support = Some(max);
+        self
+    }
+
+    //spec edges: only take part when analyzing against this variant (see analyze_against)
+    pub fn with_variant(mut self, variant: impl Into<String>) -> Self {
+        self.variant = Some(variant.into());
+        self
+    }
+
+    //spec edges: "A communicates_with B" in both directions. impl dependencies from B to A
+    //converge on it too (see matching_spec_edges and asymmetric_realizations)
+    pub fn with_symmetric(mut self) -> Self {
+        self.symmetric = true;
+        self
+    }
+
+    //impl edges: tag a dependency that originates in test code (see exclude_test_only
+    //and test_divergences)
+    pub fn with_test_only(mut self) -> Self {
+        self.test_only = true;
+        self
+    }
+
+    pub fn id(&self) -> EdgeId {
+        self.id
+    }
+
+    pub fn from(&self) -> NodeId {
+        self.from
+    }
+
+    pub fn to(&self) -> NodeId {
+        self.to
+    }
+
+    pub fn kind(&self) -> &EdgeKind {
+        &self.kind
+    }
+
+    pub fn subgraph(&self) -> SubgraphKind {
+        self.subgraph
+    }
+
+    pub fn state(&self) -> EdgeState {
+        self.state
+    }
+
+    pub fn counter(&self) -> Counter {
+        self.counter
+    }
+
+    pub fn confidence(&self) -> Option<f32> {
+        self.confidence
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn min_support(&self) -> Option<Counter> {
+        self.min_support
+    }
+
+    pub fn max_support(&self) -> Option<Counter> {
+        self.max_support
+    }
+
+    pub fn variant(&self) -> Option<&str> {
+        self.variant.as_deref()
+    }
+
+    pub fn first_seen(&self) -> Option<u64> {
+        self.first_seen
+    }
+
+    pub fn last_seen(&self) -> Option<u64> {
+        self.last_seen
+    }
+
+    pub fn is_symmetric(&self) -> bool {
+        self.symmetric
+    }
+
+    pub fn is_test_only(&self) -> bool {
+        self.test_only
+    }
+
+    //state pinned by assert_edge_state, if any
+    pub fn pinned_state(&self) -> Option<EdgeState> {
+        self.pinned
+    }
 }
 
-impl ReflexionGraph {
-    pub fn new() -> Self {
+//the graph is generic over its node/edge storage (see GraphStore). construction,
+//mappings, the analysis passes and the result queries work for every store; removals,
+//reports and io still use the default HashMapStore.
+#[derive(Debug, Clone)]
+pub struct ReflexionGraph<S = HashMapStore> {
+    pub(crate) store: S, //nodes, edges and their out-adjacency
+    pub(crate) maps_to: HashMap<NodeId, NodeId>, //impl -> arch; writes keep arch_to_impls in sync, see put_mapping
+    pub(crate) arch_to_impls: HashMap<NodeId, Vec<NodeId>>, //inverse of maps_to, sorted lists (see implementors)
+    pub(crate) proposed: HashSet<NodeId>, //impl nodes whose maps_to entry is only Proposed
+    pub(crate) last_options: AnalysisOptions, //options of the last analyze (mapping resolution)
+    pub(crate) propagation_table: HashMap<EdgeId, HashSet<EdgeId>>, //arc/propagated edge -> impl edges
+    pub(crate) ambiguous_lifts: HashMap<EdgeId, Vec<EdgeId>>, //propagated edge -> all matching spec edges (>1)
+    pub(crate) integrity_errors: Vec<GraphError>, //dangling references met by the last analyze
+    pub(crate) structural_kinds: HashSet<EdgeKind>, //structure, not dependency: skipped by classification
+    pub(crate) propagation_boundaries: HashSet<NodeKind>, //containment climbs (spec matching, inherit_mappings) stop at nodes of these kinds
+    pub(crate) layers: HashMap<NodeId, u32>, //arch node -> layer (0 = bottom), see assign_layer
+    pub(crate) groups: HashMap<NodeId, String>, //node -> reporting group (e.g. owning team), see set_group
+    pub(crate) api_boundaries: HashSet<NodeId>, //arch nodes that are the public API of their parent module
+    pub(crate) severities: HashMap<EdgeKind, Severity>, //violation severity per kind, missing = Error
+    pub(crate) active_variant: Option<String>, //variant the last analyze ran against, None = all spec edges
+    pub(crate) analyzed_kinds: Option<HashSet<EdgeKind>>, //kind filter of the last run (analyze_kinds), None = all
+    pub(crate) metadata: HashMap<String, String>, //provenance tags (commit, extractor version, ...), see set_meta
+    #[cfg(feature = "regex")]
+    pub(crate) kind_pattern: Option<KindPattern>, //strict mode: add_edge only accepts matching kinds
+    //edge ids in the order they were added. removed ids linger until prune_insertion_order
+    //drops them, so this costs one EdgeId per edge, at most twice over
+    pub(crate) insertion_order: Vec<EdgeId>,
+    pub(crate) next_node_id: NodeId,
+    pub(crate) next_edge_id: EdgeId,
+    phase: Phase, //how far the states are along the analysis, see Phase
+}
+
+//old id -> new id, returned by compact so external references can be updated
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdRemap {
+    pub nodes: HashMap<NodeId, NodeId>,
+    pub edges: HashMap<EdgeId, EdgeId>,
+}
+
+impl Default for ReflexionGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: GraphStore> ReflexionGraph<S> {
+    //graph over a custom store, which may already hold nodes and edges: fresh ids
+    //continue after the highest id found in it
+    pub fn with_store(store: S) -> Self {
+        let next_node_id = store.iter_nodes().map(|n| n.id).max().unwrap_or(0) + 1;
+        let next_edge_id = store.iter_edges().map(|e| e.id).max().unwrap_or(0) + 1;
+        let mut insertion_order: Vec<EdgeId> = store.iter_edges().map(|e| e.id).collect();
+        insertion_order.sort_unstable();
         Self {
-            nodes: HashMap::new(),
-            edges: HashMap::new(),
-            impl_out: HashMap::new(),
-            arch_out: HashMap::new(),
+            store,
             maps_to: HashMap::new(),
+            arch_to_impls: HashMap::new(),
+            proposed: HashSet::new(),
+            last_options: AnalysisOptions::default(),
             propagation_table: HashMap::new(), //arc/propagated edge -> impl edges
-            next_node_id: 1, 
-            next_edge_id: 1,
+            ambiguous_lifts: HashMap::new(),
+            integrity_errors: Vec::new(),
+            structural_kinds: [EdgeKind::contains()].into_iter().collect(),
+            propagation_boundaries: HashSet::new(),
+            layers: HashMap::new(),
+            groups: HashMap::new(),
+            api_boundaries: HashSet::new(),
+            severities: HashMap::new(),
+            active_variant: None,
+            analyzed_kinds: None,
+            metadata: HashMap::new(),
+            #[cfg(feature = "regex")]
+            kind_pattern: None,
+            insertion_order,
+            next_node_id,
+            next_edge_id,
+            phase: Phase::Built,
         }
     }
 
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    //free-form tag on the whole graph, e.g. ("commit", "4f2a9c1") or ("extractor", "rustdeps 0.3"),
+    //so stored results say what produced them. returns the previous value. tags don't
+    //take part in the analysis, so setting one keeps the results.
+    pub fn set_meta(&mut self, key: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        self.metadata.insert(key.into(), value.into())
+    }
+
+    pub fn get_meta(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).map(String::as_str)
+    }
+
+    //all tags, sorted by key
+    pub fn meta_iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        let mut tags: Vec<(&str, &str)> = self.metadata.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        tags.sort_unstable();
+        tags.into_iter()
+    }
+
+    //no nodes (and therefore no edges or mappings)
+    pub fn is_empty(&self) -> bool {
+        self.store.node_count() == 0
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&Node> {
+        self.store.get_node(id)
+    }
+
+    pub fn edge(&self, id: EdgeId) -> Option<&Edge> {
+        self.store.get_edge(id)
+    }
+
+    //edge / node behind an id taken from the graph's own records (adjacency lists,
+    //propagation_table, ...); a missing one is a bug, hence the panic
+    pub(crate) fn edge_at(&self, id: EdgeId) -> &Edge {
+        self.store.get_edge(id).expect("edge id from the graph's own records")
+    }
+
+    pub(crate) fn node_at(&self, id: NodeId) -> &Node {
+        self.store.get_node(id).expect("node id from the graph's own records")
+    }
+
+    //name of a node for reports, "?" if the id is unknown
+    pub(crate) fn node_name(&self, id: NodeId) -> &str {
+        self.store.get_node(id).map(|n| n.name.as_str()).unwrap_or("?")
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &Node> + '_ {
+        self.store.iter_nodes()
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = &Edge> + '_ {
+        self.store.iter_edges()
+    }
+
+    //ad-hoc queries ("calls edges in Implementation with counter > 3"), sorted ids
+    pub fn filter_edges<F: Fn(&Edge) -> bool>(&self, pred: F) -> Vec<EdgeId> {
+        let mut ids: Vec<EdgeId> = self.store.iter_edges().filter(|e| pred(e)).map(|e| e.id).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    pub fn filter_nodes<F: Fn(&Node) -> bool>(&self, pred: F) -> Vec<NodeId> {
+        let mut ids: Vec<NodeId> = self.store.iter_nodes().filter(|n| pred(n)).map(|n| n.id).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    //every edge in the order it was added (extractor order, propagated edges in the order
+    //analyze synthesized them), from insertion_order. edges a store held before with_store
+    //come first, by id; compact and the binary format keep the order.
+    pub fn edges_in_insertion_order(&self) -> Vec<EdgeId> {
+        self.insertion_order.iter().copied().filter(|&id| self.store.get_edge(id).is_some()).collect()
+    }
+
+    //sorted ids of all edges in one subgraph
+    pub fn edges_in_subgraph(&self, subgraph: SubgraphKind) -> Vec<EdgeId> {
+        self.filter_edges(|e| e.subgraph == subgraph)
+    }
+
+    //edges_in_subgraph into a reused buffer
+    pub(crate) fn edges_in_subgraph_into(&self, subgraph: SubgraphKind, out: &mut Vec<EdgeId>) {
+        out.clear();
+        out.extend(self.store.iter_edges().filter(|e| e.subgraph == subgraph).map(|e| e.id));
+        out.sort_unstable();
+    }
+
+    //sorted ids of all nodes in one subgraph
+    pub fn nodes_in_subgraph(&self, subgraph: SubgraphKind) -> Vec<NodeId> {
+        self.filter_nodes(|n| n.subgraph == subgraph)
+    }
+
+    //true once analyze has run and nothing changed since: only then are edge states
+    //meaningful (a freshly built or modified graph still shows defaults or stale verdicts)
+    pub fn is_analyzed(&self) -> bool {
+        self.phase == Phase::Analyzed
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    //guard for callers driving the pipeline step by step: Err(PhaseTooEarly) unless the
+    //graph got at least as far as `min` (and nothing changed since)
+    pub fn require_phase(&self, min: Phase) -> Result<(), GraphError> {
+        if self.phase >= min {
+            Ok(())
+        } else {
+            Err(GraphError::PhaseTooEarly { required: min, actual: self.phase })
+        }
+    }
+
+    pub(crate) fn set_phase(&mut self, phase: Phase) {
+        self.phase = phase;
+    }
+
+    pub(crate) fn set_analyzed(&mut self) {
+        self.set_phase(Phase::Analyzed);
+    }
+
+    //every mutation of nodes, edges or mappings makes earlier results stale
+    pub(crate) fn invalidate(&mut self) {
+        self.phase = Phase::Built;
+    }
+
     pub fn node_subgraph(&self, node: NodeId) -> Result<SubgraphKind, GraphError> {
-        self.nodes
-            .get(&node)
+        self.store
+            .get_node(node)
             .map(|n| n.subgraph)
             .ok_or(GraphError::NodeNotFound(node))
     }
 
+    //side of the model of a bare id, None if unknown (node_subgraph errs instead)
+    pub fn subgraph_of_node(&self, node: NodeId) -> Option<SubgraphKind> {
+        self.store.get_node(node).map(|n| n.subgraph)
+    }
+
+    pub fn subgraph_of_edge(&self, edge: EdgeId) -> Option<SubgraphKind> {
+        self.store.get_edge(edge).map(|e| e.subgraph)
+    }
+
     pub fn fresh_node_id(&mut self) -> NodeId {
         let id = self.next_node_id;
         self.next_node_id += 1;
@@ -134,65 +589,81 @@ impl ReflexionGraph {
 
     pub fn add_node(&mut self, mut node: Node) -> Result<NodeId, GraphError> {
         //if parent is specified, it must already exist
-        if let Some(parent_id) = node.parent && !self.nodes.contains_key(&parent_id) {
+        if let Some(parent_id) = node.parent && self.store.get_node(parent_id).is_none() {
             return Err(GraphError::ParentNotFound(parent_id));
         }
 
         //now graph owns identity, assign fresh IDs
         let id = self.fresh_node_id();
         node.id = id;
+        let parent = node.parent;
 
         //insert node
-        self.nodes.insert(id,node);
+        self.store.insert_node(node);
+        self.invalidate();
 
         //update parent's children list if needed 
-        if let Some(parent_id) = self.nodes.get(&id).and_then(|n| n.parent) {
-            self.nodes.get_mut(&parent_id).expect("Checked Above").children.push(id);
+        if let Some(parent_id) = parent {
+            self.store.get_node_mut(parent_id).expect("Checked Above").children.push(id);
         }
 
+        self.debug_assert_local(&[id], &[]);
         Ok(id)
     }
 
     pub fn add_edge(&mut self, mut edge: Edge) -> Result<EdgeId, GraphError> {
         //validate that there is a source and destination (from/to edges)
-        if !self.nodes.contains_key(&edge.from) {
+        if self.store.get_node(edge.from).is_none() {
             return Err(GraphError::NodeNotFound(edge.from));
         }
 
-        if !self.nodes.contains_key(&edge.to) {
+        if self.store.get_node(edge.to).is_none() {
             return Err(GraphError::NodeNotFound(edge.to));
         }
 
+        #[cfg(feature = "regex")]
+        if self.kind_pattern.as_ref().is_some_and(|p| !p.is_match(edge.kind.as_str())) {
+            return Err(GraphError::InvalidEdgeKind);
+        }
+
         //now graph owns identity, assign fresh IDs
         let id = self.fresh_edge_id();
         edge.id = id;
 
-        //insert edge
-        self.edges.insert(id, edge);
-
-        //update adjacency list based on subgraph
-        let edge_ref = self.edges.get(&id).expect("Just Inserted");
-
-        match edge_ref.subgraph {
-            SubgraphKind::Implementation => {
-                self.impl_out.entry(edge_ref.from).or_default().push(id);
-            } 
-            SubgraphKind::Architecture | SubgraphKind::Propagated => {
-                self.arch_out.entry(edge_ref.from).or_default().push(id);
-            }
-        }
+        //insert edge (the store updates the adjacency list of the edge's side)
+        self.store.insert_edge(edge);
+        self.insertion_order.push(id);
+        self.invalidate();
 
+        self.debug_assert_local(&[], &[id]);
         Ok(id)
     }
 
+    //first outgoing arch/propagated edge of `from` going to `to` with the given kind and subgraph
+    pub(crate) fn find_arch_edge(&self, from: NodeId, to: NodeId, kind: &EdgeKind, subgraph: SubgraphKind) -> Option<EdgeId> {
+        self.store.out_edges(from, subgraph).iter().copied().find(|&eid| {
+            let e = self.edge_at(eid);
+            e.to == to && e.subgraph == subgraph && &e.kind == kind
+        })
+    }
+
     //Prepare the graph for a fresh reflexion analysis and run:
     // - Arch edges: Specified, Counter=0
     // - Impl edges: Undefined, Counter=0
     // - Propagated edges: Undefined, Counter=0
+    // - pinned edges (assert_edge_state): their pinned state
     // - Propagation_table cleared
     pub fn init_states(&mut self) {
-        for edge in self.edges.values_mut() {
+        self.init_states_with(&mut Scratch::default());
+    }
 
+    //init_states handing the emptied propagation_table sets to `scratch` for reuse
+    pub(crate) fn init_states_with(&mut self, scratch: &mut Scratch) {
+        self.invalidate();
+        scratch.ids.clear();
+        scratch.ids.extend(self.store.iter_edges().map(|e| e.id));
+        for &eid in &scratch.ids {
+            let edge = self.store.get_edge_mut(eid).expect("listed above");
             edge.counter = 0;
 
             match edge.subgraph {
@@ -203,43 +674,361 @@ impl ReflexionGraph {
                     edge.state = EdgeState::Undefined;
                 }
             }
+            if let Some(pinned) = edge.pinned {
+                edge.state = pinned;
+            }
+        }
+        for (_, support) in self.propagation_table.drain() {
+            scratch.recycle(support);
+        }
+        self.ambiguous_lifts.clear();
+        self.integrity_errors.clear();
+        self.set_phase(Phase::Initialized);
+    }
+
+    //drop the ids of removed edges from insertion_order once they are half of it: removals
+    //stay O(1) amortized and the list at most twice the edge count
+    pub(crate) fn prune_insertion_order(&mut self) {
+        if self.insertion_order.len() > 2 * self.store.edge_count() {
+            let store = &self.store;
+            self.insertion_order.retain(|&id| store.get_edge(id).is_some());
         }
-        self.propagation_table.clear();
     }
 
     // Optional helper for future incremental modes:
     // remove all propagated edges from the graph.
     //
     // NOTE: This intentionally only removes edges and their adjacency references
-    // for impl_out/arch_out. If you later add more adjacency indexes, update here too.
+    // (GraphStore::remove_edge). If you later add more indexes, update here too.
     pub fn clear_propagated_edges(&mut self) {
+        self.clear_propagated_edges_with(&mut Scratch::default());
+    }
+
+    pub(crate) fn clear_propagated_edges_with(&mut self, scratch: &mut Scratch) {
         // collect first to avoid borrowing issues while removing
-        let to_remove: Vec<EdgeId> = self
-            .edges
-            .iter()
-            .filter_map(|(id, e)| {
-                if matches!(e.subgraph, SubgraphKind::Propagated) {
-                    Some(*id)
-                } else {
-                    None
+        scratch.ids.clear();
+        scratch.ids.extend(self.store.iter_edges().filter(|e| e.subgraph == SubgraphKind::Propagated).map(|e| e.id));
+        let mut sources = Vec::new();
+
+        for &eid in &scratch.ids {
+            //the store drops it from the adjacency lists too
+            if let Some(e) = self.store.remove_edge(eid) {
+                self.invalidate();
+                if cfg!(debug_assertions) {
+                    sources.push(e.from);
                 }
-            })
-            .collect();
 
-        for eid in to_remove {
-            if let Some(e) = self.edges.remove(&eid) {
-                // remove from adjacency lists
-                if let Some(v) = self.impl_out.get_mut(&e.from) {
-                    v.retain(|&x| x != eid);
+                // remove any propagation bookkeeping referencing this edge id
+                if let Some(mut support) = self.propagation_table.remove(&eid) {
+                    support.clear();
+                    scratch.support_sets.push(support);
                 }
-                if let Some(v) = self.arch_out.get_mut(&e.from) {
-                    v.retain(|&x| x != eid);
+                self.ambiguous_lifts.remove(&eid);
+            }
+        }
+        self.prune_insertion_order();
+        sources.sort_unstable();
+        sources.dedup();
+        self.debug_assert_local(&sources, &[]);
+    }
+}
+
+impl ReflexionGraph {
+    pub fn new() -> Self {
+        Self::with_store(HashMapStore::default())
+    }
+
+    //reset to an empty graph for reuse, keeping the allocations and the configuration
+    //(structural kinds, propagation boundaries, severities). ids restart at 1: every id handed out before is invalid.
+    pub fn clear(&mut self) {
+        self.store.nodes.clear();
+        self.store.edges.clear();
+        self.store.impl_out.clear();
+        self.store.arch_out.clear();
+        self.maps_to.clear();
+        self.arch_to_impls.clear();
+        self.proposed.clear();
+        self.propagation_table.clear();
+        self.ambiguous_lifts.clear();
+        self.integrity_errors.clear();
+        self.layers.clear();
+        self.groups.clear();
+        self.api_boundaries.clear();
+        self.last_options = AnalysisOptions::default();
+        self.active_variant = None;
+        self.analyzed_kinds = None;
+        self.metadata.clear();
+        self.insertion_order.clear();
+        self.next_node_id = 1;
+        self.next_edge_id = 1;
+        self.invalidate();
+    }
+
+    //stable breadth-first order over the nodes of one side of the model (Propagated
+    //walks architecture nodes along propagated edges): start at the parentless nodes,
+    //from each node visit its children, then the targets of its outgoing edges in that
+    //subgraph, every group ordered by name (then id). nodes unreachable that way follow
+    //at the end, by name. for exporters and tree views that must not depend on HashMap order.
+    pub fn bfs_order(&self, subgraph: SubgraphKind) -> Vec<NodeId> {
+        let node_side = match subgraph {
+            SubgraphKind::Propagated => SubgraphKind::Architecture,
+            other => other,
+        };
+        let by_name = |ids: &mut Vec<NodeId>| ids.sort_unstable_by(|a, b| self.store.nodes[a].name.cmp(&self.store.nodes[b].name).then(a.cmp(b)));
+
+        let mut all: Vec<NodeId> = self.store.nodes.values().filter(|n| n.subgraph == node_side).map(|n| n.id).collect();
+        by_name(&mut all);
+        let roots: Vec<NodeId> = all.iter().copied().filter(|id| self.store.nodes[id].parent.is_none()).collect();
+
+        let mut seen: HashSet<NodeId> = HashSet::new();
+        let mut order = Vec::with_capacity(all.len());
+        //all roots seeded at once, so the walk goes level by level across the trees
+        let mut queue: VecDeque<NodeId> = roots.into_iter().filter(|&r| seen.insert(r)).collect();
+        let mut leftovers = all.into_iter();
+        loop {
+            if queue.is_empty() {
+                match leftovers.find(|&n| seen.insert(n)) {
+                    Some(n) => queue.push_back(n),
+                    None => break,
                 }
+            }
+            while let Some(id) = queue.pop_front() {
+                order.push(id);
+                let node = &self.store.nodes[&id];
+                let mut children: Vec<NodeId> = node.children.iter().copied().filter(|c| self.store.nodes.contains_key(c)).collect();
+                let out = match subgraph {
+                    SubgraphKind::Implementation => self.store.impl_out.get(&id),
+                    _ => self.store.arch_out.get(&id),
+                };
+                let mut targets: Vec<NodeId> = out
+                    .into_iter()
+                    .flatten()
+                    .map(|eid| &self.store.edges[eid])
+                    .filter(|e| e.subgraph == subgraph && self.store.nodes.contains_key(&e.to))
+                    .map(|e| e.to)
+                    .collect();
+                by_name(&mut children);
+                by_name(&mut targets);
+                for next in children.into_iter().chain(targets) {
+                    if seen.insert(next) {
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        order
+    }
 
-                // remove any propagation bookkeeping referencing this edge id
-                self.propagation_table.remove(&eid);
+    //dry run for model editing: would an edge from -> to in `subgraph` close a dependency
+    //cycle, i.e. does `to` already reach `from` there (from == to counts)? depth-first from
+    //`to`, visiting every node at most once; structural kinds are not dependencies and
+    //are not followed. false for unknown nodes. nothing is modified.
+    pub fn would_create_cycle(&self, from: NodeId, to: NodeId, subgraph: SubgraphKind) -> bool {
+        if !self.store.nodes.contains_key(&from) || !self.store.nodes.contains_key(&to) {
+            return false;
+        }
+        let mut seen: HashSet<NodeId> = HashSet::from([to]);
+        let mut stack = vec![to];
+        while let Some(n) = stack.pop() {
+            if n == from {
+                return true;
             }
+            for &eid in self.store.out_edges(n, subgraph) {
+                let e = &self.store.edges[&eid];
+                if e.subgraph == subgraph && !self.is_structural(&e.kind) && seen.insert(e.to) {
+                    stack.push(e.to);
+                }
+            }
+        }
+        false
+    }
+
+    //vocabulary cleanup: every edge of kind `from` gets kind `to` (e.g. "call" -> "calls"),
+    //returns how many changed. edges that now share endpoints and kind with another one
+    //stay separate (there is no dedup pass; analysis counts parallel edges as separate support).
+    //InvalidEdgeKind, with nothing renamed, for a blank `to` or one outside the kind pattern.
+    pub fn rename_edge_kind(&mut self, from: &EdgeKind, to: EdgeKind) -> Result<usize, GraphError> {
+        if to.is_blank() {
+            return Err(GraphError::InvalidEdgeKind);
+        }
+        #[cfg(feature = "regex")]
+        if self.kind_pattern.as_ref().is_some_and(|p| !p.is_match(to.as_str())) {
+            return Err(GraphError::InvalidEdgeKind);
+        }
+        let mut renamed = Vec::new();
+        for e in self.store.edges.values_mut().filter(|e| &e.kind == from) {
+            e.kind = to.clone();
+            renamed.push(e.id);
+        }
+        if !renamed.is_empty() && *from != to {
+            self.invalidate();
         }
+        self.debug_assert_local(&[], &renamed);
+        Ok(renamed.len())
+    }
+
+    //remove an edge with all its adjacency and propagation bookkeeping
+    pub fn remove_edge(&mut self, id: EdgeId) -> Result<Edge, GraphError> {
+        let edge = self.store.edges.remove(&id).ok_or(GraphError::EdgeNotFound(id))?;
+        self.invalidate();
+
+        let out = match edge.subgraph {
+            SubgraphKind::Implementation => &mut self.store.impl_out,
+            SubgraphKind::Architecture | SubgraphKind::Propagated => &mut self.store.arch_out,
+        };
+        if let Some(v) = out.get_mut(&edge.from) {
+            v.retain(|&x| x != id);
+        }
+
+        //as key (arch/propagated edge) and as supporting impl edge: the edges it was counted
+        //on lose that count, see withdraw_support
+        self.propagation_table.remove(&id);
+        self.withdraw_support(id);
+        self.ambiguous_lifts.remove(&id);
+        for candidates in self.ambiguous_lifts.values_mut() {
+            candidates.retain(|&x| x != id);
+        }
+
+        self.prune_insertion_order();
+        self.debug_assert_local(&[edge.from], &[]);
+        Ok(edge)
+    }
+
+    //remove a node together with every edge touching it and every mapping from/to it.
+    //its children are detached and become roots.
+    pub fn remove_node(&mut self, id: NodeId) -> Result<Node, GraphError> {
+        if !self.store.nodes.contains_key(&id) {
+            return Err(GraphError::NodeNotFound(id));
+        }
+        self.invalidate();
+
+        let mut incident: Vec<EdgeId> = self
+            .store
+            .edges
+            .values()
+            .filter(|e| e.from == id || e.to == id)
+            .map(|e| e.id)
+            .collect();
+        incident.sort_unstable();
+        for eid in incident {
+            self.remove_edge(eid).expect("collected above");
+        }
+        self.store.impl_out.remove(&id);
+        self.store.arch_out.remove(&id);
+
+        self.take_mapping(id);
+        for i in self.arch_to_impls.remove(&id).unwrap_or_default() {
+            self.maps_to.remove(&i);
+        }
+        self.proposed.retain(|i| self.maps_to.contains_key(i));
+        self.layers.remove(&id);
+        self.groups.remove(&id);
+        self.api_boundaries.remove(&id);
+
+        let node = self.store.nodes.remove(&id).expect("checked above");
+        if let Some(parent) = node.parent.and_then(|p| self.store.nodes.get_mut(&p)) {
+            parent.children.retain(|&c| c != id);
+        }
+        for child in &node.children {
+            if let Some(c) = self.store.nodes.get_mut(child) {
+                c.parent = None;
+            }
+        }
+
+        let mut touched = vec![id];
+        touched.extend(node.parent);
+        touched.extend(&node.children);
+        self.debug_assert_local(&touched, &[]);
+        Ok(node)
+    }
+
+    //maintenance for long-lived graphs: after many removals the id space is sparse.
+    //reassigns dense ids from 1 (keeping the relative order of the old ids), rebuilds
+    //every index, maps_to and propagation_table, and returns old -> new ids.
+    //all ids handed out before are invalid afterwards, translate them through the remap.
+    pub fn compact(&mut self) -> IdRemap {
+        let mut node_ids: Vec<NodeId> = self.store.nodes.keys().copied().collect();
+        let mut edge_ids: Vec<EdgeId> = self.store.edges.keys().copied().collect();
+        node_ids.sort_unstable();
+        edge_ids.sort_unstable();
+
+        let remap = IdRemap {
+            nodes: node_ids.iter().zip(1..).map(|(&old, new)| (old, new)).collect(),
+            edges: edge_ids.iter().zip(1..).map(|(&old, new)| (old, new)).collect(),
+        };
+        let n = |id: &NodeId| remap.nodes[id];
+        let e = |id: &EdgeId| remap.edges[id];
+
+        let mut nodes = HashMap::with_capacity(self.store.nodes.len());
+        for (_, mut node) in self.store.nodes.drain() {
+            node.id = n(&node.id);
+            node.parent = node.parent.map(|p| n(&p));
+            node.children = node.children.iter().map(n).collect();
+            nodes.insert(node.id, node);
+        }
+        self.store.nodes = nodes;
+
+        let mut edges = HashMap::with_capacity(self.store.edges.len());
+        for old in edge_ids {
+            let mut edge = self.store.edges.remove(&old).expect("listed above");
+            edge.id = e(&old);
+            edge.from = n(&edge.from);
+            edge.to = n(&edge.to);
+            edges.insert(edge.id, edge);
+        }
+        self.store.edges = edges;
+        self.rebuild_indexes();
+        self.insertion_order = self.insertion_order.iter().filter_map(|id| remap.edges.get(id).copied()).collect();
+
+        //mappings to nodes that no longer exist (a damaged binary cache) are dropped
+        self.maps_to = self
+            .maps_to
+            .iter()
+            .filter_map(|(i, a)| Some((*remap.nodes.get(i)?, *remap.nodes.get(a)?)))
+            .collect();
+        self.rebuild_arch_to_impls();
+        self.proposed = self.proposed.iter().filter_map(|i| remap.nodes.get(i).copied()).collect();
+        self.layers = self.layers.iter().filter_map(|(a, &l)| Some((*remap.nodes.get(a)?, l))).collect();
+        self.api_boundaries = self.api_boundaries.iter().filter_map(|n| remap.nodes.get(n).copied()).collect();
+        self.groups = self.groups.drain().filter_map(|(n, group)| Some((*remap.nodes.get(&n)?, group))).collect();
+        self.propagation_table = self
+            .propagation_table
+            .iter()
+            .map(|(k, support)| (e(k), support.iter().map(e).collect()))
+            .collect();
+        self.ambiguous_lifts = self
+            .ambiguous_lifts
+            .iter()
+            .map(|(k, candidates)| (e(k), candidates.iter().map(e).collect()))
+            .collect();
+
+        self.next_node_id = self.store.nodes.len() as NodeId + 1;
+        self.next_edge_id = self.store.edges.len() as EdgeId + 1;
+        self.debug_assert_all();
+        remap
+    }
+
+    //recompute impl_out/arch_out from the edges (adjacency lists in edge id order),
+    //for code that rewrites or loads `edges` wholesale
+    pub(crate) fn rebuild_indexes(&mut self) {
+        self.store.impl_out.clear();
+        self.store.arch_out.clear();
+        for eid in self.edges_sorted() {
+            let edge = &self.store.edges[&eid];
+            match edge.subgraph {
+                SubgraphKind::Implementation => self.store.impl_out.entry(edge.from).or_default().push(eid),
+                SubgraphKind::Architecture | SubgraphKind::Propagated => {
+                    self.store.arch_out.entry(edge.from).or_default().push(eid)
+                }
+            }
+        }
+    }
+
+    //every edge id, ascending
+    fn edges_sorted(&self) -> Vec<EdgeId> {
+        let mut ids: Vec<EdgeId> = self.store.edges.keys().copied().collect();
+        ids.sort_unstable();
+        ids
     }
 }
 
@@ -248,7 +1037,8 @@ mod tests {
     use super::*;
     use crate::core::types::{EdgeKind, SubgraphKind};
     use crate::core::state::EdgeState;
-    use std::collections::{HashMap, HashSet};
+    use crate::core::classify::AnalysisOptions;
+    use std::collections::HashSet;
 
     fn mk_node(name: &str, subgraph: SubgraphKind, parent: Option<NodeId>) -> Node {
         Node {
@@ -257,6 +1047,10 @@ mod tests {
             subgraph,
             parent,
             children: vec![],
+            description: None,
+            kind: None,
+            external: false,
+            location: None,
         }
     }
 
@@ -269,6 +1063,16 @@ mod tests {
             subgraph,
             state: EdgeState::Undefined,
             counter: 0,
+            confidence: None,
+            pinned: None,
+            min_support: None,
+            max_support: None,
+            variant: None,
+            first_seen: None,
+            last_seen: None,
+            symmetric: false,
+            test_only: false,
+            description: None,
         }
     }
 
@@ -282,11 +1086,11 @@ mod tests {
             .unwrap();
 
         // parent exists and has the child
-        let parent = g.nodes.get(&parent_id).unwrap();
+        let parent = g.store.nodes.get(&parent_id).unwrap();
         assert!(parent.children.contains(&child_id));
 
         // child points to parent
-        let child = g.nodes.get(&child_id).unwrap();
+        let child = g.store.nodes.get(&child_id).unwrap();
         assert_eq!(child.parent, Some(parent_id));
     }
 
@@ -301,6 +1105,45 @@ mod tests {
         assert_eq!(err, GraphError::ParentNotFound(999));
     }
 
+    #[test]
+    fn graph_error_variants_stay_copy_and_comparable() {
+        fn assert_traits<T: Copy + Clone + PartialEq + Eq + std::fmt::Debug>() {}
+        assert_traits::<GraphError>();
+
+        //one of each variant, extend when adding variants
+        let all = [
+            GraphError::ParentNotFound(1),
+            GraphError::NodeNotFound(1),
+            GraphError::EdgeNotFound(1),
+            GraphError::WrongSubgraph {
+                node: 1,
+                expected: SubgraphKind::Architecture,
+                found: SubgraphKind::Implementation,
+            },
+            GraphError::MappingAlreadyExists { impl_node: 1, old_arch: 2, new_arch: 3 },
+            GraphError::ImplNodeAlreadyMapped(1),
+            GraphError::InvalidEdgeKind,
+            GraphError::HierarchyCycle { child: 1, parent: 2 },
+            GraphError::NoEdgeBetween { from: 1, to: 2 },
+            GraphError::SubgraphMismatch {
+                edge_subgraph: SubgraphKind::Propagated,
+                node: 1,
+                found: SubgraphKind::Implementation,
+            },
+            GraphError::NotAnalyzed,
+            GraphError::PhaseTooEarly { required: Phase::Analyzed, actual: Phase::Built },
+        ];
+
+        for (i, a) in all.iter().enumerate() {
+            for (j, b) in all.iter().enumerate() {
+                assert_eq!(i == j, a == b, "{} vs {}", a, b);
+            }
+        }
+
+        let copied = all[0];
+        assert_eq!(copied, GraphError::ParentNotFound(1));
+    }
+
     #[test]
     fn add_edge_updates_correct_adjacency_map() {
         let mut g = ReflexionGraph::new();
@@ -321,11 +1164,11 @@ mod tests {
             .unwrap();
 
         // arch_out contains e_arch at a1
-        let arch_out = g.arch_out.get(&a1).unwrap();
+        let arch_out = g.store.arch_out.get(&a1).unwrap();
         assert!(arch_out.contains(&e_arch));
 
         // impl_out contains e_impl at i1
-        let impl_out = g.impl_out.get(&i1).unwrap();
+        let impl_out = g.store.impl_out.get(&i1).unwrap();
         assert!(impl_out.contains(&e_impl));
     }
 
@@ -344,6 +1187,209 @@ mod tests {
     }
 
 
+    #[test]
+    fn remove_edge_cleans_adjacency_and_propagation_table() {
+        let mut g = ReflexionGraph::new();
+        let i1 = g.add_node(mk_node("Impl1", SubgraphKind::Implementation, None)).unwrap();
+        let i2 = g.add_node(mk_node("Impl2", SubgraphKind::Implementation, None)).unwrap();
+        let e = g
+            .add_edge(mk_edge(i1, i2, SubgraphKind::Implementation, EdgeKind::calls()))
+            .unwrap();
+        g.propagation_table.insert(99, [e].into_iter().collect());
+
+        g.remove_edge(e).unwrap();
+
+        assert!(g.store.impl_out[&i1].is_empty());
+        assert!(g.propagation_table[&99].is_empty());
+        assert_eq!(g.remove_edge(e).unwrap_err(), GraphError::EdgeNotFound(e));
+    }
+
+    #[test]
+    fn remove_node_drops_incident_edges_mappings_and_detaches_children() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(mk_node("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(mk_node("B", SubgraphKind::Architecture, Some(a))).unwrap();
+        let c = g.add_node(mk_node("C", SubgraphKind::Architecture, None)).unwrap();
+        let i = g.add_node(mk_node("I", SubgraphKind::Implementation, None)).unwrap();
+        g.add_edge(mk_edge(c, a, SubgraphKind::Architecture, EdgeKind::depends_on())).unwrap();
+        g.set_mapping(i, a).unwrap();
+
+        g.remove_node(a).unwrap();
+
+        assert!(g.store.edges.is_empty());
+        assert!(g.store.arch_out[&c].is_empty());
+        assert_eq!(g.mapping_len(), 0);
+        assert_eq!(g.store.nodes[&b].parent, None);
+        assert_eq!(g.remove_node(a).unwrap_err(), GraphError::NodeNotFound(a));
+    }
+
+    #[test]
+    fn compact_renumbers_densely_and_keeps_analysis_results() {
+        use crate::core::classify::AnalysisOptions;
+
+        let mut g = ReflexionGraph::new();
+        let junk = g.add_node(mk_node("junk", SubgraphKind::Architecture, None)).unwrap();
+        let app = g.add_node(mk_node("App", SubgraphKind::Architecture, None)).unwrap();
+        let svc = g.add_node(mk_node("Svc", SubgraphKind::Architecture, Some(app))).unwrap();
+        let db = g.add_node(mk_node("Db", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(mk_edge(app, db, SubgraphKind::Architecture, EdgeKind::calls())).unwrap();
+        g.add_edge(mk_edge(db, app, SubgraphKind::Architecture, EdgeKind::calls())).unwrap();
+
+        let s = g.add_node(mk_node("s", SubgraphKind::Implementation, None)).unwrap();
+        let d = g.add_node(mk_node("d", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(s, svc).unwrap();
+        g.set_mapping(d, db).unwrap();
+        let tmp = g.add_edge(mk_edge(d, s, SubgraphKind::Implementation, EdgeKind::calls())).unwrap();
+        g.add_edge(mk_edge(s, d, SubgraphKind::Implementation, EdgeKind::calls())).unwrap();
+        g.add_edge(mk_edge(d, d, SubgraphKind::Implementation, EdgeKind::calls())).unwrap();
+
+        g.remove_node(junk).unwrap();
+        g.remove_edge(tmp).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+        let before = g.to_canonical();
+
+        let remap = g.compact();
+
+        let mut node_ids: Vec<NodeId> = g.store.nodes.keys().copied().collect();
+        node_ids.sort_unstable();
+        assert_eq!(node_ids, (1..=5).collect::<Vec<_>>());
+        assert_eq!(remap.nodes[&app], 1);
+        assert_eq!(g.store.nodes[&remap.nodes[&svc]].parent, Some(remap.nodes[&app]));
+        assert_eq!(g.get_arch_node(remap.nodes[&s]).unwrap(), Some(remap.nodes[&svc]));
+        assert_eq!(g.check_invariants(), Ok(()));
+
+        //results are identical, both as compacted and after a fresh run
+        assert_eq!(g.to_canonical(), before);
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.to_canonical(), before);
+
+        //fresh ids continue right after the dense range
+        let next = g.add_node(mk_node("next", SubgraphKind::Architecture, None)).unwrap();
+        assert_eq!(next, 6);
+    }
+
+    #[test]
+    fn analyzed_flag_is_cleared_by_mutations_and_init_states() {
+        use crate::core::classify::AnalysisOptions;
+
+        let mut g = ReflexionGraph::new();
+        assert!(!g.is_analyzed());
+
+        let a = g.add_node(mk_node("A", SubgraphKind::Architecture, None)).unwrap();
+        let i = g.add_node(mk_node("i", SubgraphKind::Implementation, None)).unwrap();
+        let e = g.add_edge(mk_edge(i, i, SubgraphKind::Implementation, EdgeKind::calls())).unwrap();
+        g.set_mapping(i, a).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+        assert!(g.is_analyzed());
+
+        g.init_states();
+        assert!(!g.is_analyzed());
+
+        g.analyze(&AnalysisOptions::default());
+        g.remove_mapping(i).unwrap();
+        assert!(!g.is_analyzed());
+
+        g.analyze(&AnalysisOptions::default());
+        g.remove_edge(e).unwrap();
+        assert!(!g.is_analyzed());
+
+        g.analyze(&AnalysisOptions::default());
+        g.add_node(mk_node("B", SubgraphKind::Architecture, None)).unwrap();
+        assert!(!g.is_analyzed());
+    }
+
+    #[test]
+    fn filter_edges_and_nodes_by_predicate() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(mk_node("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(mk_node("B", SubgraphKind::Architecture, Some(a))).unwrap();
+        let i = g.add_node(mk_node("i", SubgraphKind::Implementation, None)).unwrap();
+        let calls = g.add_edge(mk_edge(i, i, SubgraphKind::Implementation, EdgeKind::calls())).unwrap();
+        g.add_edge(mk_edge(a, b, SubgraphKind::Architecture, EdgeKind::calls())).unwrap();
+        g.add_edge(mk_edge(i, i, SubgraphKind::Implementation, EdgeKind::depends_on())).unwrap();
+
+        let found = g.filter_edges(|e| {
+            e.subgraph() == SubgraphKind::Implementation && e.kind() == &EdgeKind::calls()
+        });
+        assert_eq!(found, vec![calls]);
+
+        assert_eq!(g.filter_nodes(|n| n.parent().is_some()), vec![b]);
+        assert_eq!(g.filter_nodes(|n| n.subgraph() == SubgraphKind::Architecture), vec![a, b]);
+    }
+
+    #[test]
+    fn nodes_in_subgraph_is_sorted_and_scoped() {
+        let mut g = ReflexionGraph::new();
+        let i1 = g.add_node(mk_node("i1", SubgraphKind::Implementation, None)).unwrap();
+        let a1 = g.add_node(mk_node("a1", SubgraphKind::Architecture, None)).unwrap();
+        let i2 = g.add_node(mk_node("i2", SubgraphKind::Implementation, None)).unwrap();
+        let a2 = g.add_node(mk_node("a2", SubgraphKind::Architecture, Some(a1))).unwrap();
+
+        assert_eq!(g.nodes_in_subgraph(SubgraphKind::Implementation), vec![i1, i2]);
+        assert_eq!(g.nodes_in_subgraph(SubgraphKind::Architecture), vec![a1, a2]);
+        assert!(g.nodes_in_subgraph(SubgraphKind::Propagated).is_empty());
+    }
+
+    #[test]
+    fn bfs_order_walks_roots_children_then_edges_by_name() {
+        let mut g = ReflexionGraph::new();
+        let zeta = g.add_node(mk_node("Zeta", SubgraphKind::Architecture, None)).unwrap();
+        let alpha = g.add_node(mk_node("Alpha", SubgraphKind::Architecture, None)).unwrap();
+        let y = g.add_node(mk_node("y", SubgraphKind::Architecture, Some(alpha))).unwrap();
+        let x = g.add_node(mk_node("x", SubgraphKind::Architecture, Some(alpha))).unwrap();
+        let deep = g.add_node(mk_node("deep", SubgraphKind::Architecture, Some(zeta))).unwrap();
+        g.add_node(mk_node("impl", SubgraphKind::Implementation, None)).unwrap();
+        //x's edge reaches into Zeta's tree before Zeta itself is visited
+        g.add_edge(mk_edge(x, deep, SubgraphKind::Architecture, EdgeKind::calls())).unwrap();
+        g.add_edge(mk_edge(alpha, zeta, SubgraphKind::Propagated, EdgeKind::calls())).unwrap();
+
+        assert_eq!(g.bfs_order(SubgraphKind::Architecture), vec![alpha, zeta, x, y, deep]);
+        assert_eq!(g.bfs_order(SubgraphKind::Propagated), vec![alpha, zeta, x, y, deep]);
+        assert_eq!(g.bfs_order(SubgraphKind::Implementation).len(), 1);
+    }
+
+    #[test]
+    fn clear_resets_contents_but_keeps_configuration() {
+        let mut g = ReflexionGraph::new();
+        assert!(g.is_empty());
+        g.set_structural_kinds(&[EdgeKind::from("nests")]);
+        let a = g.add_node(mk_node("A", SubgraphKind::Architecture, None)).unwrap();
+        let i = g.add_node(mk_node("i", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i, a).unwrap();
+        g.add_edge(mk_edge(i, i, SubgraphKind::Implementation, EdgeKind::calls())).unwrap();
+        g.analyze(&AnalysisOptions::default());
+        assert!(!g.is_empty());
+
+        g.clear();
+        assert!(g.is_empty() && !g.is_analyzed());
+        assert_eq!((g.edges().count(), g.mapping_len()), (0, 0));
+        assert!(g.is_structural(&EdgeKind::from("nests")));
+        assert_eq!(g.add_node(mk_node("B", SubgraphKind::Architecture, None)).unwrap(), 1);
+    }
+
+    #[test]
+    fn descriptions_survive_init_states() {
+        let mut g = ReflexionGraph::new();
+        let a = g
+            .add_node(mk_node("A", SubgraphKind::Architecture, None).with_description("entry points"))
+            .unwrap();
+        let b = g.add_node(mk_node("B", SubgraphKind::Architecture, None)).unwrap();
+        let e = g
+            .add_edge(
+                mk_edge(a, b, SubgraphKind::Architecture, EdgeKind::depends_on())
+                    .with_description("A reads B's config"),
+            )
+            .unwrap();
+
+        g.init_states();
+
+        assert_eq!(g.node(a).unwrap().description(), Some("entry points"));
+        assert_eq!(g.node(b).unwrap().description(), None);
+        assert_eq!(g.edge(e).unwrap().description(), Some("A reads B's config"));
+    }
+
     #[test]
     fn init_states_resets_edge_states_counters_and_clears_propagation_table() {
         // Build a tiny graph in "post-run" messy state
@@ -356,7 +1402,7 @@ mod tests {
         let e_impl: EdgeId = 11;
         let e_prop: EdgeId = 12;
 
-        g.edges.insert(
+        g.store.edges.insert(
             e_arch,
             Edge {
                 id: e_arch,
@@ -366,10 +1412,20 @@ mod tests {
                 subgraph: SubgraphKind::Architecture,
                 state: EdgeState::Undefined, // wrong on purpose
                 counter: 7,                  // wrong on purpose
+                confidence: None,
+                pinned: None,
+                min_support: None,
+                max_support: None,
+                variant: None,
+                first_seen: None,
+                last_seen: None,
+                symmetric: false,
+                test_only: false,
+                description: None,
             },
         );
 
-        g.edges.insert(
+        g.store.edges.insert(
             e_impl,
             Edge {
                 id: e_impl,
@@ -379,10 +1435,20 @@ mod tests {
                 subgraph: SubgraphKind::Implementation,
                 state: EdgeState::Specified, // wrong on purpose
                 counter: 9,                  // wrong on purpose
+                confidence: None,
+                pinned: None,
+                min_support: None,
+                max_support: None,
+                variant: None,
+                first_seen: None,
+                last_seen: None,
+                symmetric: false,
+                test_only: false,
+                description: None,
             },
         );
 
-        g.edges.insert(
+        g.store.edges.insert(
             e_prop,
             Edge {
                 id: e_prop,
@@ -392,6 +1458,16 @@ mod tests {
                 subgraph: SubgraphKind::Propagated,
                 state: EdgeState::Specified, // wrong on purpose
                 counter: 3,                  // wrong on purpose
+                confidence: None,
+                pinned: None,
+                min_support: None,
+                max_support: None,
+                variant: None,
+                first_seen: None,
+                last_seen: None,
+                symmetric: false,
+                test_only: false,
+                description: None,
             },
         );
 
@@ -402,19 +1478,120 @@ mod tests {
         g.init_states();
 
         // Assert
-        let a = g.edges.get(&e_arch).unwrap();
+        let a = g.store.edges.get(&e_arch).unwrap();
         assert!(matches!(a.state, EdgeState::Specified));
         assert_eq!(a.counter, 0);
 
-        let i = g.edges.get(&e_impl).unwrap();
+        let i = g.store.edges.get(&e_impl).unwrap();
         assert!(matches!(i.state, EdgeState::Undefined));
         assert_eq!(i.counter, 0);
 
-        let p = g.edges.get(&e_prop).unwrap();
+        let p = g.store.edges.get(&e_prop).unwrap();
         assert!(matches!(p.state, EdgeState::Undefined));
         assert_eq!(p.counter, 0);
 
         assert!(g.propagation_table.is_empty());
     }
 
+    #[test]
+    fn subgraph_of_bare_ids() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(mk_node("A", SubgraphKind::Architecture, None)).unwrap();
+        let i = g.add_node(mk_node("i", SubgraphKind::Implementation, None)).unwrap();
+        let e = g.add_edge(mk_edge(i, i, SubgraphKind::Implementation, EdgeKind::calls())).unwrap();
+
+        assert_eq!(g.subgraph_of_node(a), Some(SubgraphKind::Architecture));
+        assert_eq!(g.subgraph_of_node(i), Some(SubgraphKind::Implementation));
+        assert_eq!(g.subgraph_of_edge(e), Some(SubgraphKind::Implementation));
+        assert_eq!(g.subgraph_of_node(999), None);
+        assert_eq!(g.subgraph_of_edge(999), None);
+    }
+
+    #[test]
+    fn rename_edge_kind_rewrites_every_matching_edge() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(mk_node("A", SubgraphKind::Architecture, None)).unwrap();
+        let i = g.add_node(mk_node("i", SubgraphKind::Implementation, None)).unwrap();
+        let e1 = g.add_edge(mk_edge(i, i, SubgraphKind::Implementation, EdgeKind::from("call"))).unwrap();
+        let e2 = g.add_edge(mk_edge(a, a, SubgraphKind::Architecture, EdgeKind::from("call"))).unwrap();
+        let other = g.add_edge(mk_edge(i, i, SubgraphKind::Implementation, EdgeKind::depends_on())).unwrap();
+
+        assert_eq!(g.rename_edge_kind(&EdgeKind::from("call"), EdgeKind::from("  ")), Err(GraphError::InvalidEdgeKind));
+        assert_eq!(g.edge(e1).unwrap().kind(), &EdgeKind::from("call"));
+        assert_eq!(g.rename_edge_kind(&EdgeKind::from("call"), EdgeKind::calls()), Ok(2));
+        assert_eq!(g.edge(e1).unwrap().kind(), &EdgeKind::calls());
+        assert_eq!(g.edge(e2).unwrap().kind(), &EdgeKind::calls());
+        assert_eq!(g.edge(other).unwrap().kind(), &EdgeKind::depends_on());
+        assert_eq!(g.rename_edge_kind(&EdgeKind::from("call"), EdgeKind::calls()), Ok(0));
+    }
+
+    #[test]
+    fn edges_in_insertion_order_survives_removal_and_compact() {
+        let mut g = ReflexionGraph::new();
+        let z = g.add_node(mk_node("z", SubgraphKind::Implementation, None)).unwrap();
+        let a = g.add_node(mk_node("a", SubgraphKind::Implementation, None)).unwrap();
+        let first = g.add_edge(mk_edge(z, a, SubgraphKind::Implementation, EdgeKind::calls())).unwrap();
+        let dropped = g.add_edge(mk_edge(a, z, SubgraphKind::Implementation, EdgeKind::calls())).unwrap();
+        let last = g.add_edge(mk_edge(a, a, SubgraphKind::Implementation, EdgeKind::calls())).unwrap();
+        g.remove_edge(dropped).unwrap();
+        assert_eq!(g.edges_in_insertion_order(), vec![first, last]);
+
+        //churn: removed ids are pruned, so the list stays within twice the edge count
+        for _ in 0..10 {
+            let tmp = g.add_edge(mk_edge(z, z, SubgraphKind::Implementation, EdgeKind::calls())).unwrap();
+            g.remove_edge(tmp).unwrap();
+        }
+        assert!(g.insertion_order.len() <= 2 * g.store.edges.len());
+        assert_eq!(g.edges_in_insertion_order(), vec![first, last]);
+
+        let remap = g.compact();
+        assert_eq!(g.edges_in_insertion_order(), vec![remap.edges[&first], remap.edges[&last]]);
+        g.clear();
+        assert!(g.edges_in_insertion_order().is_empty());
+    }
+
+    #[test]
+    fn would_create_cycle_checks_reachability_back_to_the_source() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("Ui", SubgraphKind::Architecture, None)).unwrap();
+        let logic = g.add_node(Node::new("Logic", SubgraphKind::Architecture, Some(ui))).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(ui, logic, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(logic, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(db, ui, EdgeKind::contains(), SubgraphKind::Architecture)).unwrap();
+        let before = g.edges().count();
+
+        assert!(g.would_create_cycle(db, ui, SubgraphKind::Architecture));
+        assert!(g.would_create_cycle(db, db, SubgraphKind::Architecture));
+        //the contains edge Db -> Ui is structure, not a dependency
+        assert!(!g.would_create_cycle(logic, db, SubgraphKind::Architecture));
+        assert!(!g.would_create_cycle(db, ui, SubgraphKind::Propagated));
+        assert!(!g.would_create_cycle(db, 99, SubgraphKind::Architecture));
+        assert_eq!(g.edges().count(), before);
+    }
+
+    #[test]
+    fn phase_follows_the_pipeline_and_resets_on_change() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(mk_node("A", SubgraphKind::Architecture, None)).unwrap();
+        let i = g.add_node(mk_node("i", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i, a).unwrap();
+        g.add_edge(mk_edge(i, i, SubgraphKind::Implementation, EdgeKind::calls())).unwrap();
+        assert_eq!(g.phase(), Phase::Built);
+        assert_eq!(g.require_phase(Phase::Initialized), Err(GraphError::PhaseTooEarly { required: Phase::Initialized, actual: Phase::Built }));
+
+        g.init_states();
+        assert_eq!(g.phase(), Phase::Initialized);
+        g.propagate(&AnalysisOptions::default(), &|_| true, &mut Scratch::default());
+        assert_eq!(g.phase(), Phase::Propagated);
+        assert!(g.require_phase(Phase::Initialized).is_ok());
+        assert!(!g.is_analyzed());
+
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.phase(), Phase::Analyzed);
+        assert!(g.require_phase(Phase::Analyzed).is_ok());
+
+        g.add_node(mk_node("j", SubgraphKind::Implementation, None)).unwrap();
+        assert_eq!(g.phase(), Phase::Built);
+    }
 }
@@ -0,0 +1,72 @@
+// ad-hoc node groups (team ownership, ...) orthogonal to the containment tree
+use std::collections::HashMap;
+use crate::core::types::{EdgeId, NodeId};
+use crate::core::graph::{GraphError, ReflexionGraph};
+
+//bucket of violations_by_group for sources without a group
+pub const UNGROUPED: &str = "ungrouped";
+
+impl ReflexionGraph {
+    //put a node (either side) into a reporting group such as its owning team, replacing an
+    //earlier one. groups only feed the reports, so the analysis results stay valid.
+    pub fn set_group(&mut self, node: NodeId, group: impl Into<String>) -> Result<(), GraphError> {
+        if !self.store.nodes.contains_key(&node) {
+            return Err(GraphError::NodeNotFound(node));
+        }
+        self.groups.insert(node, group.into());
+        Ok(())
+    }
+
+    //effective group of a node: its own, else the nearest grouped ancestor's
+    pub fn group_of(&self, node: NodeId) -> Option<&str> {
+        self.self_and_ancestors(node).iter().find_map(|n| self.groups.get(n)).map(String::as_str)
+    }
+
+    //violations() of the last analyze bucketed by the group of their source module
+    //(UNGROUPED when it has none), each bucket sorted. NotAnalyzed like violations().
+    pub fn violations_by_group(&self) -> Result<HashMap<String, Vec<EdgeId>>, GraphError> {
+        let mut by_group: HashMap<String, Vec<EdgeId>> = HashMap::new();
+        for eid in self.violations()? {
+            let group = self.group_of(self.store.edges[&eid].from).unwrap_or(UNGROUPED);
+            by_group.entry(group.to_string()).or_default().push(eid);
+        }
+        Ok(by_group)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UNGROUPED;
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::{Edge, GraphError, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn violations_by_group_buckets_by_source_module_group() {
+        let mut g = ReflexionGraph::new();
+        let web = g.add_node(Node::new("Web", SubgraphKind::Architecture, None)).unwrap();
+        let ui = g.add_node(Node::new("Ui", SubgraphKind::Architecture, Some(web))).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        let log = g.add_node(Node::new("Log", SubgraphKind::Architecture, None)).unwrap();
+        let absent = g.add_edge(Edge::new(ui, log, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let mut imp = |name: &str, arch| {
+            let n = g.add_node(Node::new(name, SubgraphKind::Implementation, None)).unwrap();
+            g.set_mapping(n, arch).unwrap();
+            n
+        };
+        let (u, d, l) = (imp("u", ui), imp("d", db), imp("l", log));
+        g.add_edge(Edge::new(u, d, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(d, l, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.set_group(web, "frontend team").unwrap();
+        assert_eq!(g.set_group(99, "x"), Err(GraphError::NodeNotFound(99)));
+        g.analyze(&AnalysisOptions::default());
+        assert!(g.is_analyzed());
+
+        let propagated_from = |from| g.edges().find(|e| e.subgraph() == SubgraphKind::Propagated && e.from() == from).unwrap().id();
+        let by_group = g.violations_by_group().unwrap();
+        assert_eq!(by_group.len(), 2);
+        assert_eq!(by_group["frontend team"], vec![absent, propagated_from(ui)]);
+        assert_eq!(by_group[UNGROUPED], vec![propagated_from(db)]);
+        assert_eq!(g.group_of(u), None);
+    }
+}
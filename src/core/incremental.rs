@@ -0,0 +1,165 @@
+// incremental updates: keep counters and verdicts current for one impl edge at a time
+use crate::core::types::{EdgeId, SubgraphKind};
+use crate::core::graph::{Edge, GraphError, ReflexionGraph};
+use crate::core::state::EdgeState;
+use crate::core::store::GraphStore;
+
+//verdict of a judged spec edge for its counter (the classify rule, see there)
+pub(crate) fn spec_verdict(e: &Edge) -> EdgeState {
+    let in_range = e.min_support.is_none_or(|min| e.counter >= min) && e.max_support.is_none_or(|max| e.counter <= max);
+    match (e.counter > 0, in_range) {
+        (true, true) => EdgeState::Convergent,
+        (true, false) => EdgeState::MultiplicityViolation,
+        (false, _) if e.min_support == Some(0) => EdgeState::AllowedAbsent,
+        (false, _) => EdgeState::Absent,
+    }
+}
+
+impl<S: GraphStore> ReflexionGraph<S> {
+    //new state of a spec or propagated edge whose counter just changed. spec edges the
+    //run didn't judge (still Specified) and pinned edges keep their state.
+    fn recount_state(&mut self, eid: EdgeId) {
+        let judged = {
+            let e = self.edge_at(eid);
+            e.pinned.is_none() && e.state != EdgeState::Specified && !self.is_structural(&e.kind)
+        };
+        let e = self.store.get_edge_mut(eid).expect("caller checked");
+        if !judged {
+            return;
+        }
+        match e.subgraph {
+            SubgraphKind::Architecture => e.state = spec_verdict(e),
+            SubgraphKind::Propagated if e.counter == 0 => e.state = EdgeState::Undefined,
+            _ => {}
+        }
+    }
+
+    //take impl edge `impl_edge` out of every propagated and spec edge it was counted on:
+    //their counters drop by one (never below zero), and an edge left without support goes
+    //back to its unsupported state (propagated: Undefined, spec: Absent / AllowedAbsent)
+    pub(crate) fn withdraw_support(&mut self, impl_edge: EdgeId) {
+        let mut supported: Vec<EdgeId> = self
+            .propagation_table
+            .iter_mut()
+            .filter_map(|(&eid, support)| support.remove(&impl_edge).then_some(eid))
+            .collect();
+        supported.sort_unstable();
+        for &eid in &supported {
+            if let Some(e) = self.store.get_edge_mut(eid) {
+                e.counter = e.counter.saturating_sub(1).max(0);
+                self.recount_state(eid);
+            }
+        }
+        self.debug_assert_local(&[], &supported);
+    }
+
+    //incremental mode: (re)count one impl edge into the results of the last analyze instead
+    //of rerunning it, e.g. right after add_edge. its old support is withdrawn, then it is
+    //lifted with the last run's options (and kind filter) and counted on its propagated edge (synthesized if
+    //new) and the spec edge that realizes it, whose verdicts are updated; returns the
+    //edge's new state. only the touched edges are reclassified: ambiguous lifts and
+    //encapsulation are left to the next analyze, and is_analyzed() stays as the
+    //mutations before left it. spec and propagated edges are returned as they are.
+    pub fn reanalyze_edge(&mut self, edge: EdgeId) -> Result<EdgeState, GraphError> {
+        let e = self.store.get_edge(edge).ok_or(GraphError::EdgeNotFound(edge))?;
+        if e.subgraph != SubgraphKind::Implementation {
+            return Ok(e.state);
+        }
+        let phase = self.phase();
+        self.withdraw_support(edge);
+
+        let options = self.last_options;
+        let e = self.edge_at(edge);
+        let lifted = (self.lift_target(e.from, &options), self.lift_target(e.to, &options));
+        let mut touched = vec![edge];
+        let state = if !options.considers(e) || self.is_structural(&e.kind) || !self.in_analyzed_kinds(&e.kind) {
+            EdgeState::Undefined
+        } else if let (Some(from), Some(to)) = lifted {
+            let prop = self.propagated_edge(from, to, edge);
+            touched.push(prop);
+            self.store.get_edge_mut(prop).expect("just found or added").counter += 1;
+            self.propagation_table.entry(prop).or_default().insert(edge);
+
+            let kind = self.edge_at(prop).kind.clone();
+            let spec = self.matching_spec_edges(from, to, &kind).first().copied();
+            if let Some(spec) = spec {
+                touched.push(spec);
+                self.store.get_edge_mut(spec).expect("matched above").counter += 1;
+                self.propagation_table.entry(spec).or_default().insert(edge);
+                self.recount_state(spec);
+            }
+            let verdict = EdgeState::classify(spec.is_some(), from == to);
+            self.store.get_edge_mut(prop).expect("just found or added").state = verdict;
+            verdict
+        } else if self.is_external_edge(e.from, e.to) {
+            EdgeState::Allowed
+        } else {
+            EdgeState::Unmapped
+        };
+
+        let e = self.store.get_edge_mut(edge).expect("checked above");
+        e.state = e.pinned.unwrap_or(state);
+        let state = e.state;
+        self.set_phase(phase);
+        self.debug_assert_local(&[], &touched);
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::state::EdgeState;
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn adding_then_removing_the_only_support_restores_the_spec_edge() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let spec = g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i, a).unwrap();
+        g.set_mapping(j, b).unwrap();
+        g.analyze(&AnalysisOptions::default());
+        let before = (g.edge(spec).unwrap().state(), g.edge(spec).unwrap().counter());
+        assert_eq!(before, (EdgeState::Absent, 0));
+
+        let call = g.add_edge(Edge::new(i, j, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        assert_eq!(g.reanalyze_edge(call).unwrap(), EdgeState::Convergent);
+        assert_eq!((g.edge(spec).unwrap().state(), g.edge(spec).unwrap().counter()), (EdgeState::Convergent, 1));
+        //recounting the same edge doesn't count it twice
+        assert_eq!(g.reanalyze_edge(call).unwrap(), EdgeState::Convergent);
+        assert_eq!(g.edge(spec).unwrap().counter(), 1);
+        let prop = g.edges_in_subgraph(SubgraphKind::Propagated)[0];
+
+        g.remove_edge(call).unwrap();
+        assert_eq!((g.edge(spec).unwrap().state(), g.edge(spec).unwrap().counter()), before);
+        assert_eq!((g.edge(prop).unwrap().state(), g.edge(prop).unwrap().counter()), (EdgeState::Undefined, 0));
+        assert!(g.supporting_impl_edges(spec).unwrap().is_empty());
+
+        //a full run agrees with the incremental one
+        let incremental = g.to_canonical();
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.edge(spec).unwrap().state(), EdgeState::Absent);
+        assert!(incremental.contains("Architecture A -> B [calls] state=Absent counter=0"));
+    }
+
+    #[test]
+    fn withdrawing_support_never_underflows() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i, a).unwrap();
+        let e = g.add_edge(Edge::new(i, i, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.analyze(&AnalysisOptions::default());
+        let prop = g.edges_in_subgraph(SubgraphKind::Propagated)[0];
+        g.store.edges.get_mut(&prop).unwrap().counter = 0;
+
+        g.remove_edge(e).unwrap();
+        assert_eq!(g.edge(prop).unwrap().counter(), 0);
+        assert_eq!(g.edge(prop).unwrap().state(), EdgeState::Undefined);
+    }
+}
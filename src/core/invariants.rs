@@ -0,0 +1,204 @@
+// internal consistency of the graph's indexes and hierarchy
+use crate::core::types::{EdgeId, NodeId, SubgraphKind};
+use crate::core::graph::{Edge, ReflexionGraph};
+use crate::core::store::GraphStore;
+
+impl<S: GraphStore> ReflexionGraph<S> {
+    //consistency check for users who suspect a corrupted graph (a damaged binary cache, a
+    //custom store with bugs): adjacency lists only hold existing edges of their node and
+    //side and list every edge, edge endpoints and maps_to entries exist (impl -> arch),
+    //the implementor index mirrors maps_to, and parent/children links agree. returns every problem found, sorted.
+    pub fn check_invariants(&self) -> Result<(), Vec<String>> {
+        let problems = self.invariant_problems();
+        if problems.is_empty() { Ok(()) } else { Err(problems) }
+    }
+
+    //local check run after every mutation in debug builds, so debug builds stay linear:
+    //only the given nodes (parent/children links and adjacency lists; a removed node must
+    //have no adjacency left) and edges (endpoints, listed in their source's adjacency).
+    //the full scan is check_invariants. release builds skip it.
+    //called by: add_node, add_edge, remove_edge, remove_node, set_parent, ensure_root,
+    //rename_edge_kind, reanalyze_edge and withdraw_support (the edges they recount) and
+    //the propagated-edge cleanup of analyze; the mapping writes use debug_assert_mapping
+    //and compact debug_assert_all. exempt, as they change nothing the checks cover: clear
+    //and clear_mappings (which leave empty indexes), confirm_mapping (the proposed flag)
+    //and everything that only writes states, counters or side tables (the analysis
+    //passes, pins, severities, groups, layers, api boundaries, metadata, observe_edge).
+    //from_bytes checks its input itself and reports damage as an error, not a panic.
+    #[cfg_attr(not(debug_assertions), allow(unused_variables))]
+    pub(crate) fn debug_assert_local(&self, nodes: &[NodeId], edges: &[EdgeId]) {
+        #[cfg(debug_assertions)]
+        {
+            let mut problems = Vec::new();
+            for &n in nodes {
+                self.node_problems(n, &mut problems);
+            }
+            for e in edges.iter().filter_map(|&e| self.store.get_edge(e)) {
+                self.edge_problems(e, &mut problems);
+            }
+            assert!(problems.is_empty(), "graph invariants violated: {:?}", problems);
+        }
+    }
+
+    //the same after a mapping change: the impl node's mapping (if any) runs from an impl to
+    //an arch node and is indexed, and the arch node it was mapped to before no longer lists it
+    #[cfg_attr(not(debug_assertions), allow(unused_variables))]
+    pub(crate) fn debug_assert_mapping(&self, impl_node: NodeId, previous: Option<NodeId>) {
+        #[cfg(debug_assertions)]
+        {
+            let mut problems = Vec::new();
+            let current = self.maps_to.get(&impl_node).copied();
+            if let Some(a) = current {
+                self.mapping_problems(impl_node, a, &mut problems);
+            }
+            if let Some(p) = previous.filter(|&p| current != Some(p))
+                && self.arch_to_impls.get(&p).is_some_and(|list| list.contains(&impl_node))
+            {
+                problems.push(format!("implementor index lists {} under {}, but maps_to doesn't", impl_node, p));
+            }
+            assert!(problems.is_empty(), "graph invariants violated: {:?}", problems);
+        }
+    }
+
+    //the full scan in debug builds, for compact, which rewrites every index at once and is
+    //linear anyway
+    pub(crate) fn debug_assert_all(&self) {
+        #[cfg(debug_assertions)]
+        {
+            let problems = self.invariant_problems();
+            assert!(problems.is_empty(), "graph invariants violated: {:?}", problems);
+        }
+    }
+
+    fn invariant_problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for e in self.store.iter_edges() {
+            self.edge_problems(e, &mut problems);
+        }
+        for n in self.store.iter_nodes() {
+            self.node_problems(n.id, &mut problems);
+        }
+        for (&i, &a) in &self.maps_to {
+            self.mapping_problems(i, a, &mut problems);
+        }
+        for (&a, list) in &self.arch_to_impls {
+            for &i in list {
+                if self.maps_to.get(&i) != Some(&a) {
+                    problems.push(format!("implementor index lists {} under {}, but maps_to doesn't", i, a));
+                }
+            }
+        }
+        problems.sort();
+        problems
+    }
+
+    fn edge_problems(&self, e: &Edge, problems: &mut Vec<String>) {
+        for end in [e.from, e.to] {
+            if self.store.get_node(end).is_none() {
+                problems.push(format!("edge {} has missing endpoint {}", e.id, end));
+            }
+        }
+        //searched from the back: a new edge is last, so the check after add_edge is O(1)
+        if !self.store.out_edges(e.from, e.subgraph).iter().rev().any(|&x| x == e.id) {
+            problems.push(format!("edge {} is missing from the adjacency of node {}", e.id, e.from));
+        }
+    }
+
+    fn node_problems(&self, id: NodeId, problems: &mut Vec<String>) {
+        let side = |sg: SubgraphKind| match sg {
+            SubgraphKind::Implementation => SubgraphKind::Implementation,
+            SubgraphKind::Architecture | SubgraphKind::Propagated => SubgraphKind::Architecture,
+        };
+        let Some(n) = self.store.get_node(id) else {
+            for sg in [SubgraphKind::Implementation, SubgraphKind::Architecture] {
+                for &eid in self.store.out_edges(id, sg) {
+                    problems.push(format!("adjacency of removed node {} lists edge {}", id, eid));
+                }
+            }
+            return;
+        };
+
+        for sg in [SubgraphKind::Implementation, SubgraphKind::Architecture] {
+            for &eid in self.store.out_edges(n.id, sg) {
+                match self.store.get_edge(eid) {
+                    None => problems.push(format!("adjacency of node {} lists missing edge {}", n.id, eid)),
+                    Some(e) if e.from != n.id || side(e.subgraph) != sg => {
+                        problems.push(format!("adjacency of node {} lists foreign edge {}", n.id, eid))
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        if let Some(p) = n.parent {
+            match self.store.get_node(p) {
+                None => problems.push(format!("node {} has missing parent {}", n.id, p)),
+                //from the back for the same reason: new children are pushed last
+                Some(parent) if !parent.children.iter().rev().any(|&c| c == n.id) => {
+                    problems.push(format!("node {} is not a child of its parent {}", n.id, p))
+                }
+                Some(_) => {}
+            }
+        }
+        for &c in &n.children {
+            if self.store.get_node(c).is_none_or(|child| child.parent != Some(n.id)) {
+                problems.push(format!("child {} of node {} doesn't point back", c, n.id));
+            }
+        }
+    }
+
+    fn mapping_problems(&self, i: NodeId, a: NodeId, problems: &mut Vec<String>) {
+        let ok = self.subgraph_of_node(i) == Some(SubgraphKind::Implementation)
+            && self.subgraph_of_node(a) == Some(SubgraphKind::Architecture);
+        if !ok {
+            problems.push(format!("mapping {} => {} doesn't run from an impl to an arch node", i, a));
+        }
+        if !self.arch_to_impls.get(&a).is_some_and(|list| list.contains(&i)) {
+            problems.push(format!("mapping {} => {} is missing from the implementor index", i, a));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn check_invariants_reports_corruption() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, Some(a))).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let e = g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.set_mapping(i, a).unwrap();
+        g.remove_node(b).unwrap();
+        assert_eq!(g.check_invariants(), Ok(()));
+
+        //corrupt the mapping and its index past the checks of set_mapping
+        g.put_mapping(i, 999);
+        g.arch_to_impls.remove(&999);
+        g.arch_to_impls.entry(a).or_default().push(i);
+        g.store.arch_out.entry(a).or_default().push(e);
+        g.store.nodes.get_mut(&i).unwrap().children.push(a);
+        assert_eq!(g.check_invariants().unwrap_err(), vec![
+            format!("adjacency of node {} lists missing edge {}", a, e),
+            format!("child {} of node {} doesn't point back", a, i),
+            format!("implementor index lists {} under {}, but maps_to doesn't", i, a),
+            format!("mapping {} => 999 doesn't run from an impl to an arch node", i),
+            format!("mapping {} => 999 is missing from the implementor index", i),
+        ]);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "graph invariants violated")]
+    fn mutations_check_the_nodes_they_touch_in_debug_builds() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let e = g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.store.arch_out.entry(a).or_default().push(999);
+        //removing the edge re-checks a's adjacency, which still lists the bogus id
+        g.remove_edge(e).unwrap();
+    }
+}
@@ -0,0 +1,78 @@
+// layered architectures: layer numbers on arch nodes
+use crate::core::types::{EdgeId, NodeId, SubgraphKind};
+use crate::core::graph::{GraphError, ReflexionGraph};
+use crate::core::state::EdgeState;
+
+impl ReflexionGraph {
+    //put an arch node on a layer, 0 = bottom (e.g. Infra 0, Domain 1, Ui 2). modules
+    //without an own layer inherit the one of their nearest layered ancestor.
+    pub fn assign_layer(&mut self, arch_node: NodeId, layer: u32) -> Result<(), GraphError> {
+        self.expect_arch_node(arch_node)?;
+        self.layers.insert(arch_node, layer);
+        Ok(())
+    }
+
+    //effective layer of an arch node (own or inherited), None if nothing above it is layered
+    pub fn layer_of(&self, arch_node: NodeId) -> Option<u32> {
+        self.self_and_ancestors(arch_node).iter().find_map(|n| self.layers.get(n).copied())
+    }
+
+    //divergent impl edges that also run against the layering: their propagated edge goes
+    //from a lower layer up to a higher one. unspecified *and* layer-violating, so the
+    //first ones to fix. edges touching an unlayered module are not judged. sorted by id.
+    pub fn downward_divergences(&self) -> Vec<EdgeId> {
+        let mut out: Vec<EdgeId> = self
+            .edges_with_state_in(SubgraphKind::Propagated, EdgeState::Divergent)
+            .into_iter()
+            .filter(|prop| {
+                let e = &self.store.edges[prop];
+                matches!((self.layer_of(e.from), self.layer_of(e.to)), (Some(from), Some(to)) if from < to)
+            })
+            .flat_map(|prop| self.propagation_table.get(&prop).into_iter().flatten().copied())
+            .filter(|eid| self.store.edges[eid].state == EdgeState::Divergent)
+            .collect();
+        out.sort_unstable();
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::{Edge, GraphError, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn downward_divergences_only_flags_upward_dependencies() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("Ui", SubgraphKind::Architecture, None)).unwrap();
+        let domain = g.add_node(Node::new("Domain", SubgraphKind::Architecture, None)).unwrap();
+        let model = g.add_node(Node::new("Model", SubgraphKind::Architecture, Some(domain))).unwrap();
+        let infra = g.add_node(Node::new("Infra", SubgraphKind::Architecture, None)).unwrap();
+        g.assign_layer(ui, 2).unwrap();
+        g.assign_layer(domain, 1).unwrap();
+        g.assign_layer(infra, 0).unwrap();
+        assert_eq!(g.layer_of(model), Some(1));
+
+        let u = g.add_node(Node::new("u", SubgraphKind::Implementation, None)).unwrap();
+        let m = g.add_node(Node::new("m", SubgraphKind::Implementation, None)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(u, ui).unwrap();
+        g.set_mapping(m, model).unwrap();
+        g.set_mapping(i, infra).unwrap();
+
+        //both unspecified, but only infra -> model goes up the stack
+        g.add_edge(Edge::new(u, i, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        let up = g.add_edge(Edge::new(i, m, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.violations().unwrap().len(), 2);
+        assert_eq!(g.downward_divergences(), vec![up]);
+
+        assert_eq!(g.assign_layer(u, 0).unwrap_err(), GraphError::WrongSubgraph {
+            node: u,
+            expected: SubgraphKind::Architecture,
+            found: SubgraphKind::Implementation,
+        });
+    }
+}
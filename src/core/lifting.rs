@@ -1 +1,608 @@
 // lifting/hierarchy logic
+use std::collections::HashSet;
+use crate::core::types::{NodeId, EdgeId, EdgeKind, SubgraphKind};
+use crate::core::graph::{GraphError, Node, ReflexionGraph};
+use crate::core::state::EdgeState;
+use crate::core::analyzer::Scratch;
+use crate::core::store::GraphStore;
+
+impl<S: GraphStore> ReflexionGraph<S> {
+    //parent chain of a node, nearest parent first (the node itself is not included).
+    //unknown ids give an empty chain.
+    pub fn ancestors(&self, node: NodeId) -> Vec<NodeId> {
+        let mut chain = Vec::new();
+        let mut current = self.store.get_node(node).and_then(|n| n.parent);
+
+        while let Some(id) = current {
+            //defensive: a corrupted hierarchy must not loop forever
+            if chain.contains(&id) {
+                break;
+            }
+            chain.push(id);
+            current = self.store.get_node(id).and_then(|n| n.parent);
+        }
+        chain
+    }
+
+    //ancestor-or-self chain, the node itself first
+    pub(crate) fn self_and_ancestors(&self, node: NodeId) -> Vec<NodeId> {
+        let mut chain = vec![node];
+        chain.extend(self.ancestors(node));
+        chain
+    }
+
+    //lowest common ancestor-or-self of two nodes in the containment tree (a node is its
+    //own ancestor here, so lca(a, child of a) = a). None if they live in different trees
+    //or an id is unknown.
+    pub fn lca(&self, a: NodeId, b: NodeId) -> Option<NodeId> {
+        if self.store.get_node(a).is_none() || self.store.get_node(b).is_none() {
+            return None;
+        }
+        let above_a: HashSet<NodeId> = self.self_and_ancestors(a).into_iter().collect();
+        self.self_and_ancestors(b).into_iter().find(|n| above_a.contains(n))
+    }
+
+    //every node below `node` in the containment tree, depth first in children order
+    //(the node itself is not included). unknown ids give an empty list.
+    pub fn descendants(&self, node: NodeId) -> Vec<NodeId> {
+        let mut out = Vec::new();
+        let mut stack: Vec<NodeId> = self.store.get_node(node).map_or(Vec::new(), |n| n.children.iter().rev().copied().collect());
+
+        while let Some(id) = stack.pop() {
+            //defensive: a corrupted hierarchy must not loop forever
+            if id == node || out.contains(&id) {
+                continue;
+            }
+            out.push(id);
+            if let Some(n) = self.store.get_node(id) {
+                stack.extend(n.children.iter().rev());
+            }
+        }
+        out
+    }
+
+    //specified arch edges of `kind` (of the active variant) running from an ancestor-or-self
+    //of `from` to an ancestor-or-self of `to`, or symmetric ones running the other way, most
+    //specific first: fewest containment levels climbed in total, ties broken by climbing less
+    //on the source side. each endpoint climbs at most max_propagation_levels - 1 levels
+    //(of the last analyze), so level 1 only matches spec edges between from and to, and
+    //no further than the first propagation boundary (see spec_climb).
+    pub(crate) fn matching_spec_edges(&self, from: NodeId, to: NodeId, kind: &EdgeKind) -> Vec<EdgeId> {
+        let mut found = Vec::new();
+        self.matching_spec_edges_into(from, to, kind, &mut found);
+        found.into_iter().map(|(_, _, eid)| eid).collect()
+    }
+
+    //the arch nodes a spec match may climb to from `node`, nearest first: itself and its
+    //ancestors, at most `levels` of them, up to and including the first propagation
+    //boundary (see set_propagation_boundary_kinds)
+    fn spec_climb(&self, node: NodeId, levels: usize) -> Vec<NodeId> {
+        let mut chain = self.self_and_ancestors(node);
+        if let Some(boundary) = chain.iter().position(|&n| self.is_propagation_boundary(n)) {
+            chain.truncate(boundary + 1);
+        }
+        chain.truncate(levels);
+        chain
+    }
+
+    //matching_spec_edges into a reused buffer, as (levels climbed, source levels, edge)
+    pub(crate) fn matching_spec_edges_into(&self, from: NodeId, to: NodeId, kind: &EdgeKind, found: &mut Vec<(usize, usize, EdgeId)>) {
+        found.clear();
+        let levels = self.last_options.max_propagation_levels;
+        let tos = self.spec_climb(to, levels);
+        let spec_edges = |f: NodeId, t: NodeId| {
+            self.store.out_edges(f, SubgraphKind::Architecture).iter().copied().filter(move |&eid| {
+                let e = self.edge_at(eid);
+                e.to == t && e.subgraph == SubgraphKind::Architecture && &e.kind == kind && self.in_active_variant(e)
+            })
+        };
+
+        for (i, &f) in self.spec_climb(from, levels).iter().enumerate() {
+            for (j, &t) in tos.iter().enumerate() {
+                found.extend(spec_edges(f, t).map(|eid| (i + j, i, eid)));
+                if f != t {
+                    let reverse = spec_edges(t, f).filter(|eid| self.edge_at(*eid).symmetric);
+                    found.extend(reverse.map(|eid| (i + j, i, eid)));
+                }
+            }
+        }
+
+        found.sort_unstable();
+    }
+
+    //where one impl edge lands in architecture space: the arch nodes its endpoints map to,
+    //and its kind. None for unknown or non-impl edges and when an endpoint is unmapped.
+    //mappings are resolved as in the last analyze (see unmapped_edges); nothing is modified.
+    pub fn lift_edge(&self, impl_edge: EdgeId) -> Option<(NodeId, NodeId, EdgeKind)> {
+        let e = self.store.get_edge(impl_edge)?;
+        if e.subgraph != SubgraphKind::Implementation {
+            return None;
+        }
+        let from = self.lift_target(e.from, &self.last_options)?;
+        let to = self.lift_target(e.to, &self.last_options)?;
+        Some((from, to, e.kind.clone()))
+    }
+
+    //architectural spread of one impl node: the distinct arch nodes its outgoing
+    //dependencies land on (targets lifted through maps_to like lift_edge). unmapped
+    //targets and structural kinds are skipped; unknown ids give an empty set.
+    pub fn arch_footprint(&self, impl_node: NodeId) -> HashSet<NodeId> {
+        self.store
+            .out_edges(impl_node, SubgraphKind::Implementation)
+            .iter()
+            .map(|&eid| self.edge_at(eid))
+            .filter(|e| !self.is_structural(&e.kind))
+            .filter_map(|e| self.lift_target(e.to, &self.last_options))
+            .collect()
+    }
+
+    //lift every propagated edge onto the specified architecture:
+    // - a matching spec edge (see matching_spec_edges) takes over the support counter and
+    //   the supporting impl edges, and the propagated edge is Convergent. if several spec
+    //   edges match (same kind declared on more than one containment level), only the most
+    //   specific one gets the counter; the others are kept in ambiguous_lifts
+    // - no match but both ends in the same module -> Allowed (intra-module dependency)
+    // - otherwise the propagated edge is Divergent
+    pub(crate) fn lift(&mut self, scratch: &mut Scratch) {
+        self.edges_in_subgraph_into(SubgraphKind::Propagated, &mut scratch.ids);
+        for &prop in &scratch.ids {
+            let e = self.edge_at(prop);
+            let (from, to, counter) = (e.from, e.to, e.counter);
+
+            self.matching_spec_edges_into(from, to, &e.kind, &mut scratch.matches);
+            let matches = &scratch.matches;
+            if matches.len() > 1 {
+                self.ambiguous_lifts.insert(prop, matches.iter().map(|&(_, _, eid)| eid).collect());
+            }
+
+            if let Some(&(_, _, spec)) = matches.first() {
+                self.store.get_edge_mut(spec).expect("found above").counter += counter;
+                //take the propagated edge's set out while extending the spec edge's one
+                if let Some(support) = self.propagation_table.remove(&prop) {
+                    let target = self.propagation_table.entry(spec).or_insert_with(|| scratch.support_sets.pop().unwrap_or_default());
+                    target.extend(support.iter().copied());
+                    self.propagation_table.insert(prop, support);
+                } else {
+                    self.propagation_table.entry(spec).or_default();
+                }
+            }
+            let state = EdgeState::classify(!matches.is_empty(), from == to);
+
+            self.store.get_edge_mut(prop).expect("listed above").state = state;
+        }
+    }
+
+    //symmetric spec edges that the last analyze found realized in one direction only, with
+    //that direction as (from, to) arch nodes. Convergent all the same, listed for awareness:
+    //"A communicates_with B" backed only by A calling B may be a one-way protocol. sorted by edge.
+    pub fn asymmetric_realizations(&self) -> Vec<(EdgeId, NodeId, NodeId)> {
+        let mut out: Vec<(EdgeId, NodeId, NodeId)> = self
+            .store
+            .iter_edges()
+            .filter(|e| e.symmetric && e.subgraph == SubgraphKind::Architecture && e.from != e.to)
+            .filter_map(|spec| {
+                let support = self.propagation_table.get(&spec.id)?;
+                let (mut forward, mut backward) = (false, false);
+                for &impl_edge in support {
+                    let Some((f, t, _)) = self.lift_edge(impl_edge) else { continue };
+                    if self.self_and_ancestors(f).contains(&spec.from) && self.self_and_ancestors(t).contains(&spec.to) {
+                        forward = true;
+                    } else {
+                        backward = true;
+                    }
+                }
+                match (forward, backward) {
+                    (true, false) => Some((spec.id, spec.from, spec.to)),
+                    (false, true) => Some((spec.id, spec.to, spec.from)),
+                    _ => None,
+                }
+            })
+            .collect();
+        out.sort_unstable();
+        out
+    }
+
+    //impl edges whose lift matched more than one specified arch edge in the last analyze,
+    //with all candidates (most specific first, that one got the counter). sorted by impl edge.
+    pub fn ambiguous_convergences(&self) -> Vec<(EdgeId, Vec<EdgeId>)> {
+        let mut out: Vec<(EdgeId, Vec<EdgeId>)> = self
+            .ambiguous_lifts
+            .iter()
+            .flat_map(|(prop, candidates)| {
+                self.propagation_table
+                    .get(prop)
+                    .into_iter()
+                    .flatten()
+                    .map(move |&i| (i, candidates.clone()))
+            })
+            .collect();
+        out.sort_unstable();
+        out
+    }
+
+    //move `child` under `new_parent` (None detaches it to a root), keeping both the old
+    //and new parent's children lists in sync. rejects moves under the node itself or
+    //under one of its descendants.
+    pub fn set_parent(&mut self, child: NodeId, new_parent: Option<NodeId>) -> Result<(), GraphError> {
+        let old_parent = self.store.get_node(child).ok_or(GraphError::NodeNotFound(child))?.parent;
+
+        if let Some(p) = new_parent {
+            if self.store.get_node(p).is_none() {
+                return Err(GraphError::ParentNotFound(p));
+            }
+            if p == child || self.ancestors(p).contains(&child) {
+                return Err(GraphError::HierarchyCycle { child, parent: p });
+            }
+        }
+
+        if old_parent == new_parent {
+            return Ok(());
+        }
+
+        if let Some(old) = old_parent.and_then(|p| self.store.get_node_mut(p)) {
+            old.children.retain(|&c| c != child);
+        }
+        if let Some(p) = new_parent {
+            self.store.get_node_mut(p).expect("checked above").children.push(child);
+        }
+        self.store.get_node_mut(child).expect("checked above").parent = new_parent;
+        self.invalidate();
+        let mut touched = vec![child];
+        touched.extend(old_parent);
+        self.debug_assert_local(&touched, &[]);
+        Ok(())
+    }
+
+    //canonical top-level container for one subgraph: returns the existing parentless node
+    //called `name` in that subgraph, or creates it. with adopt_roots = true every other
+    //parentless node of the subgraph is moved under it, so the subgraph becomes one
+    //containment tree (which is what propagation and cross-tree checks assume).
+    pub fn ensure_root(&mut self, name: &str, subgraph: SubgraphKind, adopt_roots: bool) -> NodeId {
+        let existing = self
+            .store
+            .iter_nodes()
+            .filter(|n| n.subgraph == subgraph && n.parent.is_none() && n.name == name)
+            .map(|n| n.id)
+            .min();
+
+        let root = match existing {
+            Some(id) => id,
+            None => self
+                .add_node(Node::new(name, subgraph, None))
+                .expect("a parentless node cannot fail to insert"),
+        };
+
+        if adopt_roots {
+            let mut orphans: Vec<NodeId> = self
+                .store
+                .iter_nodes()
+                .filter(|n| n.subgraph == subgraph && n.parent.is_none() && n.id != root)
+                .map(|n| n.id)
+                .collect();
+            orphans.sort_unstable();
+
+            for id in &orphans {
+                self.store.get_node_mut(*id).expect("listed above").parent = Some(root);
+            }
+            if !orphans.is_empty() {
+                self.invalidate();
+            }
+            self.store.get_node_mut(root).expect("ensured above").children.extend(orphans);
+        }
+
+        self.debug_assert_local(&[root], &[]);
+        root
+    }
+
+    //topmost ancestor of a node (the node itself if it has no parent)
+    fn root_of(&self, node: NodeId) -> NodeId {
+        self.ancestors(node).last().copied().unwrap_or(node)
+    }
+
+    //modeling-quality heuristic: arch edges whose endpoints share no common ancestor
+    //(ancestor-or-self) via `parent`, which may point at a missing top-level container.
+    pub fn cross_tree_arch_edges(&self) -> Vec<EdgeId> {
+        self.cross_tree_arch_edges_with(false)
+    }
+
+    //same as cross_tree_arch_edges, but with roots_connected = true parentless nodes are
+    //treated as siblings under an implicit top-level container: an edge directly between
+    //two roots is then fine, only edges reaching into different trees below the roots are reported.
+    pub fn cross_tree_arch_edges_with(&self, roots_connected: bool) -> Vec<EdgeId> {
+        let mut out: Vec<EdgeId> = self
+            .store
+            .iter_edges()
+            .filter(|e| e.subgraph == SubgraphKind::Architecture)
+            .filter(|e| self.root_of(e.from) != self.root_of(e.to))
+            .filter(|e| {
+                let both_roots = self.store.get_node(e.from).is_some_and(|n| n.parent.is_none())
+                    && self.store.get_node(e.to).is_some_and(|n| n.parent.is_none());
+                !(roots_connected && both_roots)
+            })
+            .map(|e| e.id)
+            .collect();
+
+        out.sort_unstable();
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn ancestors_walks_parent_chain_nearest_first() {
+        let mut g = ReflexionGraph::new();
+        let root = g.add_node(Node::new("root", SubgraphKind::Architecture, None)).unwrap();
+        let mid = g.add_node(Node::new("mid", SubgraphKind::Architecture, Some(root))).unwrap();
+        let leaf = g.add_node(Node::new("leaf", SubgraphKind::Architecture, Some(mid))).unwrap();
+
+        assert_eq!(g.ancestors(leaf), vec![mid, root]);
+        assert!(g.ancestors(root).is_empty());
+        assert!(g.ancestors(999).is_empty());
+    }
+
+    #[test]
+    fn cross_tree_arch_edges_flags_edges_between_separate_trees() {
+        let mut g = ReflexionGraph::new();
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let svc = g.add_node(Node::new("Svc", SubgraphKind::Architecture, Some(app))).unwrap();
+        let api = g.add_node(Node::new("Api", SubgraphKind::Architecture, Some(app))).unwrap();
+        let infra = g.add_node(Node::new("Infra", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, Some(infra))).unwrap();
+
+        let same_tree = g
+            .add_edge(Edge::new(svc, api, EdgeKind::depends_on(), SubgraphKind::Architecture))
+            .unwrap();
+        let cross = g
+            .add_edge(Edge::new(svc, db, EdgeKind::depends_on(), SubgraphKind::Architecture))
+            .unwrap();
+        let roots = g
+            .add_edge(Edge::new(app, infra, EdgeKind::depends_on(), SubgraphKind::Architecture))
+            .unwrap();
+
+        let flagged = g.cross_tree_arch_edges();
+        assert!(!flagged.contains(&same_tree));
+        assert_eq!(flagged, vec![cross, roots]);
+
+        //with the implicit top-level container, root-to-root edges are fine
+        assert_eq!(g.cross_tree_arch_edges_with(true), vec![cross]);
+    }
+
+    #[test]
+    fn ambiguous_convergences_lists_all_candidates_most_specific_first() {
+        use crate::core::classify::AnalysisOptions;
+
+        let mut g = ReflexionGraph::new();
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let svc = g.add_node(Node::new("Svc", SubgraphKind::Architecture, Some(app))).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        let coarse = g.add_edge(Edge::new(app, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let fine = g.add_edge(Edge::new(svc, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+
+        let s = g.add_node(Node::new("s", SubgraphKind::Implementation, None)).unwrap();
+        let d = g.add_node(Node::new("d", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(s, svc).unwrap();
+        g.set_mapping(d, db).unwrap();
+        let e = g.add_edge(Edge::new(s, d, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+
+        assert_eq!(g.ambiguous_convergences(), vec![(e, vec![fine, coarse])]);
+        assert_eq!(g.edge(fine).unwrap().counter(), 1);
+        assert_eq!(g.edge(coarse).unwrap().counter(), 0);
+
+        g.init_states();
+        assert!(g.ambiguous_convergences().is_empty());
+    }
+
+    #[test]
+    fn set_parent_moves_node_and_updates_children_lists() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let c = g.add_node(Node::new("C", SubgraphKind::Architecture, Some(a))).unwrap();
+
+        g.set_parent(c, Some(b)).unwrap();
+        assert!(g.node(a).unwrap().children().is_empty());
+        assert_eq!(g.node(b).unwrap().children(), &[c]);
+        assert_eq!(g.node(c).unwrap().parent(), Some(b));
+
+        g.set_parent(c, None).unwrap();
+        assert!(g.node(b).unwrap().children().is_empty());
+        assert_eq!(g.node(c).unwrap().parent(), None);
+    }
+
+    #[test]
+    fn set_parent_rejects_cycles_and_unknown_nodes() {
+        use crate::core::graph::GraphError;
+
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, Some(a))).unwrap();
+        let c = g.add_node(Node::new("C", SubgraphKind::Architecture, Some(b))).unwrap();
+
+        assert_eq!(g.set_parent(a, Some(c)).unwrap_err(), GraphError::HierarchyCycle { child: a, parent: c });
+        assert_eq!(g.set_parent(a, Some(a)).unwrap_err(), GraphError::HierarchyCycle { child: a, parent: a });
+        assert_eq!(g.set_parent(a, Some(999)).unwrap_err(), GraphError::ParentNotFound(999));
+        assert_eq!(g.set_parent(999, None).unwrap_err(), GraphError::NodeNotFound(999));
+
+        //nothing changed
+        assert_eq!(g.ancestors(c), vec![b, a]);
+        assert_eq!(g.node(a).unwrap().parent(), None);
+    }
+
+    #[test]
+    fn ensure_root_creates_once_and_optionally_adopts_roots() {
+        let mut g = ReflexionGraph::new();
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let infra = g.add_node(Node::new("Infra", SubgraphKind::Architecture, None)).unwrap();
+        let svc = g.add_node(Node::new("Svc", SubgraphKind::Architecture, Some(app))).unwrap();
+        let code = g.add_node(Node::new("code", SubgraphKind::Implementation, None)).unwrap();
+
+        let root = g.ensure_root("System", SubgraphKind::Architecture, false);
+        assert_eq!(g.node(app).unwrap().parent(), None);
+
+        //second call returns the same node and adopts this time
+        assert_eq!(g.ensure_root("System", SubgraphKind::Architecture, true), root);
+        assert_eq!(g.node(root).unwrap().children(), &[app, infra]);
+        assert_eq!(g.node(infra).unwrap().parent(), Some(root));
+        assert_eq!(g.node(svc).unwrap().parent(), Some(app));
+        assert_eq!(g.node(code).unwrap().parent(), None);
+        assert_eq!(g.node(root).unwrap().parent(), None);
+
+        //one tree now, so nothing is cross-tree anymore
+        g.add_edge(Edge::new(svc, infra, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+        assert!(g.cross_tree_arch_edges().is_empty());
+    }
+
+    #[test]
+    fn cross_tree_arch_edges_ignores_impl_edges() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("a", SubgraphKind::Implementation, None)).unwrap();
+        let b = g.add_node(Node::new("b", SubgraphKind::Implementation, None)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        assert!(g.cross_tree_arch_edges().is_empty());
+    }
+
+    #[test]
+    fn lift_edge_maps_endpoints_without_analyzing() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        let k = g.add_node(Node::new("k", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i, a).unwrap();
+        g.set_mapping(j, b).unwrap();
+        let mapped = g.add_edge(Edge::new(i, j, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        let half = g.add_edge(Edge::new(i, k, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        let spec = g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+
+        assert_eq!(g.lift_edge(mapped), Some((a, b, EdgeKind::calls())));
+        assert_eq!(g.lift_edge(half), None);
+        assert_eq!(g.lift_edge(spec), None);
+        assert_eq!(g.lift_edge(999), None);
+        assert!(g.edges_in_subgraph(SubgraphKind::Propagated).is_empty());
+    }
+
+    #[test]
+    fn arch_footprint_collects_distinct_lifted_targets() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let c = g.add_node(Node::new("C", SubgraphKind::Architecture, None)).unwrap();
+        let cls = g.add_node(Node::new("cls", SubgraphKind::Implementation, None)).unwrap();
+        let mut targets = Vec::new();
+        for (name, arch) in [("x", Some(b)), ("y", Some(b)), ("z", Some(c)), ("w", None)] {
+            let n = g.add_node(Node::new(name, SubgraphKind::Implementation, None)).unwrap();
+            if let Some(arch) = arch {
+                g.set_mapping(n, arch).unwrap();
+            }
+            targets.push(n);
+        }
+        g.set_mapping(cls, a).unwrap();
+        for &t in &targets {
+            g.add_edge(Edge::new(cls, t, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        }
+        g.add_edge(Edge::new(targets[0], cls, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        assert_eq!(g.arch_footprint(cls), [b, c].into_iter().collect());
+        assert!(g.arch_footprint(targets[3]).is_empty());
+        assert!(g.arch_footprint(999).is_empty());
+    }
+
+    #[test]
+    fn lca_finds_the_nearest_shared_container() {
+        let mut g = ReflexionGraph::new();
+        let root = g.add_node(Node::new("root", SubgraphKind::Architecture, None)).unwrap();
+        let left = g.add_node(Node::new("left", SubgraphKind::Architecture, Some(root))).unwrap();
+        let l1 = g.add_node(Node::new("l1", SubgraphKind::Architecture, Some(left))).unwrap();
+        let l2 = g.add_node(Node::new("l2", SubgraphKind::Architecture, Some(left))).unwrap();
+        let right = g.add_node(Node::new("right", SubgraphKind::Architecture, Some(root))).unwrap();
+        let other = g.add_node(Node::new("other", SubgraphKind::Architecture, None)).unwrap();
+
+        assert_eq!(g.lca(l1, l2), Some(left));
+        assert_eq!(g.lca(l1, right), Some(root));
+        assert_eq!(g.lca(left, l2), Some(left));
+        assert_eq!(g.lca(l1, l1), Some(l1));
+        assert_eq!(g.lca(l1, other), None);
+        assert_eq!(g.lca(l1, 999), None);
+    }
+
+    #[test]
+    fn symmetric_spec_converges_either_way_and_reports_one_way_use() {
+        use crate::core::classify::AnalysisOptions;
+        use crate::core::state::EdgeState;
+
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let c = g.add_node(Node::new("C", SubgraphKind::Architecture, None)).unwrap();
+        let talks = EdgeKind::from("communicates_with");
+        let ab = g.add_edge(Edge::new(a, b, talks.clone(), SubgraphKind::Architecture).with_symmetric()).unwrap();
+        let bc = g.add_edge(Edge::new(b, c, talks.clone(), SubgraphKind::Architecture).with_symmetric()).unwrap();
+        let ac = g.add_edge(Edge::new(a, c, talks.clone(), SubgraphKind::Architecture)).unwrap();
+        let mut imp = |name: &str, arch| {
+            let n = g.add_node(Node::new(name, SubgraphKind::Implementation, None)).unwrap();
+            g.set_mapping(n, arch).unwrap();
+            n
+        };
+        let (ia, ib, ic) = (imp("a", a), imp("b", b), imp("c", c));
+        for (from, to) in [(ib, ia), (ib, ic), (ic, ib), (ic, ia)] {
+            g.add_edge(Edge::new(from, to, talks.clone(), SubgraphKind::Implementation)).unwrap();
+        }
+        g.analyze(&AnalysisOptions::default());
+
+        //B -> A realizes the symmetric A - B, C -> A does not realize the directed A -> C
+        assert_eq!(g.edge(ab).unwrap().state(), EdgeState::Convergent);
+        assert_eq!(g.edge(bc).unwrap().state(), EdgeState::Convergent);
+        assert_eq!(g.edge(ac).unwrap().state(), EdgeState::Absent);
+        assert_eq!(g.edges_with_state_in(SubgraphKind::Propagated, EdgeState::Divergent).len(), 1);
+        assert_eq!(g.asymmetric_realizations(), vec![(ab, b, a)]);
+    }
+
+    #[test]
+    fn propagation_boundaries_stop_the_spec_match_climb() {
+        use crate::core::classify::AnalysisOptions;
+        use crate::core::state::EdgeState;
+        use crate::core::types::NodeKind;
+
+        let mut g = ReflexionGraph::new();
+        let sys = g.add_node(Node::new("Sys", SubgraphKind::Architecture, None)).unwrap();
+        let svc = g.add_node(Node::new("Svc", SubgraphKind::Architecture, Some(sys)).with_kind(NodeKind::ServiceNode)).unwrap();
+        let handler = g.add_node(Node::new("Handler", SubgraphKind::Architecture, Some(svc))).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        let coarse = g.add_edge(Edge::new(sys, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let h = g.add_node(Node::new("h", SubgraphKind::Implementation, None)).unwrap();
+        let d = g.add_node(Node::new("d", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(h, handler).unwrap();
+        g.set_mapping(d, db).unwrap();
+        let call = g.add_edge(Edge::new(h, d, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        //propagated edges are synthesized anew by every run
+        let propagated = |g: &ReflexionGraph| {
+            let prop = g.find_arch_edge(handler, db, &EdgeKind::calls(), SubgraphKind::Propagated).unwrap();
+            g.edge(prop).unwrap().state()
+        };
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(propagated(&g), EdgeState::Convergent);
+        assert_eq!(g.edge(coarse).unwrap().state(), EdgeState::Convergent);
+
+        //the service is as far up as its dependencies are judged: Sys's rule doesn't cover it
+        g.set_propagation_boundary_kinds(&[NodeKind::ServiceNode]);
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(propagated(&g), EdgeState::Divergent);
+        assert_eq!(g.edge(coarse).unwrap().state(), EdgeState::Absent);
+        assert_eq!(g.edge(call).unwrap().state(), EdgeState::Divergent);
+
+        //a rule on the boundary itself still counts
+        let on_svc = g.add_edge(Edge::new(svc, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.edge(on_svc).unwrap().state(), EdgeState::Convergent);
+        assert_eq!(g.edge(call).unwrap().state(), EdgeState::Convergent);
+    }
+}
@@ -1,11 +1,22 @@
 // maps_to + rule based mapping
+use std::collections::{HashMap, HashSet};
 use crate::core::types::NodeId;
-use crate::core::graph::Node;
 use crate::core::graph::ReflexionGraph;
 use crate::core::graph::GraphError;
-use crate::core::types::SubgraphKind;
+use crate::core::classify::AnalysisOptions;
+use crate::core::types::{MappingKind, NodeKind, SubgraphKind};
+use crate::core::store::GraphStore;
+
+//a problem with one pair of a mapping import, found without touching the graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MappingIssue {
+    pub index: usize, //position of the pair in the imported list
+    pub impl_node: NodeId,
+    pub arch_node: NodeId,
+    pub error: GraphError, //NodeNotFound, WrongSubgraph or MappingAlreadyExists
+}
 
-impl ReflexionGraph {
+impl<S: GraphStore> ReflexionGraph<S> {
     //store/overwrite a mapping from implementation node to architecture node
     //1. impl_node must exist and must be in Implementation subgraph.
     //2. arch_node must exist and must be in Architecture subgraph.
@@ -16,7 +27,7 @@ impl ReflexionGraph {
 
 
     //validation helpers
-    fn expect_impl_node(&self, impl_node: NodeId) -> Result<(), GraphError> {
+    pub(crate) fn expect_impl_node(&self, impl_node: NodeId) -> Result<(), GraphError> {
         let sg = self.node_subgraph(impl_node)?;
         if sg != SubgraphKind::Implementation {
             return Err(GraphError::WrongSubgraph { node: impl_node, expected: SubgraphKind::Implementation, found: sg, });
@@ -24,7 +35,7 @@ impl ReflexionGraph {
         Ok(())
     }
 
-    fn expect_arch_node(&self, arch_node: NodeId) -> Result<(), GraphError> {
+    pub(crate) fn expect_arch_node(&self, arch_node: NodeId) -> Result<(), GraphError> {
         let sg = self.node_subgraph(arch_node)?;
         if sg != SubgraphKind::Architecture {
             return Err(GraphError::WrongSubgraph { node: arch_node, expected: SubgraphKind::Architecture, found: sg, });
@@ -34,19 +45,166 @@ impl ReflexionGraph {
 
 
     pub fn set_mapping(&mut self, impl_node: NodeId, arch_node: NodeId) -> Result<(), GraphError> {
+        self.insert_mapping(impl_node, arch_node, MappingKind::Confirmed)
+    }
+
+    //like set_mapping, but only a heuristic guess awaiting review. an identical
+    //existing mapping keeps its kind (a confirmed mapping is never downgraded).
+    pub fn propose_mapping(&mut self, impl_node: NodeId, arch_node: NodeId) -> Result<(), GraphError> {
+        self.insert_mapping(impl_node, arch_node, MappingKind::Proposed)
+    }
+
+    //every maps_to write of the crate goes through put_mapping / take_mapping (or a
+    //rebuild_arch_to_impls after bulk edits), so arch_to_impls stays its exact inverse
+    pub(crate) fn put_mapping(&mut self, impl_node: NodeId, arch_node: NodeId) -> Option<NodeId> {
+        let old = self.maps_to.insert(impl_node, arch_node);
+        if let Some(old) = old {
+            self.unindex_implementor(old, impl_node);
+        }
+        let list = self.arch_to_impls.entry(arch_node).or_default();
+        if let Err(pos) = list.binary_search(&impl_node) {
+            list.insert(pos, impl_node);
+        }
+        old
+    }
+
+    pub(crate) fn take_mapping(&mut self, impl_node: NodeId) -> Option<NodeId> {
+        let old = self.maps_to.remove(&impl_node)?;
+        self.unindex_implementor(old, impl_node);
+        Some(old)
+    }
+
+    fn unindex_implementor(&mut self, arch_node: NodeId, impl_node: NodeId) {
+        if let Some(list) = self.arch_to_impls.get_mut(&arch_node) {
+            list.retain(|&i| i != impl_node);
+            if list.is_empty() {
+                self.arch_to_impls.remove(&arch_node);
+            }
+        }
+    }
+
+    pub(crate) fn rebuild_arch_to_impls(&mut self) {
+        self.arch_to_impls.clear();
+        for (&i, &a) in &self.maps_to {
+            self.arch_to_impls.entry(a).or_default().push(i);
+        }
+        for list in self.arch_to_impls.values_mut() {
+            list.sort_unstable();
+        }
+    }
+
+    //impl nodes mapped directly onto `arch_node` ("which classes implement Auth?"), sorted,
+    //from the reverse index: no scan of maps_to. empty for unmapped or unknown nodes. mappings
+    //inherited by descendants (inherit_mappings) are not listed.
+    pub fn implementors(&self, arch_node: NodeId) -> &[NodeId] {
+        self.arch_to_impls.get(&arch_node).map_or(&[], Vec::as_slice)
+    }
+
+    fn insert_mapping(&mut self, impl_node: NodeId, arch_node: NodeId, kind: MappingKind) -> Result<(), GraphError> {
         self.expect_impl_node(impl_node)?;        
         self.expect_arch_node(arch_node)?;
 
         match self.maps_to.get(&impl_node).copied() {
             None => {
-                self.maps_to.insert(impl_node, arch_node);
+                self.put_mapping(impl_node, arch_node);
+                if kind == MappingKind::Proposed {
+                    self.proposed.insert(impl_node);
+                }
+                self.invalidate();
+                self.debug_assert_mapping(impl_node, None);
                 Ok(())            
             }
-            Some(old_arch) if old_arch == arch_node => Ok(()), //Idempotent if mapping is identical, no overwrites
+            Some(old_arch) if old_arch == arch_node => { //Idempotent if mapping is identical, no overwrites
+                if kind == MappingKind::Confirmed {
+                    self.confirm_mapping(impl_node)?; //a human repeating a proposal confirms it
+                }
+                Ok(())
+            }
             Some(old_arch) => Err(GraphError::MappingAlreadyExists { impl_node, old_arch, new_arch: arch_node, }),
         }
     }
 
+    //review step: turns a Proposed mapping into a Confirmed one.
+    //Ok(true) if it was proposed, Ok(false) if it already was confirmed or isn't mapped.
+    pub fn confirm_mapping(&mut self, impl_node: NodeId) -> Result<bool, GraphError> {
+        self.expect_impl_node(impl_node)?;
+        let was_proposed = self.proposed.remove(&impl_node);
+        if was_proposed {
+            self.invalidate();
+        }
+        Ok(was_proposed)
+    }
+
+    //None if unmapped. entries put straight into maps_to count as Confirmed.
+    pub fn mapping_kind(&self, impl_node: NodeId) -> Option<MappingKind> {
+        self.maps_to.get(&impl_node)?;
+        if self.proposed.contains(&impl_node) {
+            Some(MappingKind::Proposed)
+        } else {
+            Some(MappingKind::Confirmed)
+        }
+    }
+
+    //review queue: (impl_node, arch_node) pairs still awaiting confirmation, sorted
+    pub fn proposed_mappings(&self) -> Vec<(NodeId, NodeId)> {
+        let mut queue: Vec<(NodeId, NodeId)> = self
+            .proposed
+            .iter()
+            .filter_map(|&i| self.maps_to.get(&i).map(|&a| (i, a)))
+            .collect();
+        queue.sort_unstable();
+        queue
+    }
+
+    //mapping inheritance: the node's own mapping, else the mapping of its nearest mapped
+    //ancestor (parent, grandparent, ...), so mapping a package covers all its classes.
+    //None if nothing on the parent chain (up to a propagation boundary) is mapped.
+    pub fn resolve_mapping(&self, impl_node: NodeId) -> Option<NodeId> {
+        self.lift_target(impl_node, &AnalysisOptions { inherit_mappings: true, ..Default::default() })
+    }
+
+    //granularity control for the climbs up the containment tree: a node of one of these
+    //kinds (e.g. ServiceNode in the architecture, FunctionNode in the code) is as high as
+    //a climb from below it goes. analyze matches each propagated edge against the spec
+    //edges on its endpoints' arch ancestors (see matching_spec_edges); past a boundary it
+    //doesn't look, so a rule declared above the boundary doesn't cover what's below it
+    //and those dependencies turn Divergent. with inherit_mappings, the search for a
+    //mapped impl ancestor stops the same way. the boundary node itself still counts in
+    //both: rules on it and its own mapping apply. nodes without a kind are never
+    //boundaries. replaces the previous set.
+    pub fn set_propagation_boundary_kinds(&mut self, kinds: &[NodeKind]) {
+        self.propagation_boundaries = kinds.iter().cloned().collect();
+        self.invalidate();
+    }
+
+    pub(crate) fn is_propagation_boundary(&self, node: NodeId) -> bool {
+        self.store.get_node(node).and_then(|n| n.kind.as_ref()).is_some_and(|k| self.propagation_boundaries.contains(k))
+    }
+
+    //the arch node an impl node lifts to in an analysis run (None = unmapped for that run):
+    //its own mapping, then (inherit_mappings) the nearest ancestor's up to the first
+    //propagation boundary. ignore_proposed skips proposed mappings at every level.
+    pub(crate) fn lift_target(&self, impl_node: NodeId, options: &AnalysisOptions) -> Option<NodeId> {
+        let direct = |n: &NodeId| {
+            if options.ignore_proposed && self.proposed.contains(n) {
+                return None;
+            }
+            self.maps_to.get(n).copied()
+        };
+        if !options.inherit_mappings {
+            return direct(&impl_node);
+        }
+        for n in self.self_and_ancestors(impl_node) {
+            if let Some(arch) = direct(&n) {
+                return Some(arch);
+            }
+            if self.is_propagation_boundary(n) {
+                break;
+            }
+        }
+        None
+    }
+
     //returns Ok(Some(arch)) if mapped, Ok(None) if not mapped. errors only if impl_node doesn't
     //exist or wrong subgraph.
     pub fn get_arch_node(&self, impl_node: NodeId) -> Result<Option<NodeId>, GraphError> {
@@ -61,22 +219,150 @@ impl ReflexionGraph {
 
     pub fn remove_mapping(&mut self, impl_node: NodeId) -> Result<Option<NodeId>, GraphError> {
         self.expect_impl_node(impl_node)?;
-        Ok(self.maps_to.remove(&impl_node))
+        let removed = self.take_mapping(impl_node);
+        self.debug_assert_mapping(impl_node, removed);
+        self.proposed.remove(&impl_node);
+        if removed.is_some() {
+            self.invalidate();
+        }
+        Ok(removed)
     }
 
     pub fn clear_mappings(&mut self) {
-        self.maps_to.clear()
+        self.maps_to.clear();
+        self.arch_to_impls.clear();
+        self.proposed.clear();
+        self.invalidate();
     }
 
     pub fn mapping_len(&self) -> usize {
         self.maps_to.len()
     }
 
+    //the impl -> arch table itself, read-only: writes go through set_mapping and friends,
+    //which keep the implementors index in sync
+    pub fn maps_to(&self) -> &HashMap<NodeId, NodeId> {
+        &self.maps_to
+    }
+
     //for reports
     pub fn iter_mapping(&self) -> impl Iterator<Item=(NodeId, NodeId)> + '_ {
         self.maps_to.iter().map(|(&i, &a)| (i, a))
     }
 
+    //(impl_node, arch_node) pairs, e.g. for a traceability matrix. unordered, like maps_to
+    pub fn mappings(&self) -> impl Iterator<Item=(NodeId, NodeId)> + '_ {
+        self.iter_mapping()
+    }
+
+    //mappings as sorted (impl name, arch name) pairs for stable reporting
+    pub fn mapping_matrix(&self) -> Vec<(String, String)> {
+        let mut matrix: Vec<(String, String)> = self
+            .mappings()
+            .map(|(i, a)| (self.node_name(i).to_string(), self.node_name(a).to_string()))
+            .collect();
+        matrix.sort();
+        matrix
+    }
+
+    //refactoring hint: arch modules whose impl nodes (mapped as in the last analyze) fall
+    //apart into clusters with no dependency between them, maybe two responsibilities under
+    //one name. clusters are the connected components of the impl edges among the module's
+    //own nodes, direction ignored and structural kinds skipped. each cluster is sorted,
+    //clusters by smallest id, modules by id.
+    pub fn split_candidates(&self) -> Vec<(NodeId, Vec<Vec<NodeId>>)> {
+        let mut by_module: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+        for n in self.store.iter_nodes().filter(|n| n.subgraph == SubgraphKind::Implementation) {
+            if let Some(arch) = self.lift_target(n.id, &self.last_options) {
+                by_module.entry(arch).or_default().push(n.id);
+            }
+        }
+
+        let mut out = Vec::new();
+        for (module, mut members) in by_module {
+            members.sort_unstable();
+            let inside: HashSet<NodeId> = members.iter().copied().collect();
+            let mut adjacent: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+            for &n in &members {
+                for &eid in self.store.out_edges(n, SubgraphKind::Implementation) {
+                    let e = self.edge_at(eid);
+                    if inside.contains(&e.to) && !self.is_structural(&e.kind) {
+                        adjacent.entry(n).or_default().push(e.to);
+                        adjacent.entry(e.to).or_default().push(n);
+                    }
+                }
+            }
+
+            let mut seen: HashSet<NodeId> = HashSet::new();
+            let mut clusters: Vec<Vec<NodeId>> = Vec::new();
+            for &start in &members {
+                if !seen.insert(start) {
+                    continue;
+                }
+                let (mut cluster, mut stack) = (vec![start], vec![start]);
+                while let Some(n) = stack.pop() {
+                    for &m in adjacent.get(&n).into_iter().flatten() {
+                        if seen.insert(m) {
+                            cluster.push(m);
+                            stack.push(m);
+                        }
+                    }
+                }
+                cluster.sort_unstable();
+                clusters.push(cluster);
+            }
+            if clusters.len() > 1 {
+                out.push((module, clusters));
+            }
+        }
+        out.sort_unstable();
+        out
+    }
+
+    //dry run of importing `pairs` with set_mapping semantics: reports unknown nodes,
+    //wrong-subgraph endpoints and remaps (of existing mappings or of an earlier pair in
+    //the same list) without mutating anything. repeating an identical mapping is fine.
+    pub fn validate_mappings(&self, pairs: &[(NodeId, NodeId)]) -> Vec<MappingIssue> {
+        let mut pending: HashMap<NodeId, NodeId> = HashMap::new();
+        let mut issues = Vec::new();
+
+        for (index, &(impl_node, arch_node)) in pairs.iter().enumerate() {
+            let check = self
+                .expect_impl_node(impl_node)
+                .and_then(|_| self.expect_arch_node(arch_node))
+                .and_then(|_| {
+                    let current = pending.get(&impl_node).or(self.maps_to.get(&impl_node));
+                    match current.copied() {
+                        Some(old_arch) if old_arch != arch_node => Err(GraphError::MappingAlreadyExists {
+                            impl_node,
+                            old_arch,
+                            new_arch: arch_node,
+                        }),
+                        _ => Ok(()),
+                    }
+                });
+
+            match check {
+                Ok(()) => {
+                    pending.insert(impl_node, arch_node);
+                }
+                Err(error) => issues.push(MappingIssue { index, impl_node, arch_node, error }),
+            }
+        }
+        issues
+    }
+
+    //commit the pairs validate_mappings accepts, skip the others and report them
+    pub fn apply_mappings(&mut self, pairs: &[(NodeId, NodeId)]) -> Vec<MappingIssue> {
+        let issues = self.validate_mappings(pairs);
+        for (index, &(impl_node, arch_node)) in pairs.iter().enumerate() {
+            if issues.iter().all(|issue| issue.index != index) {
+                self.set_mapping(impl_node, arch_node).expect("validated above");
+            }
+        }
+        issues
+    }
+
     fn validate_impl_node(&self, impl_node: NodeId) -> Result<(), GraphError> {
         let found = self.node_subgraph(impl_node)?;
 
@@ -114,7 +400,11 @@ impl ReflexionGraph {
         self.validate_impl_node(impl_node)?;
         self.validate_arch_node(arch_node)?;
 
-        Ok(self.maps_to.insert(impl_node, arch_node))
+        self.invalidate();
+        self.proposed.remove(&impl_node);
+        let previous = self.put_mapping(impl_node, arch_node);
+        self.debug_assert_mapping(impl_node, previous);
+        Ok(previous)
     }
 
     pub fn validate_all_mappings(&self) -> Result<(), GraphError> {
@@ -126,6 +416,45 @@ impl ReflexionGraph {
     }
 }
 
+//removes nodes, which only the default store supports (see remove_node)
+impl ReflexionGraph {
+    //trim extraction noise before reporting: removes (via remove_node, so every index stays
+    //consistent) impl leaves without a mapping (resolved as in the last analyze) and without
+    //an impl edge to or from a mapped node, repeating until none is left, since a removal
+    //can turn its parent into such a leaf. returns how many nodes went; a second call
+    //removes nothing. nodes with children are kept, they may still inherit their way in.
+    pub fn prune_unmapped_leaves(&mut self) -> usize {
+        let mut removed = 0;
+        loop {
+            let mapped = |n: NodeId| self.lift_target(n, &self.last_options).is_some();
+            let mut near_mapped: HashSet<NodeId> = HashSet::new();
+            for e in self.store.iter_edges().filter(|e| e.subgraph == SubgraphKind::Implementation) {
+                if mapped(e.to) {
+                    near_mapped.insert(e.from);
+                }
+                if mapped(e.from) {
+                    near_mapped.insert(e.to);
+                }
+            }
+            let mut prune: Vec<NodeId> = self
+                .store
+                .iter_nodes()
+                .filter(|n| n.subgraph == SubgraphKind::Implementation && n.children.is_empty())
+                .filter(|n| !mapped(n.id) && !near_mapped.contains(&n.id))
+                .map(|n| n.id)
+                .collect();
+            if prune.is_empty() {
+                return removed;
+            }
+            prune.sort_unstable();
+            for n in prune {
+                self.remove_node(n).expect("collected above");
+                removed += 1;
+            }
+        }
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -165,8 +494,8 @@ mod tests {
         assert_eq!(g.get_arch_node(impl3).unwrap(), None);
 
         // mapped?
-        assert_eq!(g.is_mapped(impl1).unwrap(), true);
-        assert_eq!(g.is_mapped(impl3).unwrap(), false);
+        assert!(g.is_mapped(impl1).unwrap());
+        assert!(!g.is_mapped(impl3).unwrap());
     }
 
     #[test]
@@ -200,4 +529,224 @@ mod tests {
             other => panic!("unexpected error: {}", other),
         }
     }
+
+    #[test]
+    fn mapping_matrix_is_sorted_by_name() {
+        let mut g = ReflexionGraph::new();
+
+        let zeta = g
+            .add_node(Node::new("zeta", SubgraphKind::Implementation, None))
+            .unwrap();
+        let alpha = g
+            .add_node(Node::new("alpha", SubgraphKind::Implementation, None))
+            .unwrap();
+        let core = g
+            .add_node(Node::new("Core", SubgraphKind::Architecture, None))
+            .unwrap();
+
+        g.set_mapping(zeta, core).unwrap();
+        g.set_mapping(alpha, core).unwrap();
+
+        let mut pairs: Vec<(NodeId, NodeId)> = g.mappings().collect();
+        pairs.sort();
+        assert_eq!(pairs, vec![(zeta, core), (alpha, core)]);
+
+        assert_eq!(
+            g.mapping_matrix(),
+            vec![
+                ("alpha".to_string(), "Core".to_string()),
+                ("zeta".to_string(), "Core".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_mappings_reports_without_mutating_and_apply_commits_valid_pairs() {
+        let mut g = ReflexionGraph::new();
+
+        let impl1 = g
+            .add_node(Node::new("impl1", SubgraphKind::Implementation, None))
+            .unwrap();
+        let impl2 = g
+            .add_node(Node::new("impl2", SubgraphKind::Implementation, None))
+            .unwrap();
+        let arch1 = g
+            .add_node(Node::new("arch1", SubgraphKind::Architecture, None))
+            .unwrap();
+        let arch2 = g
+            .add_node(Node::new("arch2", SubgraphKind::Architecture, None))
+            .unwrap();
+        g.set_mapping(impl1, arch1).unwrap();
+
+        let pairs = [
+            (impl1, arch1), // identical, fine
+            (impl1, arch2), // remap of an existing mapping
+            (999, arch1),   // unknown node
+            (arch1, arch2), // wrong subgraph
+            (impl2, arch2), // fine
+            (impl2, arch1), // remap of the pair above
+        ];
+
+        let issues = g.validate_mappings(&pairs);
+        let indices: Vec<usize> = issues.iter().map(|i| i.index).collect();
+        assert_eq!(indices, vec![1, 2, 3, 5]);
+        assert_eq!(
+            issues[0].error,
+            GraphError::MappingAlreadyExists { impl_node: impl1, old_arch: arch1, new_arch: arch2 }
+        );
+        assert_eq!(issues[1].error, GraphError::NodeNotFound(999));
+        assert!(matches!(issues[2].error, GraphError::WrongSubgraph { .. }));
+        assert_eq!(
+            issues[3].error,
+            GraphError::MappingAlreadyExists { impl_node: impl2, old_arch: arch2, new_arch: arch1 }
+        );
+        assert_eq!(g.mapping_len(), 1);
+
+        assert_eq!(g.apply_mappings(&pairs), issues);
+        assert_eq!(g.get_arch_node(impl1).unwrap(), Some(arch1));
+        assert_eq!(g.get_arch_node(impl2).unwrap(), Some(arch2));
+    }
+
+    #[test]
+    fn proposed_mappings_form_a_review_queue() {
+        let mut g = ReflexionGraph::new();
+
+        let impl1 = g
+            .add_node(Node::new("impl1", SubgraphKind::Implementation, None))
+            .unwrap();
+        let impl2 = g
+            .add_node(Node::new("impl2", SubgraphKind::Implementation, None))
+            .unwrap();
+        let arch1 = g
+            .add_node(Node::new("arch1", SubgraphKind::Architecture, None))
+            .unwrap();
+
+        g.propose_mapping(impl1, arch1).unwrap();
+        g.propose_mapping(impl1, arch1).unwrap(); // still only proposed
+        g.set_mapping(impl2, arch1).unwrap();
+        g.propose_mapping(impl2, arch1).unwrap(); // no downgrade
+
+        assert_eq!(g.mapping_kind(impl1), Some(MappingKind::Proposed));
+        assert_eq!(g.mapping_kind(impl2), Some(MappingKind::Confirmed));
+        assert_eq!(g.proposed_mappings(), vec![(impl1, arch1)]);
+
+        assert!(g.confirm_mapping(impl1).unwrap());
+        assert!(!g.confirm_mapping(impl1).unwrap());
+        assert!(g.proposed_mappings().is_empty());
+
+        g.propose_mapping(impl1, arch1).unwrap(); // already confirmed, stays so
+        assert_eq!(g.mapping_kind(impl1), Some(MappingKind::Confirmed));
+
+        g.remove_mapping(impl1).unwrap();
+        assert_eq!(g.mapping_kind(impl1), None);
+    }
+
+    #[test]
+    fn inherited_mappings_stop_at_propagation_boundaries() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let f = g.add_node(Node::new("A_f", SubgraphKind::Architecture, None)).unwrap();
+        let class = g.add_node(Node::new("Cls", SubgraphKind::Implementation, None)).unwrap();
+        let func = g.add_node(Node::new("Cls::f", SubgraphKind::Implementation, Some(class)).with_kind(NodeKind::FunctionNode)).unwrap();
+        let inner = g.add_node(Node::new("Cls::f::closure", SubgraphKind::Implementation, Some(func))).unwrap();
+        g.set_mapping(class, a).unwrap();
+        let inherit = AnalysisOptions { inherit_mappings: true, ..Default::default() };
+
+        assert_eq!(g.lift_target(inner, &inherit), Some(a));
+        g.set_propagation_boundary_kinds(&[NodeKind::FunctionNode]);
+        assert_eq!(g.lift_target(inner, &inherit), None);
+        assert_eq!(g.lift_target(func, &inherit), None);
+        assert_eq!(g.resolve_mapping(inner), None);
+        //the boundary's own mapping still reaches everything below it
+        g.set_mapping(func, f).unwrap();
+        assert_eq!(g.lift_target(inner, &inherit), Some(f));
+    }
+
+    #[test]
+    fn split_candidates_lists_modules_with_disjoint_impl_clusters() {
+        use crate::core::graph::Edge;
+        use crate::core::types::EdgeKind;
+
+        let mut g = ReflexionGraph::new();
+        let billing = g.add_node(Node::new("Billing", SubgraphKind::Architecture, None)).unwrap();
+        let ui = g.add_node(Node::new("Ui", SubgraphKind::Architecture, None)).unwrap();
+        let mut imp = |name: &str, arch| {
+            let n = g.add_node(Node::new(name, SubgraphKind::Implementation, None)).unwrap();
+            g.set_mapping(n, arch).unwrap();
+            n
+        };
+        let (invoice, tax, mail, smtp) = (imp("invoice", billing), imp("tax", billing), imp("mail", billing), imp("smtp", billing));
+        let (form, view) = (imp("form", ui), imp("view", ui));
+        g.add_edge(Edge::new(tax, invoice, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(mail, smtp, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        //a structural edge and one leaving the module don't join clusters
+        g.add_edge(Edge::new(form, view, EdgeKind::contains(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(view, tax, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(form, mail, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        assert_eq!(
+            g.split_candidates(),
+            vec![(billing, vec![vec![invoice, tax], vec![mail, smtp]]), (ui, vec![vec![form], vec![view]])]
+        );
+
+        g.add_edge(Edge::new(smtp, tax, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(view, form, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        assert!(g.split_candidates().is_empty());
+    }
+
+    #[test]
+    fn prune_unmapped_leaves_drops_isolated_unmapped_nodes_until_stable() {
+        use crate::core::graph::Edge;
+        use crate::core::types::EdgeKind;
+
+        let mut g = ReflexionGraph::new();
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let main = g.add_node(Node::new("main", SubgraphKind::Implementation, None)).unwrap();
+        let caller = g.add_node(Node::new("caller", SubgraphKind::Implementation, None)).unwrap();
+        let pkg = g.add_node(Node::new("vendor", SubgraphKind::Implementation, None)).unwrap();
+        let lib = g.add_node(Node::new("vendor::lib", SubgraphKind::Implementation, Some(pkg))).unwrap();
+        let noise = g.add_node(Node::new("noise", SubgraphKind::Implementation, None)).unwrap();
+        g.add_edge(Edge::new(caller, main, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(lib, noise, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.set_mapping(main, app).unwrap();
+
+        //lib and noise first, then vendor once it has no children left
+        assert_eq!(g.prune_unmapped_leaves(), 3);
+        for gone in [pkg, lib, noise] {
+            assert!(g.node(gone).is_none());
+        }
+        assert!(g.node(main).is_some() && g.node(caller).is_some());
+        assert_eq!(g.edges().count(), 1);
+        assert_eq!(g.prune_unmapped_leaves(), 0);
+    }
+
+    #[test]
+    fn implementors_follow_every_mapping_mutation() {
+        let mut g = ReflexionGraph::new();
+        let auth = g.add_node(Node::new("Auth", SubgraphKind::Architecture, None)).unwrap();
+        let ui = g.add_node(Node::new("Ui", SubgraphKind::Architecture, None)).unwrap();
+        let [login, token, view] = ["login", "token", "view"].map(|n| g.add_node(Node::new(n, SubgraphKind::Implementation, None)).unwrap());
+        g.set_mapping(token, auth).unwrap();
+        g.propose_mapping(login, auth).unwrap();
+        g.set_mapping(view, ui).unwrap();
+        assert_eq!(g.implementors(auth), &[login, token]);
+        assert!(g.implementors(999).is_empty());
+
+        g.set_mapping_overwrite(login, ui).unwrap();
+        assert_eq!((g.implementors(auth), g.implementors(ui)), (&[token][..], &[login, view][..]));
+        g.remove_mapping(token).unwrap();
+        assert!(g.implementors(auth).is_empty());
+        g.remove_node(view).unwrap();
+        assert_eq!(g.implementors(ui), &[login]);
+
+        g.set_mapping(token, auth).unwrap();
+        let remap = g.compact();
+        assert_eq!(g.implementors(remap.nodes[&auth]), &[remap.nodes[&token]]);
+        g.remove_node(remap.nodes[&ui]).unwrap();
+        assert_eq!(g.get_arch_node(remap.nodes[&login]), Ok(None));
+        assert_eq!(g.check_invariants(), Ok(()));
+
+        g.clear_mappings();
+        assert!(g.implementors(remap.nodes[&auth]).is_empty());
+    }
 }
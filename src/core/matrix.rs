@@ -0,0 +1,111 @@
+// module x module conformance matrix (the classic reflexion DSM view)
+use std::collections::HashMap;
+use crate::core::types::{NodeId, SubgraphKind};
+use crate::core::graph::ReflexionGraph;
+use crate::core::state::EdgeState;
+
+//square matrix over all arch modules, in bfs_order. cell (i, j) is the dominant (worst,
+//see EdgeState::severity) state among the spec and propagated edges from module i to
+//module j, None where nothing runs between them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConformanceMatrix {
+    modules: Vec<NodeId>,
+    labels: Vec<String>,
+    index: HashMap<NodeId, usize>,
+    cells: Vec<Option<EdgeState>>, //row major, modules.len()^2
+}
+
+impl ConformanceMatrix {
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    //row/column order
+    pub fn modules(&self) -> &[NodeId] {
+        &self.modules
+    }
+
+    //module names, same order as modules()
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    pub fn position(&self, module: NodeId) -> Option<usize> {
+        self.index.get(&module).copied()
+    }
+
+    //by row/column position; None also for positions out of range
+    pub fn cell(&self, row: usize, col: usize) -> Option<EdgeState> {
+        if row >= self.len() || col >= self.len() {
+            return None;
+        }
+        self.cells[row * self.len() + col]
+    }
+
+    //by module pair
+    pub fn get(&self, from: NodeId, to: NodeId) -> Option<EdgeState> {
+        self.cell(self.position(from)?, self.position(to)?)
+    }
+}
+
+impl ReflexionGraph {
+    //data for a dependency matrix view after analyze. structural kinds are skipped.
+    pub fn conformance_matrix(&self) -> ConformanceMatrix {
+        let modules = self.bfs_order(SubgraphKind::Architecture);
+        let labels = modules.iter().map(|&m| self.node_name(m).to_string()).collect();
+        let index: HashMap<NodeId, usize> = modules.iter().enumerate().map(|(i, &m)| (m, i)).collect();
+        let n = modules.len();
+        let mut cells: Vec<Option<EdgeState>> = vec![None; n * n];
+
+        for e in self.store.edges.values() {
+            if e.subgraph == SubgraphKind::Implementation || self.is_structural(&e.kind) {
+                continue;
+            }
+            let (Some(&row), Some(&col)) = (index.get(&e.from), index.get(&e.to)) else {
+                continue;
+            };
+            let cell = &mut cells[row * n + col];
+            *cell = Some(cell.map_or(e.state, |s| s.max(e.state)));
+        }
+
+        ConformanceMatrix { modules, labels, index, cells }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::state::EdgeState;
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn conformance_matrix_holds_the_worst_state_per_module_pair() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i, a).unwrap();
+        g.set_mapping(j, b).unwrap();
+        g.add_edge(Edge::new(i, j, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(j, i, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.analyze(&AnalysisOptions::default());
+
+        let m = g.conformance_matrix();
+        assert_eq!(m.modules(), &[a, b]);
+        assert_eq!(m.labels(), &["A".to_string(), "B".to_string()]);
+        //calls converges, depends_on is absent: the absence dominates
+        assert_eq!(m.get(a, b), Some(EdgeState::Absent));
+        assert_eq!(m.get(b, a), Some(EdgeState::Divergent));
+        assert_eq!(m.cell(0, 0), None);
+        assert_eq!(m.cell(2, 0), None);
+        assert_eq!(m.get(a, i), None);
+    }
+}
@@ -0,0 +1,303 @@
+// structural metrics over the adjacency indexes
+use std::collections::HashMap;
+use crate::core::types::{EdgeKind, NodeId, SubgraphKind};
+use crate::core::graph::{GraphError, ReflexionGraph};
+use crate::core::state::EdgeState;
+
+//number of edges per state, one named field per EdgeState
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StateCounts {
+    pub undefined: usize,
+    pub specified: usize,
+    pub convergent: usize,
+    pub absent: usize,
+    pub allowed_absent: usize,
+    pub allowed: usize,
+    pub divergent: usize,
+    pub unmapped: usize,
+    pub multiplicity_violation: usize,
+}
+
+impl StateCounts {
+    fn add(&mut self, state: EdgeState) {
+        let slot = match state {
+            EdgeState::Undefined => &mut self.undefined,
+            EdgeState::Specified => &mut self.specified,
+            EdgeState::Convergent => &mut self.convergent,
+            EdgeState::Absent => &mut self.absent,
+            EdgeState::AllowedAbsent => &mut self.allowed_absent,
+            EdgeState::Allowed => &mut self.allowed,
+            EdgeState::Divergent => &mut self.divergent,
+            EdgeState::Unmapped => &mut self.unmapped,
+            EdgeState::MultiplicityViolation => &mut self.multiplicity_violation,
+        };
+        *slot += 1;
+    }
+
+    pub fn total(&self) -> usize {
+        self.undefined
+            + self.specified
+            + self.convergent
+            + self.absent
+            + self.allowed_absent
+            + self.allowed
+            + self.divergent
+            + self.unmapped
+            + self.multiplicity_violation
+    }
+
+    //edges in a violation state (EdgeState::is_violation)
+    pub fn violations(&self) -> usize {
+        self.absent + self.divergent + self.multiplicity_violation
+    }
+}
+
+impl ReflexionGraph {
+    //number of outgoing edges of `node` in one subgraph (0 for unknown nodes)
+    pub fn fan_out(&self, node: NodeId, subgraph: SubgraphKind) -> usize {
+        match subgraph {
+            SubgraphKind::Implementation => self.store.impl_out.get(&node).map_or(0, |v| v.len()),
+            SubgraphKind::Architecture | SubgraphKind::Propagated => self
+                .store
+                .arch_out
+                .get(&node)
+                .map_or(0, |v| v.iter().filter(|eid| self.store.edges[eid].subgraph == subgraph).count()),
+        }
+    }
+
+    //number of incoming edges of `node` in one subgraph. there is no reverse index,
+    //so this scans the edges: use degree_distribution for whole-graph numbers
+    pub fn fan_in(&self, node: NodeId, subgraph: SubgraphKind) -> usize {
+        self.store.edges.values().filter(|e| e.subgraph == subgraph && e.to == node).count()
+    }
+
+    //in + out degree of `node` in one subgraph (a self-loop counts twice)
+    pub fn degree(&self, node: NodeId, subgraph: SubgraphKind) -> usize {
+        self.fan_out(node, subgraph) + self.fan_in(node, subgraph)
+    }
+
+    //degree of every node living on that side of the model (propagated edges connect
+    //architecture nodes), highest first, ties by id: central modules come first
+    pub fn degree_distribution(&self, subgraph: SubgraphKind) -> Vec<(NodeId, usize)> {
+        let node_side = match subgraph {
+            SubgraphKind::Propagated => SubgraphKind::Architecture,
+            other => other,
+        };
+
+        let mut degrees: HashMap<NodeId, usize> = self
+            .store
+            .nodes
+            .values()
+            .filter(|n| n.subgraph == node_side)
+            .map(|n| (n.id, 0))
+            .collect();
+        for e in self.store.edges.values().filter(|e| e.subgraph == subgraph) {
+            *degrees.entry(e.from).or_default() += 1;
+            *degrees.entry(e.to).or_default() += 1;
+        }
+
+        let mut out: Vec<(NodeId, usize)> = degrees.into_iter().collect();
+        out.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        out
+    }
+
+    //vocabulary of one subgraph: how many edges of each kind it has. comparing the
+    //Architecture and Implementation histograms shows which relations the spec talks
+    //about vs. what the code actually contains (input for a relation policy)
+    pub fn kind_histogram(&self, subgraph: SubgraphKind) -> HashMap<EdgeKind, usize> {
+        let mut histogram: HashMap<EdgeKind, usize> = HashMap::new();
+        for e in self.store.edges.values().filter(|e| e.subgraph == subgraph) {
+            *histogram.entry(e.kind.clone()).or_default() += 1;
+        }
+        histogram
+    }
+
+    //which relations the code honors: (convergent, specified) spec edges per kind, e.g.
+    //depends_on (9, 10) but calls (2, 5). structural kinds and spec edges outside the
+    //analyzed variant are left out, like in classify; a kind with no spec edge is absent.
+    //NotAnalyzed unless the states are current.
+    pub fn convergence_by_kind(&self) -> Result<HashMap<EdgeKind, (usize, usize)>, GraphError> {
+        self.require_analyzed()?;
+        let mut by_kind: HashMap<EdgeKind, (usize, usize)> = HashMap::new();
+        let judged = self
+            .store
+            .edges
+            .values()
+            .filter(|e| e.subgraph == SubgraphKind::Architecture && !self.is_structural(&e.kind) && self.in_active_variant(e));
+        for e in judged {
+            let entry = by_kind.entry(e.kind.clone()).or_default();
+            entry.0 += usize::from(e.state == EdgeState::Convergent);
+            entry.1 += 1;
+        }
+        Ok(by_kind)
+    }
+
+    //state counts over every edge of every subgraph. note that a divergence shows up
+    //on its impl edges and on the propagated edge; use count_states_in for one side.
+    //NotAnalyzed unless the states are current (see require_analyzed).
+    pub fn count_states(&self) -> Result<StateCounts, GraphError> {
+        self.require_analyzed()?;
+        Ok(self.tally_states(None))
+    }
+
+    pub fn count_states_in(&self, subgraph: SubgraphKind) -> Result<StateCounts, GraphError> {
+        self.require_analyzed()?;
+        Ok(self.tally_states(Some(subgraph)))
+    }
+
+    //state counts of one subgraph (None = all) as they are, analyzed or not
+    pub(crate) fn tally_states(&self, subgraph: Option<SubgraphKind>) -> StateCounts {
+        let mut counts = StateCounts::default();
+        for e in self.store.edges.values().filter(|e| subgraph.is_none_or(|s| e.subgraph == s)) {
+            counts.add(e.state);
+        }
+        counts
+    }
+
+    //"god module" smell: nodes whose implementation fan-out exceeds `threshold`,
+    //as (node, fan-out), highest fan-out first, ties by id
+    pub fn high_fan_out_nodes(&self, threshold: usize) -> Vec<(NodeId, usize)> {
+        self.high_fan_out_nodes_in(SubgraphKind::Implementation, threshold)
+    }
+
+    //high_fan_out_nodes over another subgraph, e.g. Architecture for specified dependencies
+    pub fn high_fan_out_nodes_in(&self, subgraph: SubgraphKind, threshold: usize) -> Vec<(NodeId, usize)> {
+        let mut out: Vec<(NodeId, usize)> = self
+            .store
+            .nodes
+            .keys()
+            .map(|&id| (id, self.fan_out(id, subgraph)))
+            .filter(|&(_, n)| n > threshold)
+            .collect();
+        out.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::graph::{Edge, GraphError, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn fan_out_counts_only_the_chosen_subgraph() {
+        let mut g = ReflexionGraph::new();
+        let hub = g.add_node(Node::new("Hub", SubgraphKind::Architecture, None)).unwrap();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let c = g.add_node(Node::new("C", SubgraphKind::Architecture, None)).unwrap();
+        for to in [a, b, c] {
+            g.add_edge(Edge::new(hub, to, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+        }
+        g.add_edge(Edge::new(hub, a, EdgeKind::calls(), SubgraphKind::Propagated)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+
+        assert_eq!(g.fan_out(hub, SubgraphKind::Architecture), 3);
+        assert_eq!(g.fan_out(hub, SubgraphKind::Propagated), 1);
+        assert_eq!(g.fan_out(hub, SubgraphKind::Implementation), 0);
+        assert_eq!(g.fan_out(999, SubgraphKind::Architecture), 0);
+
+        assert_eq!(g.high_fan_out_nodes_in(SubgraphKind::Architecture, 2), vec![(hub, 3)]);
+        assert_eq!(g.high_fan_out_nodes_in(SubgraphKind::Architecture, 0), vec![(hub, 3), (a, 1)]);
+        assert!(g.high_fan_out_nodes(0).is_empty());
+    }
+
+    #[test]
+    fn kind_histogram_is_scoped_to_the_subgraph() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        g.add_edge(Edge::new(a, a, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(i, i, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(i, i, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(i, i, EdgeKind::from("reads_field"), SubgraphKind::Implementation)).unwrap();
+
+        let arch = g.kind_histogram(SubgraphKind::Architecture);
+        assert_eq!(arch.len(), 1);
+        assert_eq!(arch[&EdgeKind::depends_on()], 1);
+
+        let code = g.kind_histogram(SubgraphKind::Implementation);
+        assert_eq!(code[&EdgeKind::calls()], 2);
+        assert_eq!(code[&EdgeKind::from("reads_field")], 1);
+        assert!(!code.contains_key(&EdgeKind::depends_on()));
+
+        assert!(g.kind_histogram(SubgraphKind::Propagated).is_empty());
+    }
+
+    #[test]
+    fn degree_combines_in_and_out_edges() {
+        let mut g = ReflexionGraph::new();
+        let core = g.add_node(Node::new("Core", SubgraphKind::Architecture, None)).unwrap();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let lone = g.add_node(Node::new("Lone", SubgraphKind::Architecture, None)).unwrap();
+        g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        g.add_edge(Edge::new(a, core, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(b, core, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(core, b, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+
+        assert_eq!(g.fan_in(core, SubgraphKind::Architecture), 2);
+        assert_eq!(g.degree(core, SubgraphKind::Architecture), 3);
+        assert_eq!(g.degree(lone, SubgraphKind::Architecture), 0);
+
+        assert_eq!(
+            g.degree_distribution(SubgraphKind::Architecture),
+            vec![(core, 3), (b, 2), (a, 1), (lone, 0)]
+        );
+    }
+
+    #[test]
+    fn count_states_names_every_state() {
+        use super::StateCounts;
+        use crate::core::classify::AnalysisOptions;
+
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        let k = g.add_node(Node::new("k", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i, a).unwrap();
+        g.set_mapping(j, b).unwrap();
+        g.add_edge(Edge::new(j, i, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(i, k, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.analyze(&AnalysisOptions::default());
+
+        let all = g.count_states().unwrap();
+        assert_eq!(all, StateCounts { absent: 1, divergent: 2, unmapped: 1, ..Default::default() });
+        assert_eq!(all.total(), 4);
+        assert_eq!(all.violations(), 3);
+
+        let spec = g.count_states_in(SubgraphKind::Architecture).unwrap();
+        assert_eq!((spec.total(), spec.violations()), (1, 1));
+        assert_eq!(ReflexionGraph::new().count_states(), Err(GraphError::NotAnalyzed));
+        g.add_node(Node::new("late", SubgraphKind::Implementation, None)).unwrap();
+        assert_eq!(g.count_states_in(SubgraphKind::Architecture), Err(GraphError::NotAnalyzed));
+    }
+
+    #[test]
+    fn convergence_by_kind_splits_spec_edges_per_relation() {
+        use crate::core::classify::AnalysisOptions;
+
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(b, a, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::contains(), SubgraphKind::Architecture)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i, a).unwrap();
+        g.set_mapping(j, b).unwrap();
+        g.add_edge(Edge::new(i, j, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        assert_eq!(g.convergence_by_kind(), Err(GraphError::NotAnalyzed));
+        g.analyze(&AnalysisOptions::default());
+
+        let by_kind = g.convergence_by_kind().unwrap();
+        assert_eq!(by_kind.len(), 2);
+        assert_eq!(by_kind[&EdgeKind::calls()], (1, 2));
+        assert_eq!(by_kind[&EdgeKind::depends_on()], (0, 1));
+    }
+}
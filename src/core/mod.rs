@@ -1,4 +1,30 @@
 pub mod types;
 pub mod state;
 pub mod graph;
+pub mod store;
+pub mod invariants;
+pub mod builder;
 pub mod mapping;
+pub mod lifting;
+pub mod canonical;
+pub mod propagate;
+pub mod classify;
+pub mod analyzer;
+pub mod incremental;
+pub mod results;
+pub mod metrics;
+pub mod delta;
+pub mod sanity;
+pub mod layering;
+pub mod groups;
+pub mod encapsulation;
+pub mod external;
+pub mod variants;
+pub mod spec;
+pub mod suggest;
+pub mod trend;
+pub mod matrix;
+pub mod walk;
+pub mod summary;
+#[cfg(feature = "regex")]
+pub mod pattern;
@@ -0,0 +1,401 @@
+// edge kind naming rules: a small std-only regex matcher (feature "regex")
+use std::fmt;
+use crate::core::graph::ReflexionGraph;
+use crate::core::store::GraphStore;
+
+//the subset of regex syntax kind conventions need: literals, `.`, classes (`[a-z_]`,
+//`[^0-9]`, `\d` `\w` `\s`), groups with alternation `(calls|uses)`, the quantifiers
+//`*` `+` `?` and the anchors `^` `$`. like a regex, it matches anywhere in the kind
+//unless anchored. compiled once by set_kind_pattern into an NFA (Thompson construction)
+//that is_match simulates one char at a time, in O(pattern length * kind length): no
+//backtracking, so patterns like `^(a|a)*b$` can't blow up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KindPattern {
+    source: String,
+    prog: Vec<Inst>,
+}
+
+//where (char index) and why a pattern didn't compile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternError {
+    pub at: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid kind pattern at {}: {}", self.at, self.message)
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+//parsed pattern, before compilation
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Atom {
+    Char(char),
+    Any,
+    Class { ranges: Vec<(char, char)>, negated: bool },
+    Start,
+    End,
+    Group(Vec<Vec<Atom>>),
+    Repeat { atom: Box<Atom>, min: usize, max: Option<usize> },
+}
+
+impl Atom {
+    fn accepts(&self, c: char) -> bool {
+        match self {
+            Atom::Char(want) => c == *want,
+            Atom::Any => true,
+            Atom::Class { ranges, negated } => ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(&c)) != *negated,
+            _ => false,
+        }
+    }
+}
+
+//one NFA instruction: Take consumes a char its (single-char) atom accepts, Split forks
+//into both targets, Jump continues elsewhere, Start/End only pass at the ends of the kind
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Inst {
+    Take(Atom),
+    Start,
+    End,
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+impl KindPattern {
+    pub fn new(pattern: &str) -> Result<Self, PatternError> {
+        let mut p = Parser { chars: pattern.chars().collect(), pos: 0 };
+        let root = p.alternatives()?;
+        if p.pos < p.chars.len() {
+            return Err(p.error("unmatched ')'"));
+        }
+        let mut prog = Vec::new();
+        compile_alternatives(&root, &mut prog);
+        prog.push(Inst::Match);
+        Ok(Self { source: pattern.to_string(), prog })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    //runs all NFA threads in lockstep over the chars; a thread is only kept once per
+    //instruction and position, which bounds the work and ends empty loops like `(a*)*`
+    pub fn is_match(&self, text: &str) -> bool {
+        let chars: Vec<char> = text.chars().collect();
+        let mut seen = vec![usize::MAX; self.prog.len()];
+        let (mut current, mut next) = (Vec::new(), Vec::new());
+        for at in 0..=chars.len() {
+            //unanchored: a fresh thread starts at every position
+            if self.follow(0, at, chars.len(), &mut current, &mut seen) {
+                return true;
+            }
+            let Some(&c) = chars.get(at) else { break };
+            next.clear();
+            for &pc in &current {
+                if let Inst::Take(atom) = &self.prog[pc]
+                    && atom.accepts(c)
+                    && self.follow(pc + 1, at + 1, chars.len(), &mut next, &mut seen)
+                {
+                    return true;
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+        }
+        false
+    }
+
+    //adds the Take instructions reachable from `pc` without consuming a char at position
+    //`at` to `threads`; true if Match is reachable
+    fn follow(&self, pc: usize, at: usize, len: usize, threads: &mut Vec<usize>, seen: &mut [usize]) -> bool {
+        let mut stack = vec![pc];
+        while let Some(pc) = stack.pop() {
+            if seen[pc] == at {
+                continue;
+            }
+            seen[pc] = at;
+            match &self.prog[pc] {
+                Inst::Take(_) => threads.push(pc),
+                Inst::Start if at == 0 => stack.push(pc + 1),
+                Inst::End if at == len => stack.push(pc + 1),
+                Inst::Start | Inst::End => {}
+                Inst::Split(a, b) => stack.extend([*b, *a]),
+                Inst::Jump(to) => stack.push(*to),
+                Inst::Match => return true,
+            }
+        }
+        false
+    }
+}
+
+//a|b|c as: split(a, split(b, c)), each alternative jumping past the rest when done
+fn compile_alternatives(alts: &[Vec<Atom>], prog: &mut Vec<Inst>) {
+    let mut jumps = Vec::new();
+    for (n, seq) in alts.iter().enumerate() {
+        let split = (n + 1 < alts.len()).then(|| {
+            prog.push(Inst::Split(0, 0));
+            prog.len() - 1
+        });
+        for atom in seq {
+            compile_atom(atom, prog);
+        }
+        if let Some(split) = split {
+            jumps.push(prog.len());
+            prog.push(Inst::Jump(0));
+            prog[split] = Inst::Split(split + 1, prog.len());
+        }
+    }
+    let end = prog.len();
+    for j in jumps {
+        prog[j] = Inst::Jump(end);
+    }
+}
+
+//x{min,max} as `min` copies of x followed by a loop (no max) or optional copies
+fn compile_atom(atom: &Atom, prog: &mut Vec<Inst>) {
+    match atom {
+        Atom::Start => prog.push(Inst::Start),
+        Atom::End => prog.push(Inst::End),
+        Atom::Group(alts) => compile_alternatives(alts, prog),
+        Atom::Repeat { atom, min, max } => {
+            for _ in 0..*min {
+                compile_atom(atom, prog);
+            }
+            match max {
+                None => {
+                    let split = prog.len();
+                    prog.push(Inst::Split(0, 0));
+                    compile_atom(atom, prog);
+                    prog.push(Inst::Jump(split));
+                    prog[split] = Inst::Split(split + 1, prog.len());
+                }
+                Some(max) => {
+                    let mut splits = Vec::new();
+                    for _ in *min..*max {
+                        splits.push(prog.len());
+                        prog.push(Inst::Split(0, 0));
+                        compile_atom(atom, prog);
+                    }
+                    let end = prog.len();
+                    for split in splits {
+                        prog[split] = Inst::Split(split + 1, end);
+                    }
+                }
+            }
+        }
+        single => prog.push(Inst::Take(single.clone())),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &'static str) -> PatternError {
+        PatternError { at: self.pos, message }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    //seq ('|' seq)*, up to a ')' or the end
+    fn alternatives(&mut self) -> Result<Vec<Vec<Atom>>, PatternError> {
+        let mut alts = vec![self.sequence()?];
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            alts.push(self.sequence()?);
+        }
+        Ok(alts)
+    }
+
+    fn sequence(&mut self) -> Result<Vec<Atom>, PatternError> {
+        let mut seq = Vec::new();
+        while let Some(c) = self.peek() {
+            let atom = match c {
+                '|' | ')' => break,
+                '*' | '+' | '?' => return Err(self.error("nothing to repeat")),
+                _ => self.atom()?,
+            };
+            let (min, max) = match self.peek() {
+                Some('*') => (0, None),
+                Some('+') => (1, None),
+                Some('?') => (0, Some(1)),
+                _ => {
+                    seq.push(atom);
+                    continue;
+                }
+            };
+            if matches!(atom, Atom::Start | Atom::End) {
+                return Err(self.error("nothing to repeat"));
+            }
+            self.pos += 1;
+            seq.push(Atom::Repeat { atom: Box::new(atom), min, max });
+        }
+        Ok(seq)
+    }
+
+    fn atom(&mut self) -> Result<Atom, PatternError> {
+        Ok(match self.next().expect("peeked by the caller") {
+            '.' => Atom::Any,
+            '^' => Atom::Start,
+            '$' => Atom::End,
+            '(' => {
+                let alts = self.alternatives()?;
+                if self.next() != Some(')') {
+                    return Err(self.error("unclosed group"));
+                }
+                Atom::Group(alts)
+            }
+            '[' => self.class()?,
+            '\\' => self.escape()?,
+            c => Atom::Char(c),
+        })
+    }
+
+    fn escape(&mut self) -> Result<Atom, PatternError> {
+        let c = self.next().ok_or_else(|| self.error("trailing backslash"))?;
+        let ranges = match c {
+            'd' => vec![('0', '9')],
+            'w' => vec![('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')],
+            's' => vec![(' ', ' '), ('\t', '\t'), ('\n', '\n'), ('\r', '\r')],
+            c => return Ok(Atom::Char(c)),
+        };
+        Ok(Atom::Class { ranges, negated: false })
+    }
+
+    //after '[': optional '^', items (char, escape, or range a-z), then ']'
+    fn class(&mut self) -> Result<Atom, PatternError> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.pos += 1;
+        }
+        let mut ranges = Vec::new();
+        loop {
+            let lo = match self.next() {
+                None => return Err(self.error("unclosed class")),
+                Some(']') if !ranges.is_empty() => break,
+                Some(']') => return Err(self.error("empty class")),
+                Some('\\') => match self.escape()? {
+                    Atom::Char(c) => c,
+                    Atom::Class { ranges: set, .. } => {
+                        ranges.extend(set);
+                        continue;
+                    }
+                    _ => unreachable!("escape yields a char or a class"),
+                },
+                Some(c) => c,
+            };
+            let is_range = self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&c| c != ']');
+            if !is_range {
+                ranges.push((lo, lo));
+                continue;
+            }
+            self.pos += 1;
+            let hi = self.next().expect("checked above");
+            if hi < lo {
+                return Err(self.error("range out of order"));
+            }
+            ranges.push((lo, hi));
+        }
+        Ok(Atom::Class { ranges, negated })
+    }
+}
+
+impl<S: GraphStore> ReflexionGraph<S> {
+    //strict kinds: from now on add_edge rejects edges whose kind doesn't match `pattern`
+    //with InvalidEdgeKind (e.g. "^[a-z][a-z_]*$" against extractor garbage). edges already
+    //in the graph are not re-checked. replaces an earlier pattern.
+    pub fn set_kind_pattern(&mut self, pattern: &str) -> Result<(), PatternError> {
+        self.kind_pattern = Some(KindPattern::new(pattern)?);
+        Ok(())
+    }
+
+    //back to accepting any non-blank kind
+    pub fn clear_kind_pattern(&mut self) {
+        self.kind_pattern = None;
+    }
+
+    pub fn kind_pattern(&self) -> Option<&str> {
+        self.kind_pattern.as_ref().map(KindPattern::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KindPattern;
+    use crate::core::graph::{Edge, GraphError, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    fn is_match(pattern: &str, text: &str) -> bool {
+        KindPattern::new(pattern).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn kind_pattern_supports_the_common_regex_subset() {
+        assert!(is_match("^[a-z][a-z_]*$", "depends_on"));
+        assert!(!is_match("^[a-z][a-z_]*$", "Depends-On"));
+        assert!(is_match("^(calls|uses)$", "uses"));
+        assert!(!is_match("^(calls|uses)$", "callsx"));
+        assert!(is_match("call", "recalled")); //unanchored, like a regex search
+        assert!(is_match("^\\w+\\.v\\d?$", "api.v"));
+        assert!(is_match("^[^ ]+$", "x"));
+        assert!(!is_match("^[^ ]+$", "a b"));
+        assert!(is_match("^(a*)*b$", "aaab")); //empty repetitions terminate
+        assert!(is_match("^a.+z$", "a-to-z"));
+
+        for bad in ["*a", "(ab", "ab)", "[]", "[z-a]", "a\\", "^*"] {
+            assert!(KindPattern::new(bad).is_err(), "{}", bad);
+        }
+        assert_eq!(KindPattern::new("(ab").unwrap_err().to_string(), "invalid kind pattern at 4: unclosed group");
+    }
+
+    #[test]
+    fn strict_graph_rejects_kinds_outside_the_pattern() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        g.set_kind_pattern("^[a-z_]+$").unwrap();
+        assert_eq!(g.kind_pattern(), Some("^[a-z_]+$"));
+
+        assert!(g.add_edge(Edge::new(a, b, EdgeKind::depends_on(), SubgraphKind::Architecture)).is_ok());
+        let err = g.add_edge(Edge::new(a, b, EdgeKind::from("<unknown>"), SubgraphKind::Architecture)).unwrap_err();
+        assert_eq!(err, GraphError::InvalidEdgeKind);
+
+        g.clear_kind_pattern();
+        assert!(g.add_edge(Edge::new(a, b, EdgeKind::from("<unknown>"), SubgraphKind::Architecture)).is_ok());
+    }
+
+    #[test]
+    fn pathological_patterns_match_in_linear_time() {
+        //exponential for a backtracker: every a can be taken by either alternative
+        let many_a = "a".repeat(64);
+        assert!(!is_match("^(a|a)*b$", &many_a));
+        assert!(is_match("^(a|a)*b$", &format!("{}b", many_a)));
+        assert!(!is_match("^(a+)+$", &format!("{}!", many_a)));
+        assert!(!is_match("(x*)*(x*)*y", &"x".repeat(200)));
+    }
+
+    #[test]
+    fn rename_edge_kind_respects_the_pattern() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let e = g.add_edge(Edge::new(a, a, EdgeKind::from("call"), SubgraphKind::Architecture)).unwrap();
+        g.set_kind_pattern("^[a-z_]+$").unwrap();
+
+        assert_eq!(g.rename_edge_kind(&EdgeKind::from("call"), EdgeKind::from("Calls!")), Err(GraphError::InvalidEdgeKind));
+        assert_eq!(g.edge(e).unwrap().kind().as_str(), "call");
+        assert_eq!(g.rename_edge_kind(&EdgeKind::from("call"), EdgeKind::calls()), Ok(1));
+    }
+}
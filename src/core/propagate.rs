@@ -1 +1,448 @@
 // propagation logic
+use std::collections::{HashMap, HashSet};
+use crate::core::types::{NodeId, EdgeId, EdgeKind, SubgraphKind};
+use crate::core::graph::{Edge, GraphError, ReflexionGraph};
+use crate::core::state::{EdgeState, Phase};
+use crate::core::classify::AnalysisOptions;
+use crate::core::analyzer::Scratch;
+use crate::core::store::GraphStore;
+
+//search state of realizing_paths: the dependency's support and kind, the impl nodes a
+//chain may pass through, the chain being built (and its nodes) and the chains found.
+//`longer` records that some chain got as far as its last edge, so a longer one may exist
+struct ChainWalk<'g> {
+    support: &'g HashSet<EdgeId>,
+    kind: &'g EdgeKind,
+    inside: HashSet<NodeId>,
+    max: usize,
+    visited: Vec<NodeId>,
+    chain: Vec<EdgeId>,
+    found: Vec<Vec<EdgeId>>,
+    longer: bool,
+}
+
+impl<S: GraphStore> ReflexionGraph<S> {
+    //returns the propagated edge (from, to, kind of `impl_edge`), synthesizing it on first
+    //use. the kind is only cloned for a new edge, not for every impl edge counted on it
+    pub(crate) fn propagated_edge(&mut self, from: NodeId, to: NodeId, impl_edge: EdgeId) -> EdgeId {
+        let kind = &self.edge_at(impl_edge).kind;
+        if let Some(eid) = self.find_arch_edge(from, to, kind, SubgraphKind::Propagated) {
+            return eid;
+        }
+        let kind = kind.clone();
+        self.add_edge(Edge::new(from, to, kind, SubgraphKind::Propagated))
+            .expect("endpoints are mapped arch nodes, so they exist")
+    }
+
+    //push every impl edge through maps_to into architecture space:
+    // - both endpoints mapped -> counted on the propagated edge between the two arch nodes,
+    //   and recorded in propagation_table (propagated edge -> impl edges)
+    // - an endpoint unmapped -> impl edge becomes Unmapped, or Allowed when every
+    //   unmapped endpoint is external (a cross-repo reference, see external_dependencies)
+    // - an endpoint or mapping target that doesn't exist (a damaged binary cache) is an
+    //   integrity error, not a modeling gap: recorded in integrity_errors, edge left Undefined
+    // - edges of a structural kind, or filtered out by the options (all of them at
+    //   max_propagation_levels 0) or by `include`, are left Undefined
+    pub(crate) fn propagate(&mut self, options: &AnalysisOptions, include: &dyn Fn(&Edge) -> bool, scratch: &mut Scratch) {
+        self.last_options = *options;
+        self.edges_in_subgraph_into(SubgraphKind::Implementation, &mut scratch.ids);
+        for &eid in &scratch.ids {
+            let e = self.edge_at(eid);
+            if !options.considers(e) || !include(e) || self.is_structural(&e.kind) {
+                continue;
+            }
+
+            let lifted = (
+                self.lift_target(e.from, options),
+                self.lift_target(e.to, options),
+            );
+            let dangling = [Some(e.from), Some(e.to), lifted.0, lifted.1]
+                .into_iter()
+                .flatten()
+                .find(|n| self.store.get_node(*n).is_none());
+            if let Some(missing) = dangling {
+                self.integrity_errors.push(GraphError::NodeNotFound(missing));
+                continue;
+            }
+
+            let (Some(arch_from), Some(arch_to)) = lifted else {
+                let state = if self.is_external_edge(e.from, e.to) { EdgeState::Allowed } else { EdgeState::Unmapped };
+                self.store.get_edge_mut(eid).expect("listed above").state = state;
+                continue;
+            };
+
+            let prop = self.propagated_edge(arch_from, arch_to, eid);
+            self.store.get_edge_mut(prop).expect("just found or added").counter += 1;
+            let support = self.propagation_table.entry(prop).or_insert_with(|| scratch.support_sets.pop().unwrap_or_default());
+            support.insert(eid);
+        }
+        //after the loop: synthesizing propagated edges goes through add_edge, which invalidates
+        self.set_phase(Phase::Propagated);
+    }
+
+    //impl edges behind a spec or propagated edge (drill-down for one dependency)
+    pub fn supporting_impl_edges(&self, edge: EdgeId) -> Option<&HashSet<EdgeId>> {
+        self.propagation_table.get(&edge)
+    }
+
+    //drill-down for a Convergent verdict: up to `max` impl edge chains realizing the spec
+    //or propagated edge `arch_edge`. a chain ends in one of its supporting impl edges (the
+    //call that crosses into the target) and may start further back inside the source: the
+    //edges before it run between impl nodes that lift to the same arch nodes as the
+    //supporting edges' sources, have the dependency's kind and took part in the last run
+    //(not Undefined). no node repeats within a chain. shortest chains first, so the
+    //supporting edges themselves come first, then ties in depth-first order from the
+    //lowest node id. empty for unknown edges and edges without support. the search is
+    //exhaustive per length, so keep `max` small on dense modules.
+    pub fn realizing_paths(&self, arch_edge: EdgeId, max: usize) -> Vec<Vec<EdgeId>> {
+        let (Some(support), Some(spec)) = (self.supporting_impl_edges(arch_edge), self.store.get_edge(arch_edge)) else {
+            return Vec::new();
+        };
+        let sources: HashSet<NodeId> = support
+            .iter()
+            .filter_map(|s| self.store.get_edge(*s))
+            .filter_map(|s| self.lift_target(s.from, &self.last_options))
+            .collect();
+        let inside: Vec<NodeId> = self
+            .filter_nodes(|n| n.subgraph == SubgraphKind::Implementation)
+            .into_iter()
+            .filter(|&n| self.lift_target(n, &self.last_options).is_some_and(|a| sources.contains(&a)))
+            .collect();
+        let mut walk = ChainWalk {
+            support,
+            kind: &spec.kind,
+            inside: inside.iter().copied().collect(),
+            max,
+            visited: Vec::new(),
+            chain: Vec::new(),
+            found: Vec::new(),
+            longer: false,
+        };
+        for length in 1..=inside.len() {
+            walk.longer = false;
+            for &start in &inside {
+                walk.visited = vec![start];
+                self.extend_chains(&mut walk, start, length);
+            }
+            if walk.found.len() >= max || !walk.longer {
+                break;
+            }
+        }
+        walk.found
+    }
+
+    //depth-first step of realizing_paths: chains of exactly `left` more edges from `node`
+    fn extend_chains(&self, walk: &mut ChainWalk<'_>, node: NodeId, left: usize) {
+        walk.longer |= left == 1;
+        for &eid in self.store.out_edges(node, SubgraphKind::Implementation) {
+            if walk.found.len() >= walk.max {
+                return;
+            }
+            let e = self.edge_at(eid);
+            if &e.kind != walk.kind || e.state == EdgeState::Undefined {
+                continue;
+            }
+            if left == 1 {
+                if walk.support.contains(&eid) {
+                    walk.chain.push(eid);
+                    walk.found.push(walk.chain.clone());
+                    walk.chain.pop();
+                }
+            } else if walk.inside.contains(&e.to) && !walk.visited.contains(&e.to) {
+                walk.chain.push(eid);
+                walk.visited.push(e.to);
+                self.extend_chains(walk, e.to, left - 1);
+                walk.visited.pop();
+                walk.chain.pop();
+            }
+        }
+    }
+
+    //the whole propagation table in one borrow-only pass: (spec/propagated edge, impl
+    //edges behind it), unordered. cheaper than supporting_impl_edges per edge when a UI
+    //wants every module dependency with its evidence at once.
+    pub fn propagation_entries(&self) -> impl Iterator<Item = (EdgeId, &HashSet<EdgeId>)> + '_ {
+        self.propagation_table.iter().map(|(&eid, support)| (eid, support))
+    }
+
+    //dangling references the last analyze ran into (NodeNotFound), in edge id order
+    pub fn integrity_errors(&self) -> &[GraphError] {
+        &self.integrity_errors
+    }
+
+    //Unmapped impl edges with the endpoint that lacks a mapping: one entry per unmapped
+    //endpoint (two for an edge where neither side is mapped), sorted. tells the user
+    //exactly which nodes the mapping file is missing. mappings are resolved as in the last
+    //analyze: with ignore_proposed, endpoints that only have a proposed mapping count as
+    //lacking one; with inherit_mappings, endpoints whose package is mapped don't.
+    pub fn unmapped_edges(&self) -> Vec<(EdgeId, NodeId)> {
+        let mut out: Vec<(EdgeId, NodeId)> = self
+            .store
+            .iter_edges()
+            .filter(|e| e.subgraph == SubgraphKind::Implementation && e.state == EdgeState::Unmapped)
+            .flat_map(|e| {
+                let mut missing = vec![e.from];
+                if e.to != e.from {
+                    missing.push(e.to);
+                }
+                missing.retain(|&n| self.lift_target(n, &self.last_options).is_none());
+                missing.into_iter().map(move |n| (e.id, n))
+            })
+            .collect();
+        out.sort_unstable();
+        out
+    }
+
+    //the Unmapped impl edges where only one endpoint lacks a mapping, with that endpoint:
+    //each of these is one mapping away from being classified, so they are the ones to
+    //map first. resolved like unmapped_edges; sorted by edge id.
+    pub fn half_mapped_edges(&self) -> Vec<(EdgeId, NodeId)> {
+        let mut per_edge: HashMap<EdgeId, Vec<NodeId>> = HashMap::new();
+        for (eid, node) in self.unmapped_edges() {
+            per_edge.entry(eid).or_default().push(node);
+        }
+        let mut out: Vec<(EdgeId, NodeId)> = per_edge
+            .into_iter()
+            .filter(|(eid, missing)| {
+                let e = self.edge_at(*eid);
+                missing.len() == 1 && e.from != e.to
+            })
+            .map(|(eid, missing)| (eid, missing[0]))
+            .collect();
+        out.sort_unstable();
+        out
+    }
+
+    //mapping work plan: impl nodes whose mapping would clear every Unmapped edge, in the
+    //order to map them. greedy: next comes the node completing the most still-unmapped edges
+    //(both ends mapped after it), then the one touching the most, then the lowest id.
+    //resolved like unmapped_edges, so nodes that can't be mapped to help are never listed.
+    pub fn mapping_frontier(&self) -> Vec<NodeId> {
+        let mut pending: HashMap<EdgeId, HashSet<NodeId>> = HashMap::new();
+        for (eid, node) in self.unmapped_edges() {
+            pending.entry(eid).or_default().insert(node);
+        }
+
+        let mut order = Vec::new();
+        while !pending.is_empty() {
+            let mut impact: HashMap<NodeId, (usize, usize)> = HashMap::new();
+            for missing in pending.values() {
+                for &n in missing {
+                    let entry = impact.entry(n).or_default();
+                    entry.0 += usize::from(missing.len() == 1);
+                    entry.1 += 1;
+                }
+            }
+            let (&next, _) = impact
+                .iter()
+                .max_by(|(a, x), (b, y)| x.cmp(y).then(b.cmp(a)))
+                .expect("pending edges have missing endpoints");
+            order.push(next);
+            pending.retain(|_, missing| {
+                missing.remove(&next);
+                !missing.is_empty()
+            });
+        }
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::types::{EdgeId, EdgeKind, SubgraphKind};
+    use crate::core::state::EdgeState;
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::analyzer::Scratch;
+
+    #[test]
+    fn propagate_merges_impl_edges_between_the_same_arch_nodes() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let i1 = g.add_node(Node::new("i1", SubgraphKind::Implementation, None)).unwrap();
+        let i2 = g.add_node(Node::new("i2", SubgraphKind::Implementation, None)).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i1, a).unwrap();
+        g.set_mapping(i2, a).unwrap();
+        g.set_mapping(j, b).unwrap();
+
+        let e1 = g.add_edge(Edge::new(i1, j, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        let e2 = g.add_edge(Edge::new(i2, j, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        g.init_states();
+        g.propagate(&AnalysisOptions::default(), &|_| true, &mut Scratch::default());
+
+        let props = g.edges_in_subgraph(SubgraphKind::Propagated);
+        assert_eq!(props.len(), 1);
+        let p = g.edge(props[0]).unwrap();
+        assert_eq!((p.from(), p.to()), (a, b));
+        assert_eq!(p.counter(), 2);
+        assert_eq!(g.propagation_table[&props[0]], [e1, e2].into_iter().collect());
+    }
+
+    #[test]
+    fn propagate_marks_edges_with_unmapped_endpoint() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i, a).unwrap();
+
+        let e = g.add_edge(Edge::new(i, j, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        g.init_states();
+        g.propagate(&AnalysisOptions::default(), &|_| true, &mut Scratch::default());
+
+        assert_eq!(g.edge(e).unwrap().state(), EdgeState::Unmapped);
+        assert!(g.edges_in_subgraph(SubgraphKind::Propagated).is_empty());
+    }
+
+    #[test]
+    fn unmapped_edges_names_the_missing_endpoint() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        let k = g.add_node(Node::new("k", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i, a).unwrap();
+
+        let half = g.add_edge(Edge::new(i, j, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        let none = g.add_edge(Edge::new(j, k, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+
+        assert_eq!(g.unmapped_edges(), vec![(half, j), (none, j), (none, k)]);
+        assert_eq!(g.half_mapped_edges(), vec![(half, j)]);
+        assert!(g.integrity_errors().is_empty());
+    }
+
+    #[test]
+    fn dangling_mapping_is_an_integrity_error_not_unmapped() {
+        use crate::core::graph::GraphError;
+
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i, a).unwrap();
+        g.put_mapping(j, 999); //bypasses set_mapping validation
+
+        let e = g.add_edge(Edge::new(i, j, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+
+        assert_eq!(g.integrity_errors(), &[GraphError::NodeNotFound(999)]);
+        assert_eq!(g.edge(e).unwrap().state(), EdgeState::Undefined);
+        assert!(g.unmapped_edges().is_empty());
+    }
+
+    #[test]
+    fn propagation_entries_cover_spec_and_propagated_edges() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let spec = g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i, a).unwrap();
+        g.set_mapping(j, b).unwrap();
+        let e = g.add_edge(Edge::new(i, j, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+
+        let mut entries: Vec<(EdgeId, Vec<EdgeId>)> = g
+            .propagation_entries()
+            .map(|(eid, support)| (eid, support.iter().copied().collect()))
+            .collect();
+        entries.sort();
+        let prop = g.edges_in_subgraph(SubgraphKind::Propagated)[0];
+        assert_eq!(entries, vec![(spec, vec![e]), (prop, vec![e])]);
+
+        assert!(g.supporting_impl_edges(spec).unwrap().contains(&e));
+        assert_eq!(g.realizing_paths(spec, 5), vec![vec![e]]);
+        assert!(g.realizing_paths(spec, 0).is_empty());
+        assert!(g.realizing_paths(e, 5).is_empty());
+        assert!(g.supporting_impl_edges(e).is_none());
+    }
+
+    #[test]
+    fn mapping_frontier_orders_nodes_by_edges_they_complete() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let [m, hub, x, y, lone] = ["m", "hub", "x", "y", "lone"].map(|name| g.add_node(Node::new(name, SubgraphKind::Implementation, None)).unwrap());
+        g.set_mapping(m, a).unwrap();
+        for (from, to) in [(m, hub), (hub, m), (m, hub), (x, y), (y, m), (lone, lone)] {
+            g.add_edge(Edge::new(from, to, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        }
+        g.analyze(&AnalysisOptions::default());
+
+        //hub completes 3 edges, y completes y -> m (and half of x -> y), then x and lone
+        assert_eq!(g.mapping_frontier(), vec![hub, y, x, lone]);
+
+        g.set_mapping(hub, a).unwrap();
+        g.set_mapping(y, a).unwrap();
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.mapping_frontier(), vec![x, lone]);
+    }
+
+    #[test]
+    fn max_propagation_levels_bounds_how_far_up_edges_propagate() {
+        let mut g = ReflexionGraph::new();
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let ui = g.add_node(Node::new("App.Ui", SubgraphKind::Architecture, Some(app))).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        let spec = g.add_edge(Edge::new(app, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let view = g.add_node(Node::new("view", SubgraphKind::Implementation, None)).unwrap();
+        let store = g.add_node(Node::new("store", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(view, ui).unwrap();
+        g.set_mapping(store, db).unwrap();
+        let call = g.add_edge(Edge::new(view, store, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        let levels = |n| AnalysisOptions { max_propagation_levels: n, ..Default::default() };
+
+        //0: nothing propagates
+        g.analyze(&levels(0));
+        assert!(g.edges_in_subgraph(SubgraphKind::Propagated).is_empty());
+        assert_eq!(g.edge(call).unwrap().state(), EdgeState::Undefined);
+        assert_eq!(g.edge(spec).unwrap().state(), EdgeState::Absent);
+        assert_eq!(g.propagation_entries().count(), 0);
+
+        //1: App.Ui -> Db is synthesized, but App -> Db is a level too high to match it
+        g.analyze(&levels(1));
+        let prop = g.edges_in_subgraph(SubgraphKind::Propagated)[0];
+        assert_eq!(g.edge(prop).unwrap().state(), EdgeState::Divergent);
+        assert_eq!(g.edge(spec).unwrap().state(), EdgeState::Absent);
+        assert!(g.supporting_impl_edges(prop).unwrap().contains(&call));
+        assert_eq!(g.supporting_impl_edges(spec), None);
+
+        //2 and the default: the spec edge takes the support
+        for options in [levels(2), AnalysisOptions::default()] {
+            g.analyze(&options);
+            assert_eq!(g.edge(spec).unwrap().state(), EdgeState::Convergent);
+            assert!(g.supporting_impl_edges(spec).unwrap().contains(&call));
+        }
+    }
+
+    #[test]
+    fn realizing_paths_follow_calls_inside_the_source_module() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let spec = g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let [entry, helper, j] = ["entry", "helper", "j"].map(|name| g.add_node(Node::new(name, SubgraphKind::Implementation, None)).unwrap());
+        g.set_mapping(entry, a).unwrap();
+        g.set_mapping(helper, a).unwrap();
+        g.set_mapping(j, b).unwrap();
+        let inner = g.add_edge(Edge::new(entry, helper, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        let cross = g.add_edge(Edge::new(helper, j, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        //a cycle back to entry and an edge of another kind don't make chains of their own
+        g.add_edge(Edge::new(helper, entry, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(entry, helper, EdgeKind::depends_on(), SubgraphKind::Implementation)).unwrap();
+        g.analyze(&AnalysisOptions::default());
+
+        assert_eq!(g.realizing_paths(spec, 5), vec![vec![cross], vec![inner, cross]]);
+        assert_eq!(g.realizing_paths(spec, 1), vec![vec![cross]]);
+        let prop = g.find_arch_edge(a, b, &EdgeKind::calls(), SubgraphKind::Propagated).unwrap();
+        assert_eq!(g.realizing_paths(prop, 5), vec![vec![cross], vec![inner, cross]]);
+    }
+}
@@ -0,0 +1,844 @@
+// analysis result queries
+use std::collections::{BTreeSet, HashMap, HashSet};
+use crate::core::types::{Counter, EdgeId, EdgeKind, NodeId, NodeKind, SubgraphKind};
+use crate::core::graph::{GraphError, ReflexionGraph};
+use crate::core::state::{EdgeState, NodeState, Severity};
+use crate::core::store::GraphStore;
+
+//bucket of violations_by_file for impl nodes without a location
+pub const UNKNOWN_FILE: &str = "unknown";
+
+//per-module roll-up of the last analyze, for architecture reviews
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Coverage {
+    pub specified: usize, //non-structural spec edges touching the module (either end)
+    pub convergent: usize, //of which realized by the code
+    pub absent: usize, //of which not realized
+    pub mapped_impl_nodes: usize, //impl nodes mapped onto the module
+}
+
+impl Coverage {
+    //share of the specified edges that converged (1.0 when nothing is specified)
+    pub fn ratio(&self) -> f64 {
+        if self.specified == 0 {
+            return 1.0;
+        }
+        self.convergent as f64 / self.specified as f64
+    }
+}
+
+//why a spec edge is Absent, from the node states of its endpoints (see contextual_absences)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AbsenceContext {
+    NotWired, //both modules are implemented, the dependency between them is missing
+    SourceUnimplemented, //the source module is SpecifiedOnly
+    TargetUnimplemented, //the target module is SpecifiedOnly
+    BothUnimplemented, //neither module is implemented yet
+}
+
+//one endpoint of a Violation, resolved
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViolationEnd {
+    pub id: NodeId,
+    pub name: String,
+    pub kind: Option<NodeKind>,
+}
+
+//a violation with everything exporters need, see violations_detailed. violations live in
+//architecture space, so `from` and `to` are the arch modules the impl evidence lifted to;
+//only for an encapsulation breach (an impl edge) they are the impl nodes themselves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub edge: EdgeId, //the spec edge (Absent, MultiplicityViolation), propagated edge (Divergent) or breaching impl edge
+    pub state: EdgeState,
+    pub kind: EdgeKind,
+    pub severity: Severity, //severity_of(kind)
+    pub from: ViolationEnd,
+    pub to: ViolationEnd,
+    pub counter: Counter, //impl edges counted on it (0 for an absence)
+    pub support: Vec<EdgeId>, //those impl edges, sorted
+}
+
+impl<S: GraphStore> ReflexionGraph<S> {
+    //node state after the last analyze: impl nodes are Mapped or Unmapped (resolved as in
+    //that run), arch nodes Mapped when an impl node maps onto them or a descendant, else
+    //SpecifiedOnly. Undefined before analyze, after a mutation, and for unknown ids.
+    pub fn node_state(&self, node: NodeId) -> NodeState {
+        let Some(n) = self.store.get_node(node).filter(|_| self.is_analyzed()) else {
+            return NodeState::Undefined;
+        };
+        let mapped = match n.subgraph {
+            SubgraphKind::Implementation => self.lift_target(node, &self.last_options).is_some(),
+            _ => self.realized_arch_nodes().contains(&node),
+        };
+        match (mapped, n.subgraph) {
+            (true, _) => NodeState::Mapped,
+            (false, SubgraphKind::Implementation) => NodeState::Unmapped,
+            (false, _) => NodeState::SpecifiedOnly,
+        }
+    }
+
+    //absent_edges with the reason: "designed but not wired up" (NotWired) is work on a
+    //dependency, the other contexts mean a whole module still has to be built. sorted by id.
+    pub fn contextual_absences(&self) -> Result<Vec<(EdgeId, AbsenceContext)>, GraphError> {
+        let realized = self.realized_arch_nodes();
+        Ok(self
+            .absent_edges()?
+            .into_iter()
+            .map(|eid| {
+                let e = self.edge_at(eid);
+                let context = match (realized.contains(&e.from), realized.contains(&e.to)) {
+                    (true, true) => AbsenceContext::NotWired,
+                    (false, true) => AbsenceContext::SourceUnimplemented,
+                    (true, false) => AbsenceContext::TargetUnimplemented,
+                    (false, false) => AbsenceContext::BothUnimplemented,
+                };
+                (eid, context)
+            })
+            .collect())
+    }
+
+    //result queries that need a current analyze fail with NotAnalyzed before the first one
+    //and after any change. on a stale graph they would read defaults (everything Undefined
+    //or Specified) and report "no violations". this is the full list:
+    //  - here: violations, violations_detailed, violations_by_file, test_divergences,
+    //    has_errors, absent_edges, contextual_absences, absent_report, unrealized_targets,
+    //    weak_convergences, and edges_with_state for states only analyze assigns
+    //  - metrics: count_states, count_states_in, convergence_by_kind
+    //  - groups: violations_by_group; trend: trend_point
+    //  - delta: violation_keys, diff_violations, write_baseline, check_against_baseline
+    //topology, mapping and spec queries stay available at any time, and the exporters
+    //(summary, dot, junit, ...) render the states as they are and show staleness themselves.
+    //crate-internal passes use the unguarded edges_with_state_in / violation_ids.
+    pub(crate) fn require_analyzed(&self) -> Result<(), GraphError> {
+        if self.is_analyzed() { Ok(()) } else { Err(GraphError::NotAnalyzed) }
+    }
+
+    //sorted ids of the edges in `state`, over all subgraphs. Undefined and Specified are
+    //the states edges start in and can be asked for at any time, the others need analyze.
+    pub fn edges_with_state(&self, state: EdgeState) -> Result<Vec<EdgeId>, GraphError> {
+        if !matches!(state, EdgeState::Undefined | EdgeState::Specified) {
+            self.require_analyzed()?;
+        }
+        Ok(self.filter_edges(|e| e.state == state))
+    }
+
+    //sorted ids of the edges of one subgraph currently in `state`
+    pub(crate) fn edges_with_state_in(&self, subgraph: SubgraphKind, state: EdgeState) -> Vec<EdgeId> {
+        let mut ids: Vec<EdgeId> = self
+            .store
+            .iter_edges()
+            .filter(|e| e.subgraph == subgraph && e.state == state)
+            .map(|e| e.id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    //one state per input id, in input order (one allocation, sized up front).
+    //fails on the first id that isn't an edge of this graph.
+    pub fn states_for(&self, edges: &[EdgeId]) -> Result<Vec<EdgeState>, GraphError> {
+        let mut states = Vec::with_capacity(edges.len());
+        for &eid in edges {
+            let e = self.store.get_edge(eid).ok_or(GraphError::EdgeNotFound(eid))?;
+            states.push(e.state);
+        }
+        Ok(states)
+    }
+
+    //architecture-space violations of the last analyze, sorted by id: Absent spec edges,
+    //spec edges with a MultiplicityViolation, and Divergent propagated edges (one per offending module dependency,
+    //its counter = number of impl edges behind it). impl edges carry the verdict too, but
+    //listing them here would count every divergence once per offending call.
+    //NotAnalyzed unless the states are current.
+    pub fn violations(&self) -> Result<Vec<EdgeId>, GraphError> {
+        self.require_analyzed()?;
+        Ok(self.violation_ids())
+    }
+
+    //the impl edges behind each Divergent dependency of the last analyze, bucketed by the
+    //location of their source node (its own, else the nearest ancestor's; UNKNOWN_FILE when
+    //neither has one), each bucket sorted. for per-file warnings in editors and CI comments.
+    pub fn violations_by_file(&self) -> Result<HashMap<String, Vec<EdgeId>>, GraphError> {
+        self.require_analyzed()?;
+        let mut by_file: HashMap<String, Vec<EdgeId>> = HashMap::new();
+        for eid in self.edges_with_state_in(SubgraphKind::Propagated, EdgeState::Divergent) {
+            for &impl_edge in self.supporting_impl_edges(eid).into_iter().flatten() {
+                let from = self.edge_at(impl_edge).from;
+                let file = self
+                    .self_and_ancestors(from)
+                    .iter()
+                    .find_map(|n| self.node_at(*n).location.as_deref())
+                    .unwrap_or(UNKNOWN_FILE);
+                by_file.entry(file.to_string()).or_default().push(impl_edge);
+            }
+        }
+        for ids in by_file.values_mut() {
+            ids.sort_unstable();
+        }
+        Ok(by_file)
+    }
+
+    //Divergent dependencies of the last analyze that only test code causes: every impl edge
+    //behind them is tagged with_test_only. sorted; empty when the run excluded test edges.
+    pub fn test_divergences(&self) -> Result<Vec<EdgeId>, GraphError> {
+        self.require_analyzed()?;
+        Ok(self
+            .edges_with_state_in(SubgraphKind::Propagated, EdgeState::Divergent)
+            .into_iter()
+            .filter(|eid| {
+                let mut support = self.supporting_impl_edges(*eid).into_iter().flatten().peekable();
+                support.peek().is_some() && support.all(|i| self.edge_at(*i).test_only)
+            })
+            .collect())
+    }
+
+    //violations() with the fields every report resolves anyway, in the same order. the
+    //id-only violations() stays the cheap check. NotAnalyzed like violations().
+    pub fn violations_detailed(&self) -> Result<Vec<Violation>, GraphError> {
+        let end = |id: NodeId| {
+            let n = self.node_at(id);
+            ViolationEnd { id, name: n.name.clone(), kind: n.kind.clone() }
+        };
+        Ok(self
+            .violations()?
+            .into_iter()
+            .map(|eid| {
+                let e = self.edge_at(eid);
+                let mut support: Vec<EdgeId> = self.supporting_impl_edges(eid).into_iter().flatten().copied().collect();
+                support.sort_unstable();
+                Violation {
+                    edge: eid,
+                    state: e.state,
+                    kind: e.kind.clone(),
+                    severity: self.severity_of(&e.kind),
+                    from: end(e.from),
+                    to: end(e.to),
+                    counter: e.counter,
+                    support,
+                }
+            })
+            .collect())
+    }
+
+    //violations without the guard, for reports that show staleness themselves: Absent and
+    //MultiplicityViolation spec edges, Divergent propagated edges, and the impl edges that
+    //breach an API boundary under an otherwise fine module dependency (encapsulation_breaches)
+    pub(crate) fn violation_ids(&self) -> Vec<EdgeId> {
+        let mut ids = self.edges_with_state_in(SubgraphKind::Architecture, EdgeState::Absent);
+        ids.extend(self.edges_with_state_in(SubgraphKind::Architecture, EdgeState::MultiplicityViolation));
+        ids.extend(self.edges_with_state_in(SubgraphKind::Propagated, EdgeState::Divergent));
+        ids.extend(self.encapsulation_breaches());
+        ids.sort_unstable();
+        ids
+    }
+
+    //incremental adoption: downgrade violations of a noisy relation kind to warnings
+    //(or back up to errors). only affects reporting and has_errors, not the states.
+    pub fn set_severity(&mut self, kind: EdgeKind, severity: Severity) {
+        self.severities.insert(kind, severity);
+    }
+
+    //severity of violations of this kind (Error unless set_severity said otherwise)
+    pub fn severity_of(&self, kind: &EdgeKind) -> Severity {
+        self.severities.get(kind).copied().unwrap_or(Severity::Error)
+    }
+
+    //CI gate: does the last analyze have any violation of error severity? NotAnalyzed on a
+    //stale graph, which would otherwise pass the gate with no violations at all.
+    pub fn has_errors(&self) -> Result<bool, GraphError> {
+        Ok(self
+            .violations()?
+            .iter()
+            .any(|eid| self.severity_of(&self.edge_at(*eid).kind) == Severity::Error))
+    }
+
+    //"what did we design but not build?": spec edges left Absent by the last analyze
+    pub fn absent_edges(&self) -> Result<Vec<EdgeId>, GraphError> {
+        self.require_analyzed()?;
+        Ok(self.edges_with_state_in(SubgraphKind::Architecture, EdgeState::Absent))
+    }
+
+    //spec edges whose target module has no implementation at all: no impl node maps to the
+    //target or to any of its descendants (whose dependencies would lift onto it). they can
+    //never converge, and the fix is "build the module", not "add the dependency". mappings
+    //are counted as in the last analyze (see ignore_proposed). sorted ids.
+    pub fn unrealized_targets(&self) -> Result<Vec<EdgeId>, GraphError> {
+        self.require_analyzed()?;
+        let realized = self.realized_arch_nodes();
+        Ok(self.filter_edges(|e| {
+            e.subgraph == SubgraphKind::Architecture
+                && !self.is_structural(&e.kind)
+                && !realized.contains(&e.to)
+        }))
+    }
+
+    //arch nodes with an implementation: mapped onto directly or through a descendant
+    pub(crate) fn realized_arch_nodes(&self) -> HashSet<NodeId> {
+        self.maps_to
+            .iter()
+            .filter(|(i, _)| !(self.last_options.ignore_proposed && self.proposed.contains(*i)))
+            .flat_map(|(_, &a)| self.self_and_ancestors(a))
+            .collect()
+    }
+
+    //Convergent spec edges backed by fewer than `min_support` impl edges: green, but on
+    //thin evidence (a single call, or a couple of runtime samples) that may be accidental
+    pub fn weak_convergences(&self, min_support: Counter) -> Result<Vec<EdgeId>, GraphError> {
+        self.require_analyzed()?;
+        let mut ids = self.edges_with_state_in(SubgraphKind::Architecture, EdgeState::Convergent);
+        ids.retain(|eid| self.edge_at(*eid).counter < min_support);
+        Ok(ids)
+    }
+
+    //focused "here's what's wrong" graph: violations() plus the divergent impl edges behind
+    //them, their endpoint nodes, and the mappings and containment links among those nodes.
+    //states, counters and propagation records are kept; ids are freshly assigned.
+    pub fn violations_subgraph(&self) -> ReflexionGraph {
+        let mut edge_ids: BTreeSet<EdgeId> = self.violation_ids().into_iter().collect();
+        edge_ids.extend(self.edges_with_state_in(SubgraphKind::Implementation, EdgeState::Divergent));
+
+        let node_ids: BTreeSet<NodeId> = edge_ids
+            .iter()
+            .flat_map(|eid| [self.edge_at(*eid).from, self.edge_at(*eid).to])
+            .collect();
+
+        let mut out = ReflexionGraph::new();
+        let mut node_map: HashMap<NodeId, NodeId> = HashMap::new();
+        for &old in &node_ids {
+            let mut node = self.node_at(old).clone();
+            node.parent = None;
+            node.children.clear();
+            node_map.insert(old, out.add_node(node).expect("no parent to check"));
+        }
+        for &old in &node_ids {
+            if let Some(&parent) = self.node_at(old).parent.and_then(|p| node_map.get(&p)) {
+                out.set_parent(node_map[&old], Some(parent)).expect("copied from an acyclic hierarchy");
+            }
+        }
+
+        let mut edge_map: HashMap<EdgeId, EdgeId> = HashMap::new();
+        for &old in &edge_ids {
+            let mut edge = self.edge_at(old).clone();
+            edge.from = node_map[&edge.from];
+            edge.to = node_map[&edge.to];
+            edge_map.insert(old, out.add_edge(edge).expect("endpoints copied above"));
+        }
+
+        for (i_old, a) in self.iter_mapping() {
+            if let (Some(&i), Some(&a)) = (node_map.get(&i_old), node_map.get(&a)) {
+                out.put_mapping(i, a);
+                if self.proposed.contains(&i_old) {
+                    out.proposed.insert(i);
+                }
+            }
+        }
+        out.severities = self.severities.clone();
+        out.active_variant = self.active_variant.clone();
+        out.metadata = self.metadata.clone();
+        for (old, &layer) in &self.layers {
+            if let Some(&new) = node_map.get(old) {
+                out.layers.insert(new, layer);
+            }
+        }
+        out.api_boundaries = self.api_boundaries.iter().filter_map(|n| node_map.get(n).copied()).collect();
+        for (old, group) in &self.groups {
+            if let Some(&new) = node_map.get(old) {
+                out.groups.insert(new, group.clone());
+            }
+        }
+        for (old, support) in &self.propagation_table {
+            if let Some(&new) = edge_map.get(old) {
+                let kept = support.iter().filter_map(|s| edge_map.get(s).copied()).collect();
+                out.propagation_table.insert(new, kept);
+            }
+        }
+
+        if self.is_analyzed() {
+            out.set_analyzed();
+        }
+        out
+    }
+
+    //coverage of one arch node on its own. unknown ids give an all-zero Coverage.
+    pub fn arch_coverage(&self, arch_node: NodeId) -> Coverage {
+        self.coverage_of(&[arch_node].into_iter().collect())
+    }
+
+    //coverage of an arch node together with all of its descendants: a spec edge between
+    //two nodes of the subtree is counted once, mappings onto any of them count.
+    pub fn arch_coverage_subtree(&self, arch_node: NodeId) -> Coverage {
+        let mut members: HashSet<NodeId> = self.descendants(arch_node).into_iter().collect();
+        members.insert(arch_node);
+        self.coverage_of(&members)
+    }
+
+    fn coverage_of(&self, members: &HashSet<NodeId>) -> Coverage {
+        let mut cov = Coverage::default();
+        for e in self.store.iter_edges() {
+            if e.subgraph != SubgraphKind::Architecture
+                || self.is_structural(&e.kind)
+                || !(members.contains(&e.from) || members.contains(&e.to))
+            {
+                continue;
+            }
+            cov.specified += 1;
+            match e.state {
+                EdgeState::Convergent => cov.convergent += 1,
+                EdgeState::Absent => cov.absent += 1,
+                _ => {}
+            }
+        }
+        cov.mapped_impl_nodes = members.iter().map(|&a| self.implementors(a).len()).sum();
+        cov
+    }
+
+    //specified arch edges grouped by their source module (module report card), each list
+    //sorted by id. modules without outgoing spec edges are left out.
+    pub fn arch_module_edges(&self) -> HashMap<NodeId, Vec<EdgeId>> {
+        let mut out: HashMap<NodeId, Vec<EdgeId>> = HashMap::new();
+        for module in self.store.iter_nodes().filter(|n| n.subgraph == SubgraphKind::Architecture).map(|n| n.id) {
+            let mut spec: Vec<EdgeId> = self
+                .store
+                .out_edges(module, SubgraphKind::Architecture)
+                .iter()
+                .copied()
+                .filter(|eid| self.edge_at(*eid).subgraph == SubgraphKind::Architecture)
+                .collect();
+            if !spec.is_empty() {
+                spec.sort_unstable();
+                out.insert(module, spec);
+            }
+        }
+        out
+    }
+
+    //like arch_module_edges, but every module also lists the spec edges leaving its
+    //descendants (a container's card covers its whole subtree)
+    pub fn arch_module_edges_rolled_up(&self) -> HashMap<NodeId, Vec<EdgeId>> {
+        let mut out: HashMap<NodeId, Vec<EdgeId>> = HashMap::new();
+        for (module, edges) in self.arch_module_edges() {
+            for owner in self.self_and_ancestors(module) {
+                out.entry(owner).or_default().extend(&edges);
+            }
+        }
+        for edges in out.values_mut() {
+            edges.sort_unstable();
+        }
+        out
+    }
+
+    //absent_edges as sorted (from name, to name) pairs
+    pub fn absent_report(&self) -> Result<Vec<(String, String)>, GraphError> {
+        let mut report: Vec<(String, String)> = self
+            .absent_edges()?
+            .into_iter()
+            .map(|eid| {
+                let e = self.edge_at(eid);
+                (self.node_name(e.from).to_string(), self.node_name(e.to).to_string())
+            })
+            .collect();
+        report.sort();
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::{Edge, GraphError, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+    use crate::core::state::EdgeState;
+
+    #[test]
+    fn absent_edges_lists_unimplemented_design() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        let built = g.add_edge(Edge::new(ui, app, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let missing = g.add_edge(Edge::new(app, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+
+        let u = g.add_node(Node::new("u", SubgraphKind::Implementation, None)).unwrap();
+        let a = g.add_node(Node::new("a", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(u, ui).unwrap();
+        g.set_mapping(a, app).unwrap();
+        g.add_edge(Edge::new(u, a, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(a, u, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+
+        assert_eq!(g.absent_edges().unwrap(), vec![missing]);
+        assert!(!g.absent_edges().unwrap().contains(&built));
+        assert_eq!(g.absent_report().unwrap(), vec![("App".to_string(), "Db".to_string())]);
+
+        //the App -> UI divergence shows up once, as its propagated edge
+        let violations = g.violations().unwrap();
+        assert_eq!(violations.len(), 2);
+        assert!(violations.contains(&missing));
+    }
+
+    #[test]
+    fn states_for_aligns_with_input_ids() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let spec = g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let code = g.add_edge(Edge::new(i, i, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+
+        assert_eq!(
+            g.states_for(&[code, spec, code]).unwrap(),
+            vec![EdgeState::Unmapped, EdgeState::Absent, EdgeState::Unmapped]
+        );
+        assert!(g.states_for(&[]).unwrap().is_empty());
+        assert_eq!(g.states_for(&[spec, 999]).unwrap_err(), GraphError::EdgeNotFound(999));
+    }
+
+    #[test]
+    fn weak_convergences_filters_by_support() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let c = g.add_node(Node::new("C", SubgraphKind::Architecture, None)).unwrap();
+        let thin = g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let solid = g.add_edge(Edge::new(a, c, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(b, c, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+
+        let ia = g.add_node(Node::new("ia", SubgraphKind::Implementation, None)).unwrap();
+        let ib = g.add_node(Node::new("ib", SubgraphKind::Implementation, None)).unwrap();
+        let ic = g.add_node(Node::new("ic", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(ia, a).unwrap();
+        g.set_mapping(ib, b).unwrap();
+        g.set_mapping(ic, c).unwrap();
+        g.add_edge(Edge::new(ia, ib, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        for _ in 0..3 {
+            g.add_edge(Edge::new(ia, ic, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        }
+
+        g.analyze(&AnalysisOptions::default());
+
+        //the absent B -> C is not a (weak) convergence
+        assert_eq!(g.weak_convergences(2).unwrap(), vec![thin]);
+        assert_eq!(g.weak_convergences(4).unwrap(), vec![thin, solid]);
+        assert!(g.weak_convergences(1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn violations_subgraph_keeps_only_the_problems() {
+        let mut g = ReflexionGraph::new();
+        let sys = g.add_node(Node::new("Sys", SubgraphKind::Architecture, None)).unwrap();
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, Some(sys))).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, Some(sys))).unwrap();
+        let log = g.add_node(Node::new("Log", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(app, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(app, log, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+
+        let a = g.add_node(Node::new("a", SubgraphKind::Implementation, None)).unwrap();
+        let d = g.add_node(Node::new("d", SubgraphKind::Implementation, None)).unwrap();
+        let l = g.add_node(Node::new("l", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(a, app).unwrap();
+        g.set_mapping(d, db).unwrap();
+        g.set_mapping(l, log).unwrap();
+        g.add_edge(Edge::new(a, l, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(d, a, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+        let v = g.violations_subgraph();
+
+        //App -> Db absent, Db -> App divergent (propagated + impl), nothing about Log
+        assert!(v.is_analyzed());
+        assert_eq!(
+            v.to_canonical(),
+            "\
+nodes:
+  Architecture App parent=-
+  Architecture Db parent=-
+  Implementation a parent=-
+  Implementation d parent=-
+edges:
+  Architecture App -> Db [calls] state=Absent counter=0
+  Implementation d -> a [calls] state=Divergent counter=0
+  Propagated Db -> App [calls] state=Divergent counter=1
+mappings:
+  a => App
+  d => Db
+"
+        );
+        let prop = v.edges_in_subgraph(SubgraphKind::Propagated)[0];
+        let imp = v.edges_in_subgraph(SubgraphKind::Implementation)[0];
+        assert!(v.propagation_table[&prop].contains(&imp));
+    }
+
+    #[test]
+    fn arch_coverage_rolls_up_edge_states() {
+        use super::Coverage;
+
+        let mut g = ReflexionGraph::new();
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let ui = g.add_node(Node::new("Ui", SubgraphKind::Architecture, Some(app))).unwrap();
+        let logic = g.add_node(Node::new("Logic", SubgraphKind::Architecture, Some(app))).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(ui, logic, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(logic, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(app, db, EdgeKind::contains(), SubgraphKind::Architecture)).unwrap();
+
+        let u = g.add_node(Node::new("u", SubgraphKind::Implementation, None)).unwrap();
+        let l = g.add_node(Node::new("l", SubgraphKind::Implementation, None)).unwrap();
+        let l2 = g.add_node(Node::new("l2", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(u, ui).unwrap();
+        g.set_mapping(l, logic).unwrap();
+        g.set_mapping(l2, logic).unwrap();
+        g.add_edge(Edge::new(u, l, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        g.analyze(&AnalysisOptions::default());
+
+        let logic_cov = g.arch_coverage(logic);
+        assert_eq!(logic_cov, Coverage { specified: 2, convergent: 1, absent: 1, mapped_impl_nodes: 2 });
+        assert_eq!(logic_cov.ratio(), 0.5);
+
+        //the structural contains edge doesn't count, Ui -> Logic is counted once
+        assert_eq!(g.arch_coverage(app), Coverage::default());
+        assert_eq!(
+            g.arch_coverage_subtree(app),
+            Coverage { specified: 2, convergent: 1, absent: 1, mapped_impl_nodes: 3 }
+        );
+        assert_eq!(g.arch_coverage(999), Coverage::default());
+    }
+
+    #[test]
+    fn has_errors_ignores_warning_kinds() {
+        use crate::core::state::Severity;
+
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i, a).unwrap();
+        g.set_mapping(j, b).unwrap();
+        g.add_edge(Edge::new(i, j, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        //a stale graph must not pass the gate
+        assert_eq!(g.has_errors(), Err(GraphError::NotAnalyzed));
+        g.analyze(&AnalysisOptions::default());
+
+        assert_eq!(g.severity_of(&EdgeKind::calls()), Severity::Error);
+        assert!(g.has_errors().unwrap());
+
+        g.set_severity(EdgeKind::calls(), Severity::Warning);
+        assert_eq!(g.violations().unwrap().len(), 1);
+        assert!(!g.has_errors().unwrap());
+
+        g.add_edge(Edge::new(j, i, EdgeKind::depends_on(), SubgraphKind::Implementation)).unwrap();
+        assert_eq!(g.has_errors(), Err(GraphError::NotAnalyzed));
+        g.analyze(&AnalysisOptions::default());
+        assert!(g.has_errors().unwrap());
+    }
+
+    #[test]
+    fn arch_module_edges_groups_by_source_module() {
+        let mut g = ReflexionGraph::new();
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let ui = g.add_node(Node::new("Ui", SubgraphKind::Architecture, Some(app))).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        let own = g.add_edge(Edge::new(app, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let nested = g.add_edge(Edge::new(ui, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(db, app, EdgeKind::calls(), SubgraphKind::Propagated)).unwrap();
+
+        let flat = g.arch_module_edges();
+        assert_eq!(flat.len(), 2);
+        assert_eq!(flat[&app], vec![own]);
+        assert_eq!(flat[&ui], vec![nested]);
+        assert!(!flat.contains_key(&db));
+
+        let rolled = g.arch_module_edges_rolled_up();
+        assert_eq!(rolled[&app], vec![own, nested]);
+        assert_eq!(rolled[&ui], vec![nested]);
+    }
+
+    #[test]
+    fn unrealized_targets_are_spec_edges_into_unmapped_modules() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let b1 = g.add_node(Node::new("B1", SubgraphKind::Architecture, Some(b))).unwrap();
+        let c = g.add_node(Node::new("C", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let to_c = g.add_edge(Edge::new(a, c, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i, a).unwrap();
+        g.set_mapping(j, b1).unwrap(); //B is realized through its child
+        g.analyze(&AnalysisOptions::default());
+
+        assert_eq!(g.unrealized_targets().unwrap(), vec![to_c]);
+
+        let k = g.add_node(Node::new("k", SubgraphKind::Implementation, None)).unwrap();
+        g.propose_mapping(k, c).unwrap();
+        g.analyze(&AnalysisOptions { ignore_proposed: true, ..Default::default() });
+        assert_eq!(g.unrealized_targets().unwrap(), vec![to_c]);
+        g.analyze(&AnalysisOptions::default());
+        assert!(g.unrealized_targets().unwrap().is_empty());
+    }
+
+    #[test]
+    fn contextual_absences_separate_unwired_from_unbuilt() {
+        use super::AbsenceContext;
+        use crate::core::state::NodeState;
+
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let c = g.add_node(Node::new("C", SubgraphKind::Architecture, None)).unwrap();
+        let d = g.add_node(Node::new("D", SubgraphKind::Architecture, None)).unwrap();
+        let ab = g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let ac = g.add_edge(Edge::new(a, c, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let ca = g.add_edge(Edge::new(c, a, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let cd = g.add_edge(Edge::new(c, d, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        let k = g.add_node(Node::new("k", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i, a).unwrap();
+        g.set_mapping(j, b).unwrap();
+        assert_eq!(g.node_state(a), NodeState::Undefined);
+        g.analyze(&AnalysisOptions::default());
+
+        assert_eq!(g.contextual_absences().unwrap(), vec![
+            (ab, AbsenceContext::NotWired),
+            (ac, AbsenceContext::TargetUnimplemented),
+            (ca, AbsenceContext::SourceUnimplemented),
+            (cd, AbsenceContext::BothUnimplemented),
+        ]);
+        assert_eq!(g.node_state(a), NodeState::Mapped);
+        assert_eq!(g.node_state(c), NodeState::SpecifiedOnly);
+        assert_eq!(g.node_state(i), NodeState::Mapped);
+        assert_eq!(g.node_state(k), NodeState::Unmapped);
+    }
+
+    #[test]
+    fn violations_by_file_buckets_divergent_evidence_by_source_location() {
+        use super::UNKNOWN_FILE;
+
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        let file = g.add_node(Node::new("view", SubgraphKind::Implementation, None).with_location("src/view.rs")).unwrap();
+        let func = g.add_node(Node::new("view::render", SubgraphKind::Implementation, Some(file))).unwrap();
+        let loose = g.add_node(Node::new("helper", SubgraphKind::Implementation, None)).unwrap();
+        let repo = g.add_node(Node::new("repo", SubgraphKind::Implementation, None).with_location("src/repo.rs")).unwrap();
+        for (i, a) in [(file, ui), (func, ui), (loose, ui), (repo, db)] {
+            g.set_mapping(i, a).unwrap();
+        }
+        let direct = g.add_edge(Edge::new(file, repo, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        let nested = g.add_edge(Edge::new(func, repo, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        let unknown = g.add_edge(Edge::new(loose, repo, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(repo, repo, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.analyze(&AnalysisOptions::default());
+
+        let by_file = g.violations_by_file().unwrap();
+        assert_eq!(by_file.len(), 2);
+        assert_eq!(by_file["src/view.rs"], vec![direct, nested]);
+        assert_eq!(by_file[UNKNOWN_FILE], vec![unknown]);
+    }
+
+    #[test]
+    fn violations_detailed_resolves_endpoints_and_support() {
+        use super::{Violation, ViolationEnd};
+        use crate::core::state::Severity;
+        use crate::core::types::NodeKind;
+
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("UI", SubgraphKind::Architecture, None).with_kind(NodeKind::UINode)).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        let spec = g.add_edge(Edge::new(ui, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let (v, d) = (
+            g.add_node(Node::new("view", SubgraphKind::Implementation, None)).unwrap(),
+            g.add_node(Node::new("dao", SubgraphKind::Implementation, None)).unwrap(),
+        );
+        g.set_mapping(v, ui).unwrap();
+        g.set_mapping(d, db).unwrap();
+        let back = g.add_edge(Edge::new(d, v, EdgeKind::depends_on(), SubgraphKind::Implementation)).unwrap();
+        assert_eq!(g.violations_detailed(), Err(GraphError::NotAnalyzed));
+
+        g.set_severity(EdgeKind::depends_on(), Severity::Warning);
+        g.analyze(&AnalysisOptions::default());
+        let detailed = g.violations_detailed().unwrap();
+        assert_eq!(detailed.iter().map(|v| v.edge).collect::<Vec<_>>(), g.violations().unwrap());
+
+        let ui_end = ViolationEnd { id: ui, name: "UI".to_string(), kind: Some(NodeKind::UINode) };
+        let db_end = ViolationEnd { id: db, name: "Db".to_string(), kind: None };
+        assert_eq!(
+            detailed[0],
+            Violation {
+                edge: spec,
+                state: EdgeState::Absent,
+                kind: EdgeKind::calls(),
+                severity: Severity::Error,
+                from: ui_end.clone(),
+                to: db_end.clone(),
+                counter: 0,
+                support: vec![],
+            }
+        );
+        let divergent = &detailed[1];
+        assert_eq!((divergent.state, divergent.severity), (EdgeState::Divergent, Severity::Warning));
+        assert_eq!((&divergent.from, &divergent.to), (&db_end, &ui_end));
+        assert_eq!((divergent.counter, divergent.support.clone()), (1, vec![back]));
+    }
+
+    #[test]
+    fn result_queries_refuse_stale_states() {
+        use std::path::Path;
+
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let spec = g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.init_states();
+        let stale = Some(GraphError::NotAnalyzed);
+        //the guarded list of require_analyzed, checked on a graph that was never analyzed
+        //and on one that changed since
+        let refuses = |g: &ReflexionGraph| {
+            let baseline = ReflexionGraph::new();
+            let io_stale = |e: Option<std::io::Error>| {
+                e.and_then(|e| e.into_inner()).and_then(|e| e.downcast::<GraphError>().ok()).map(|e| *e)
+            };
+            let missing = Path::new("/nonexistent/reflexion-baseline.txt");
+            assert_eq!(g.violations().err(), stale);
+            assert_eq!(g.violations_detailed().err(), stale);
+            assert_eq!(g.violations_by_file().err(), stale);
+            assert_eq!(g.test_divergences().err(), stale);
+            assert_eq!(g.has_errors().err(), stale);
+            assert_eq!(g.absent_edges().err(), stale);
+            assert_eq!(g.contextual_absences().err(), stale);
+            assert_eq!(g.absent_report().err(), stale);
+            assert_eq!(g.unrealized_targets().err(), stale);
+            assert_eq!(g.weak_convergences(2).err(), stale);
+            assert_eq!(g.edges_with_state(EdgeState::Absent).err(), stale);
+            assert_eq!(g.count_states().err(), stale);
+            assert_eq!(g.count_states_in(SubgraphKind::Architecture).err(), stale);
+            assert_eq!(g.convergence_by_kind().err(), stale);
+            assert_eq!(g.violations_by_group().err(), stale);
+            assert_eq!(g.trend_point("4f2a9c1").err(), stale);
+            assert_eq!(g.violation_keys().err(), stale);
+            assert_eq!(g.diff_violations(&baseline).err(), stale);
+            assert_eq!(io_stale(g.write_baseline(missing).err()), stale);
+            assert_eq!(io_stale(g.check_against_baseline(missing).err()), stale);
+        };
+
+        //an unanalyzed graph would otherwise report no violations at all
+        refuses(&g);
+        assert_eq!(g.edges_with_state(EdgeState::Specified), Ok(vec![spec]));
+
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.violations(), Ok(vec![spec]));
+        assert_eq!(g.edges_with_state(EdgeState::Absent), Ok(vec![spec]));
+
+        g.add_node(Node::new("C", SubgraphKind::Architecture, None)).unwrap();
+        refuses(&g);
+        //topology and spec queries don't depend on the states
+        assert_eq!(g.arch_module_edges().len(), 1);
+    }
+}
@@ -0,0 +1,80 @@
+// setup heuristics: catch models that were built the wrong way round
+use crate::core::types::SubgraphKind;
+use crate::core::graph::ReflexionGraph;
+
+//likely modeling mistake found by sanity_check. none of these is an error on its own,
+//but each explains an "everything is Unmapped / Absent" result better than the result does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Warning {
+    //both sides have nodes but nothing maps them: every impl edge ends up Unmapped
+    NoMappings { arch_nodes: usize, impl_nodes: usize },
+    //most maps_to entries run arch -> impl (set_mapping rejects that, so the mappings came
+    //from a damaged binary cache)
+    MappingsReversed { reversed: usize, total: usize },
+    //the spec is bigger than the code: usually arch and impl nodes were added to the wrong subgraph
+    ArchLargerThanImpl { arch_nodes: usize, impl_nodes: usize },
+}
+
+impl ReflexionGraph {
+    //cheap setup checks, run before analyze or when a result looks implausible.
+    //empty graphs and graphs with only one side give no warnings.
+    pub fn sanity_check(&self) -> Vec<Warning> {
+        let arch_nodes = self.store.nodes.values().filter(|n| n.subgraph == SubgraphKind::Architecture).count();
+        let impl_nodes = self.store.nodes.values().filter(|n| n.subgraph == SubgraphKind::Implementation).count();
+        let mut warnings = Vec::new();
+        if arch_nodes == 0 || impl_nodes == 0 {
+            return warnings;
+        }
+
+        if self.maps_to.is_empty() {
+            warnings.push(Warning::NoMappings { arch_nodes, impl_nodes });
+        }
+
+        let side = |n| self.store.nodes.get(n).map(|n| n.subgraph);
+        let reversed = self
+            .maps_to
+            .iter()
+            .filter(|(i, a)| side(i) == Some(SubgraphKind::Architecture) && side(a) == Some(SubgraphKind::Implementation))
+            .count();
+        let total = self.maps_to.len();
+        if reversed * 2 > total {
+            warnings.push(Warning::MappingsReversed { reversed, total });
+        }
+
+        if arch_nodes > impl_nodes {
+            warnings.push(Warning::ArchLargerThanImpl { arch_nodes, impl_nodes });
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Warning;
+    use crate::core::graph::{Node, ReflexionGraph};
+    use crate::core::types::SubgraphKind;
+
+    #[test]
+    fn sanity_check_flags_swapped_setups() {
+        let mut g = ReflexionGraph::new();
+        assert!(g.sanity_check().is_empty());
+
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let a = g.add_node(Node::new("a", SubgraphKind::Implementation, None)).unwrap();
+        let b = g.add_node(Node::new("b", SubgraphKind::Implementation, None)).unwrap();
+        assert_eq!(g.sanity_check(), vec![Warning::NoMappings { arch_nodes: 1, impl_nodes: 2 }]);
+
+        g.put_mapping(app, a); //bypasses set_mapping validation
+        assert_eq!(g.sanity_check(), vec![Warning::MappingsReversed { reversed: 1, total: 1 }]);
+
+        g.clear_mappings();
+        g.set_mapping(a, app).unwrap();
+        g.set_mapping(b, app).unwrap();
+        assert!(g.sanity_check().is_empty());
+
+        for name in ["X", "Y"] {
+            g.add_node(Node::new(name, SubgraphKind::Architecture, None)).unwrap();
+        }
+        assert_eq!(g.sanity_check(), vec![Warning::ArchLargerThanImpl { arch_nodes: 3, impl_nodes: 2 }]);
+    }
+}
@@ -0,0 +1,180 @@
+// spec hygiene: lints over the specified architecture itself
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use crate::core::types::{EdgeId, EdgeKind, NodeId, SubgraphKind};
+use crate::core::graph::{Edge, ReflexionGraph};
+
+impl ReflexionGraph {
+    //spec edges already implied by the transitive closure of the other spec edges of the
+    //same kind: A -> C is redundant when A -> B -> ... -> C is specified too, or when A -> C
+    //is declared more than once (every copy but the lowest id is listed). only edges that apply together count: an unlabeled edge is implied by
+    //unlabeled ones, an edge of a variant by unlabeled ones and those of its variant.
+    //structural kinds are skipped. cycle-safe; sorted ids.
+    pub fn redundant_spec_edges(&self) -> Vec<EdgeId> {
+        self.filter_edges(|e| {
+            e.subgraph == SubgraphKind::Architecture && !self.is_structural(&e.kind) && self.implied_by_others(e)
+        })
+    }
+
+    //sign-off checklist of the intended relationships: every ordered (from, to) module pair
+    //with at least one spec edge, with the distinct kinds between them (sorted by name).
+    //pairs sorted by (from, to); structural kinds are skipped, like in the other lints.
+    pub fn arch_module_pairs(&self) -> Vec<(NodeId, NodeId, Vec<EdgeKind>)> {
+        let mut pairs: BTreeMap<(NodeId, NodeId), Vec<EdgeKind>> = BTreeMap::new();
+        for (&from, out) in &self.store.arch_out {
+            for eid in out {
+                let e = &self.store.edges[eid];
+                if e.subgraph == SubgraphKind::Architecture && !self.is_structural(&e.kind) {
+                    pairs.entry((from, e.to)).or_default().push(e.kind.clone());
+                }
+            }
+        }
+        pairs
+            .into_iter()
+            .map(|((from, to), mut kinds)| {
+                kinds.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+                kinds.dedup();
+                (from, to, kinds)
+            })
+            .collect()
+    }
+
+    //rule hygiene: spec edges between two modules that have no implementation at all
+    //(no impl node maps to either endpoint or below it, see unrealized_targets). unlike
+    //an absence nothing is missing in the code yet: the spec describes modules that
+    //haven't been built. structural kinds are skipped; sorted ids.
+    pub fn dead_spec_edges(&self) -> Vec<EdgeId> {
+        let realized = self.realized_arch_nodes();
+        self.filter_edges(|e| {
+            e.subgraph == SubgraphKind::Architecture
+                && !self.is_structural(&e.kind)
+                && !realized.contains(&e.from)
+                && !realized.contains(&e.to)
+        })
+    }
+
+    //is there a path from spec.from to spec.to over other spec edges of its kind and scope.
+    //of identical copies only the lower ids count, so the first copy isn't implied by the
+    //later ones while each of them is implied by it
+    fn implied_by_others(&self, spec: &Edge) -> bool {
+        let later_copy = |e: &Edge| e.id > spec.id && e.from == spec.from && e.to == spec.to && e.variant == spec.variant;
+        let usable = |e: &Edge| {
+            e.id != spec.id
+                && !later_copy(e)
+                && e.subgraph == SubgraphKind::Architecture
+                && e.kind == spec.kind
+                && (e.variant.is_none() || e.variant == spec.variant)
+        };
+
+        let mut seen: HashSet<_> = HashSet::new();
+        let mut queue = VecDeque::from([spec.from]);
+        while let Some(n) = queue.pop_front() {
+            for eid in self.store.arch_out.get(&n).into_iter().flatten() {
+                let e = &self.store.edges[eid];
+                if !usable(e) {
+                    continue;
+                }
+                if e.to == spec.to {
+                    return true;
+                }
+                if seen.insert(e.to) {
+                    queue.push_back(e.to);
+                }
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, NodeId, SubgraphKind};
+
+    fn arch(g: &mut ReflexionGraph, name: &str) -> NodeId {
+        g.add_node(Node::new(name, SubgraphKind::Architecture, None)).unwrap()
+    }
+
+    #[test]
+    fn redundant_spec_edges_finds_transitively_implied_edges() {
+        let mut g = ReflexionGraph::new();
+        let (a, b, c) = (arch(&mut g, "A"), arch(&mut g, "B"), arch(&mut g, "C"));
+        let dep = EdgeKind::depends_on;
+        g.add_edge(Edge::new(a, b, dep(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(b, c, dep(), SubgraphKind::Architecture)).unwrap();
+        let shortcut = g.add_edge(Edge::new(a, c, dep(), SubgraphKind::Architecture)).unwrap();
+        //other kind: not implied by depends_on edges
+        g.add_edge(Edge::new(a, c, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+
+        assert_eq!(g.redundant_spec_edges(), vec![shortcut]);
+    }
+
+    #[test]
+    fn redundant_spec_edges_is_cycle_safe_and_variant_aware() {
+        let mut g = ReflexionGraph::new();
+        let (a, b, c) = (arch(&mut g, "A"), arch(&mut g, "B"), arch(&mut g, "C"));
+        let dep = EdgeKind::depends_on;
+        g.add_edge(Edge::new(a, b, dep(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(b, a, dep(), SubgraphKind::Architecture)).unwrap();
+        //a <-> b cycle: neither edge implies the other, and the search terminates
+        assert!(g.redundant_spec_edges().is_empty());
+
+        let (x, y) = (arch(&mut g, "X"), arch(&mut g, "Y"));
+        g.add_edge(Edge::new(c, x, dep(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(x, y, dep(), SubgraphKind::Architecture).with_variant("target")).unwrap();
+        g.add_edge(Edge::new(c, y, dep(), SubgraphKind::Architecture)).unwrap();
+        //the unlabeled c -> y only follows in the "target" variant
+        assert!(g.redundant_spec_edges().is_empty());
+
+        let cy_target = g.add_edge(Edge::new(c, y, dep(), SubgraphKind::Architecture).with_variant("target")).unwrap();
+        assert_eq!(g.redundant_spec_edges(), vec![cy_target]);
+    }
+
+    #[test]
+    fn dead_spec_edges_join_two_unbuilt_modules() {
+        let mut g = ReflexionGraph::new();
+        let (a, b, c) = (arch(&mut g, "A"), arch(&mut g, "B"), arch(&mut g, "C"));
+        let planned = g.add_node(Node::new("Planned", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let dead = g.add_edge(Edge::new(c, planned, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let half = g.add_edge(Edge::new(a, c, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i, a).unwrap();
+        g.set_mapping(j, b).unwrap();
+
+        assert_eq!(g.dead_spec_edges(), vec![dead]);
+        g.analyze(&crate::core::classify::AnalysisOptions::default());
+        assert_eq!(g.unrealized_targets().unwrap(), vec![dead, half]);
+    }
+
+    #[test]
+    fn redundant_spec_edges_keeps_the_first_copy_of_a_duplicated_rule() {
+        let mut g = ReflexionGraph::new();
+        let (a, b) = (arch(&mut g, "A"), arch(&mut g, "B"));
+        let first = g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let second = g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let third = g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        assert_eq!(g.redundant_spec_edges(), vec![second, third]);
+
+        g.remove_edge(second).unwrap();
+        g.remove_edge(third).unwrap();
+        assert!(g.redundant_spec_edges().is_empty());
+        assert!(g.edge(first).is_some());
+    }
+
+    #[test]
+    fn arch_module_pairs_lists_each_specified_pair_once_with_its_kinds() {
+        let mut g = ReflexionGraph::new();
+        let (a, b, c) = (arch(&mut g, "A"), arch(&mut g, "B"), arch(&mut g, "C"));
+        g.add_edge(Edge::new(b, c, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture).with_variant("v2")).unwrap();
+        g.add_edge(Edge::new(a, c, EdgeKind::contains(), SubgraphKind::Architecture)).unwrap();
+
+        assert_eq!(
+            g.arch_module_pairs(),
+            vec![(a, b, vec![EdgeKind::calls(), EdgeKind::depends_on()]), (b, c, vec![EdgeKind::depends_on()])]
+        );
+    }
+}
@@ -1,24 +1,51 @@
+use std::cmp::Ordering;
+
 // convergent, divergent, etc..
+//the discriminants are the persisted encoding (as_u8/from_u8, the binary format): never
+//renumber them, new states take the next free value
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(u8)]
 pub enum EdgeState {
-    Undefined, //we don't know yet/missing data
-    Specified, //edge exists in Architecture spec 
-    Convergent, //spec says it should exist AND code contains a matching edge (happy path)
-    Absent, //spec says it should exist BUT code doesn't contain it 
-    AllowedAbsent, //spec says it's optional, if it is missing it is okay
-    Allowed, //spec doesn't mention it explicitly, but the spec allows it (allowed edge list) 
-    Divergent, //code contains and edge that isn't specified and not allowed by rules/spec 
-    Unmapped, //we can't compare because mapping is missing
+    Undefined = 0, //we don't know yet/missing data
+    Specified = 1, //edge exists in Architecture spec 
+    Convergent = 2, //spec says it should exist AND code contains a matching edge (happy path)
+    Absent = 3, //spec says it should exist BUT code doesn't contain it 
+    AllowedAbsent = 4, //spec says it's optional, if it is missing it is okay
+    Allowed = 5, //spec doesn't mention it explicitly, but the spec allows it (allowed edge list) 
+    Divergent = 6, //code contains and edge that isn't specified and not allowed by rules/spec 
+    Unmapped = 7, //we can't compare because mapping is missing
+    MultiplicityViolation = 8, //spec edge is realized, but by fewer/more impl edges than its min/max_support
 }
 
 impl EdgeState {
-    //violation -> (absent, divergent)
+    //stable one-byte encoding for external storage (columnar exports, ...)
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    //inverse of as_u8, None for bytes no state uses
+    pub fn from_u8(byte: u8) -> Option<EdgeState> {
+        Some(match byte {
+            0 => EdgeState::Undefined,
+            1 => EdgeState::Specified,
+            2 => EdgeState::Convergent,
+            3 => EdgeState::Absent,
+            4 => EdgeState::AllowedAbsent,
+            5 => EdgeState::Allowed,
+            6 => EdgeState::Divergent,
+            7 => EdgeState::Unmapped,
+            8 => EdgeState::MultiplicityViolation,
+            _ => return None,
+        })
+    }
+
+    //violation -> (absent, divergent, multiplicityViolation)
     //not a violation -> (convergent, allowed, allowedAbsent)
     //neither {analysis incomplete / undecided} -> (undefined, unmapped, specified)
     //violations -> architectural debt
     //undefined/unmapped -> tooling or modeling debt
     pub fn is_violation(&self) -> bool {
-        matches!(self, EdgeState::Absent | EdgeState::Divergent)
+        matches!(self, EdgeState::Absent | EdgeState::Divergent | EdgeState::MultiplicityViolation)
     }
 
     pub fn is_unknown(&self) -> bool {
@@ -28,6 +55,65 @@ impl EdgeState {
     pub fn is_ok(&self) -> bool {
         matches!(self, EdgeState::Allowed | EdgeState::AllowedAbsent | EdgeState::Convergent)
     }
+
+    //the lift rule for one propagated dependency: realizes a spec edge -> Convergent,
+    //else inside one module -> Allowed, else Divergent. pure, so what-if queries
+    //(classify_hypothetical) and the analysis can't disagree.
+    pub fn classify(matches_spec: bool, same_module: bool) -> EdgeState {
+        match (matches_spec, same_module) {
+            (true, _) => EdgeState::Convergent,
+            (false, true) => EdgeState::Allowed,
+            (false, false) => EdgeState::Divergent,
+        }
+    }
+
+    //severity rank behind Ord, independent of declaration order:
+    //Divergent > Absent > MultiplicityViolation > Unmapped > Undefined > Specified > AllowedAbsent > Allowed > Convergent
+    //(violations, then unknowns, then fine). worst first = sort descending,
+    //e.g. list.sort_by(|a, b| b.cmp(a))
+    pub fn severity(&self) -> u8 {
+        match self {
+            EdgeState::Convergent => 0,
+            EdgeState::Allowed => 1,
+            EdgeState::AllowedAbsent => 2,
+            EdgeState::Specified => 3,
+            EdgeState::Undefined => 4,
+            EdgeState::Unmapped => 5,
+            EdgeState::MultiplicityViolation => 6,
+            EdgeState::Absent => 7,
+            EdgeState::Divergent => 8,
+        }
+    }
+}
+
+impl Ord for EdgeState {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.severity().cmp(&other.severity())
+    }
+}
+
+impl PartialOrd for EdgeState {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+//where a graph is in the analysis lifecycle, in pipeline order (Ord follows it):
+//init_states -> Initialized, propagation -> Propagated, the full analyze -> Analyzed.
+//any mutation of nodes, edges or mappings goes back to Built.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Phase {
+    Built, //fresh or changed since the last run: states are defaults or stale
+    Initialized, //states reset for a run (Specified / Undefined), nothing propagated yet
+    Propagated, //impl edges lifted and counted, spec edges not classified yet
+    Analyzed, //every state is a result of the last analyze
+}
+
+//how bad a violation of one edge kind is for CI gates (set_severity). Warning < Error.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    Warning, //reported, but doesn't fail has_errors
+    Error, //default for every kind
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -50,16 +136,39 @@ impl NodeState {
     pub fn is_ok(&self) -> bool {
         matches!(self, NodeState::Mapped)
     }
+
+    //severity rank behind Ord: Unmapped > SpecifiedOnly > Undefined > Mapped
+    pub fn severity(&self) -> u8 {
+        match self {
+            NodeState::Mapped => 0,
+            NodeState::Undefined => 1,
+            NodeState::SpecifiedOnly => 2,
+            NodeState::Unmapped => 3,
+        }
+    }
+}
+
+impl Ord for NodeState {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.severity().cmp(&other.severity())
+    }
+}
+
+impl PartialOrd for NodeState {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::EdgeState;
+    use super::{EdgeState, NodeState};
 
     #[test]
     fn is_violation_true_for_absent_and_divergent() {
         assert!(EdgeState::Absent.is_violation());
         assert!(EdgeState::Divergent.is_violation());
+        assert!(EdgeState::MultiplicityViolation.is_violation());
     }
 
     #[test]
@@ -71,4 +180,62 @@ mod tests {
         assert!(!EdgeState::Allowed.is_violation());
         assert!(!EdgeState::Unmapped.is_violation());
     }
+
+    #[test]
+    fn classify_applies_the_lift_rule() {
+        assert_eq!(EdgeState::classify(true, false), EdgeState::Convergent);
+        assert_eq!(EdgeState::classify(true, true), EdgeState::Convergent);
+        assert_eq!(EdgeState::classify(false, true), EdgeState::Allowed);
+        assert_eq!(EdgeState::classify(false, false), EdgeState::Divergent);
+    }
+
+    #[test]
+    fn edge_state_order_follows_documented_severity() {
+        let documented = [
+            EdgeState::Divergent,
+            EdgeState::Absent,
+            EdgeState::MultiplicityViolation,
+            EdgeState::Unmapped,
+            EdgeState::Undefined,
+            EdgeState::Specified,
+            EdgeState::AllowedAbsent,
+            EdgeState::Allowed,
+            EdgeState::Convergent,
+        ];
+        for pair in documented.windows(2) {
+            assert!(pair[0] > pair[1], "{:?} should outrank {:?}", pair[0], pair[1]);
+        }
+
+        let mut list = vec![EdgeState::Convergent, EdgeState::Absent, EdgeState::Divergent, EdgeState::Allowed];
+        list.sort_by(|a, b| b.cmp(a));
+        assert_eq!(list, vec![EdgeState::Divergent, EdgeState::Absent, EdgeState::Allowed, EdgeState::Convergent]);
+    }
+
+    #[test]
+    fn edge_state_byte_encoding_is_pinned() {
+        let pinned = [
+            (EdgeState::Undefined, 0),
+            (EdgeState::Specified, 1),
+            (EdgeState::Convergent, 2),
+            (EdgeState::Absent, 3),
+            (EdgeState::AllowedAbsent, 4),
+            (EdgeState::Allowed, 5),
+            (EdgeState::Divergent, 6),
+            (EdgeState::Unmapped, 7),
+            (EdgeState::MultiplicityViolation, 8),
+        ];
+        for (state, byte) in pinned {
+            assert_eq!(state.as_u8(), byte);
+            assert_eq!(EdgeState::from_u8(byte), Some(state));
+        }
+        assert_eq!(EdgeState::from_u8(9), None);
+        assert_eq!(EdgeState::from_u8(u8::MAX), None);
+    }
+
+    #[test]
+    fn node_state_order_puts_problems_highest() {
+        assert!(NodeState::Unmapped > NodeState::SpecifiedOnly);
+        assert!(NodeState::SpecifiedOnly > NodeState::Undefined);
+        assert!(NodeState::Undefined > NodeState::Mapped);
+    }
 }
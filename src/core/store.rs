@@ -0,0 +1,272 @@
+// pluggable node/edge storage behind ReflexionGraph
+use std::collections::HashMap;
+use crate::core::types::{EdgeId, NodeId, SubgraphKind};
+use crate::core::graph::{Edge, Node};
+
+//what ReflexionGraph needs from its node/edge storage: lookup, insertion/removal by id
+//(ids are assigned by the graph before insert), iteration, and the out-adjacency per
+//side of the model. stores must keep out_edges in sync on insert_edge/remove_edge.
+//scope: a graph over any store can be built, mapped and analyzed: construction, lookups
+//and iteration, mappings, analyze (also via Analyzer), reanalyze_edge and the result
+//queries (violations, convergences, ...) go through this trait only. removals
+//(remove_edge, remove_node, compact, prune_unmapped_leaves), the derived reports (metrics,
+//summary, suggest, ...) and io exist for ReflexionGraph<HashMapStore> only.
+pub trait GraphStore {
+    fn get_node(&self, id: NodeId) -> Option<&Node>;
+    fn get_node_mut(&mut self, id: NodeId) -> Option<&mut Node>;
+    fn get_edge(&self, id: EdgeId) -> Option<&Edge>;
+    fn get_edge_mut(&mut self, id: EdgeId) -> Option<&mut Edge>;
+
+    fn insert_node(&mut self, node: Node);
+    fn insert_edge(&mut self, edge: Edge);
+    fn remove_node(&mut self, id: NodeId) -> Option<Node>;
+    fn remove_edge(&mut self, id: EdgeId) -> Option<Edge>;
+
+    fn iter_nodes(&self) -> Box<dyn Iterator<Item = &Node> + '_>;
+    fn iter_edges(&self) -> Box<dyn Iterator<Item = &Edge> + '_>;
+    fn node_count(&self) -> usize;
+    fn edge_count(&self) -> usize;
+
+    //outgoing edges of `node`, in insertion order: Implementation edges for the
+    //Implementation side, Architecture and Propagated edges for the other two
+    fn out_edges(&self, node: NodeId, subgraph: SubgraphKind) -> &[EdgeId];
+}
+
+//default in-memory store: hash maps plus one adjacency index per side. removals, reports
+//and io use these fields directly, which is why they only exist for this store.
+#[derive(Debug, Clone, Default)]
+pub struct HashMapStore {
+    pub(crate) nodes: HashMap<NodeId, Node>,
+    pub(crate) edges: HashMap<EdgeId, Edge>,
+    pub(crate) impl_out: HashMap<NodeId, Vec<EdgeId>>,
+    pub(crate) arch_out: HashMap<NodeId, Vec<EdgeId>>,
+}
+
+impl HashMapStore {
+    fn index_for(&mut self, subgraph: SubgraphKind) -> &mut HashMap<NodeId, Vec<EdgeId>> {
+        match subgraph {
+            SubgraphKind::Implementation => &mut self.impl_out,
+            SubgraphKind::Architecture | SubgraphKind::Propagated => &mut self.arch_out,
+        }
+    }
+}
+
+impl GraphStore for HashMapStore {
+    fn get_node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(&id)
+    }
+
+    fn get_node_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.get_mut(&id)
+    }
+
+    fn get_edge(&self, id: EdgeId) -> Option<&Edge> {
+        self.edges.get(&id)
+    }
+
+    fn get_edge_mut(&mut self, id: EdgeId) -> Option<&mut Edge> {
+        self.edges.get_mut(&id)
+    }
+
+    fn insert_node(&mut self, node: Node) {
+        self.nodes.insert(node.id, node);
+    }
+
+    fn insert_edge(&mut self, edge: Edge) {
+        self.index_for(edge.subgraph).entry(edge.from).or_default().push(edge.id);
+        self.edges.insert(edge.id, edge);
+    }
+
+    fn remove_node(&mut self, id: NodeId) -> Option<Node> {
+        self.impl_out.remove(&id);
+        self.arch_out.remove(&id);
+        self.nodes.remove(&id)
+    }
+
+    fn remove_edge(&mut self, id: EdgeId) -> Option<Edge> {
+        let edge = self.edges.remove(&id)?;
+        if let Some(out) = self.index_for(edge.subgraph).get_mut(&edge.from) {
+            out.retain(|&e| e != id);
+        }
+        Some(edge)
+    }
+
+    fn iter_nodes(&self) -> Box<dyn Iterator<Item = &Node> + '_> {
+        Box::new(self.nodes.values())
+    }
+
+    fn iter_edges(&self) -> Box<dyn Iterator<Item = &Edge> + '_> {
+        Box::new(self.edges.values())
+    }
+
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    fn out_edges(&self, node: NodeId, subgraph: SubgraphKind) -> &[EdgeId] {
+        let index = match subgraph {
+            SubgraphKind::Implementation => &self.impl_out,
+            SubgraphKind::Architecture | SubgraphKind::Propagated => &self.arch_out,
+        };
+        index.get(&node).map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::graph::ReflexionGraph;
+    use crate::core::analyzer::Analyzer;
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::state::EdgeState;
+    use crate::core::types::EdgeKind;
+
+    //minimal alternative backend: plain vectors, linear lookups
+    #[derive(Default)]
+    struct VecStore {
+        nodes: Vec<Node>,
+        edges: Vec<Edge>,
+        out: Vec<(NodeId, SubgraphKind, Vec<EdgeId>)>,
+    }
+
+    fn side(subgraph: SubgraphKind) -> SubgraphKind {
+        match subgraph {
+            SubgraphKind::Propagated => SubgraphKind::Architecture,
+            other => other,
+        }
+    }
+
+    impl GraphStore for VecStore {
+        fn get_node(&self, id: NodeId) -> Option<&Node> {
+            self.nodes.iter().find(|n| n.id == id)
+        }
+        fn get_node_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+            self.nodes.iter_mut().find(|n| n.id == id)
+        }
+        fn get_edge(&self, id: EdgeId) -> Option<&Edge> {
+            self.edges.iter().find(|e| e.id == id)
+        }
+        fn get_edge_mut(&mut self, id: EdgeId) -> Option<&mut Edge> {
+            self.edges.iter_mut().find(|e| e.id == id)
+        }
+        fn insert_node(&mut self, node: Node) {
+            self.nodes.push(node);
+        }
+        fn insert_edge(&mut self, edge: Edge) {
+            let key = (edge.from, side(edge.subgraph));
+            match self.out.iter_mut().find(|(n, s, _)| (*n, *s) == key) {
+                Some((_, _, ids)) => ids.push(edge.id),
+                None => self.out.push((key.0, key.1, vec![edge.id])),
+            }
+            self.edges.push(edge);
+        }
+        fn remove_node(&mut self, id: NodeId) -> Option<Node> {
+            let pos = self.nodes.iter().position(|n| n.id == id)?;
+            self.out.retain(|(n, _, _)| *n != id);
+            Some(self.nodes.remove(pos))
+        }
+        fn remove_edge(&mut self, id: EdgeId) -> Option<Edge> {
+            let pos = self.edges.iter().position(|e| e.id == id)?;
+            for (_, _, ids) in &mut self.out {
+                ids.retain(|&e| e != id);
+            }
+            Some(self.edges.remove(pos))
+        }
+        fn iter_nodes(&self) -> Box<dyn Iterator<Item = &Node> + '_> {
+            Box::new(self.nodes.iter())
+        }
+        fn iter_edges(&self) -> Box<dyn Iterator<Item = &Edge> + '_> {
+            Box::new(self.edges.iter())
+        }
+        fn node_count(&self) -> usize {
+            self.nodes.len()
+        }
+        fn edge_count(&self) -> usize {
+            self.edges.len()
+        }
+        fn out_edges(&self, node: NodeId, subgraph: SubgraphKind) -> &[EdgeId] {
+            let key = (node, side(subgraph));
+            self.out.iter().find(|(n, s, _)| (*n, *s) == key).map_or(&[], |(_, _, ids)| ids.as_slice())
+        }
+    }
+
+    #[test]
+    fn graph_runs_on_a_custom_store() {
+        let mut g = ReflexionGraph::with_store(VecStore::default());
+        let pkg = g.add_node(Node::new("pkg", SubgraphKind::Implementation, None)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, Some(pkg))).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        let e = g.add_edge(Edge::new(i, j, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        assert_eq!(g.node(pkg).unwrap().children(), &[i]);
+        assert_eq!(g.edge(e).unwrap().to(), j);
+        assert_eq!(g.store().out_edges(i, SubgraphKind::Implementation), &[e]);
+        assert!(g.store().out_edges(i, SubgraphKind::Architecture).is_empty());
+        assert_eq!(g.nodes_in_subgraph(SubgraphKind::Implementation), vec![pkg, i, j]);
+        assert_eq!(g.add_edge(Edge::new(i, 99, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap_err(),
+            crate::core::graph::GraphError::NodeNotFound(99));
+    }
+
+    #[test]
+    fn with_store_continues_ids_after_existing_content() {
+        let mut store = HashMapStore::default();
+        let mut n = Node::new("old", SubgraphKind::Architecture, None);
+        n.id = 7;
+        store.insert_node(n);
+
+        let mut g = ReflexionGraph::with_store(store);
+        let fresh = g.add_node(Node::new("new", SubgraphKind::Architecture, None)).unwrap();
+        assert_eq!(fresh, 8);
+        assert_eq!(g.node(7).unwrap().name(), "old");
+    }
+
+    //a -> b specified, b -> c not; i -> j realizes the spec edge, j -> k diverges.
+    //returns (spec edge, convergent impl edge, divergent impl edge)
+    fn small_model<S: GraphStore>(g: &mut ReflexionGraph<S>) -> (EdgeId, EdgeId, EdgeId) {
+        let arch = |g: &mut ReflexionGraph<S>, name: &str| g.add_node(Node::new(name, SubgraphKind::Architecture, None)).unwrap();
+        let (a, b, c) = (arch(g, "a"), arch(g, "b"), arch(g, "c"));
+        let spec = g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let mut code = Vec::new();
+        for (name, module) in [("i", a), ("j", b), ("k", c)] {
+            let n = g.add_node(Node::new(name, SubgraphKind::Implementation, None)).unwrap();
+            g.set_mapping(n, module).unwrap();
+            code.push(n);
+        }
+        let conv = g.add_edge(Edge::new(code[0], code[1], EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        let div = g.add_edge(Edge::new(code[1], code[2], EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        (spec, conv, div)
+    }
+
+    #[test]
+    fn analysis_runs_on_a_custom_store() {
+        let mut g = ReflexionGraph::with_store(VecStore::default());
+        let (spec, conv, div) = small_model(&mut g);
+        assert_eq!(g.violations().unwrap_err(), crate::core::graph::GraphError::NotAnalyzed);
+
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.edge(spec).unwrap().state(), EdgeState::Convergent);
+        assert_eq!(g.edge(conv).unwrap().state(), EdgeState::Convergent);
+        assert_eq!(g.edge(div).unwrap().state(), EdgeState::Divergent);
+        let violations = g.violations().unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(g.edge(violations[0]).unwrap().subgraph(), SubgraphKind::Propagated);
+        assert!(g.absent_edges().unwrap().is_empty());
+        assert_eq!(g.reanalyze_edge(div).unwrap(), EdgeState::Divergent);
+
+        //same verdicts (and ids) as the same steps on the default store, also through a
+        //reused Analyzer
+        let mut reference = ReflexionGraph::new();
+        assert_eq!(small_model(&mut reference), (spec, conv, div));
+        reference.analyze(&AnalysisOptions::default());
+        let mut analyzer = Analyzer::new();
+        analyzer.analyze(&mut g, &AnalysisOptions::default());
+        analyzer.analyze(&mut reference, &AnalysisOptions::default());
+        assert_eq!(g.violations_detailed().unwrap(), reference.violations_detailed().unwrap());
+        assert_eq!(g.arch_module_edges(), reference.arch_module_edges());
+        assert_eq!(g.check_invariants(), reference.check_invariants());
+    }
+}
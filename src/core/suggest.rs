@@ -0,0 +1,132 @@
+// name-similarity mapping suggestions for bootstrapping a mapping
+use crate::core::types::{NodeId, SubgraphKind};
+use crate::core::graph::ReflexionGraph;
+
+//lowercase words of a name, split at non-alphanumerics and camelCase humps:
+//"billing::InvoiceService" -> billing, invoice, service
+fn tokens(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower {
+            words.push(std::mem::take(&mut word));
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+//default similarity: shared words over all words (Dice coefficient of the word sets)
+#[cfg(not(feature = "jaro-winkler"))]
+fn similarity(a: &str, b: &str) -> f64 {
+    use std::collections::HashSet;
+    let (a, b): (HashSet<String>, HashSet<String>) = (tokens(a).into_iter().collect(), tokens(b).into_iter().collect());
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    2.0 * a.intersection(&b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+//jaro-winkler feature: Jaro-Winkler similarity of the names reduced to their words
+//(std only), which also scores abbreviations and typos ("InvoiceSvc" vs "Invoices")
+#[cfg(feature = "jaro-winkler")]
+fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b): (Vec<char>, Vec<char>) = (tokens(a).concat().chars().collect(), tokens(b).concat().chars().collect());
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut taken = vec![false; b.len()];
+    let mut matched_a = Vec::new();
+    for (i, &c) in a.iter().enumerate() {
+        let lo = i.saturating_sub(window);
+        let hi = (i + window + 1).min(b.len());
+        if let Some(j) = (lo..hi).find(|&j| !taken[j] && b[j] == c) {
+            taken[j] = true;
+            matched_a.push(c);
+        }
+    }
+    if matched_a.is_empty() {
+        return 0.0;
+    }
+    let matched_b = b.iter().zip(&taken).filter(|(_, t)| **t).map(|(c, _)| *c);
+    let transpositions = matched_a.iter().zip(matched_b).filter(|(x, y)| **x != *y).count() / 2;
+    let m = matched_a.len() as f64;
+    let jaro = (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0;
+    let prefix = a.iter().zip(&b).take(4).take_while(|(x, y)| x == y).count();
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+impl ReflexionGraph {
+    //candidate mappings for non-external impl nodes without one (resolved as in the last analyze):
+    //every (impl node, arch node, score) whose name similarity is at least `threshold`,
+    //best first (ties by impl, then arch id). scores are in [0, 1]; by default the share
+    //of shared words ("billing_invoice" vs "Billing": 0.67), with the jaro-winkler feature
+    //Jaro-Winkler over the words. nothing is mapped: review, then set_mapping.
+    pub fn suggest_mappings(&self, threshold: f64) -> Vec<(NodeId, NodeId, f64)> {
+        let arch: Vec<(NodeId, &str)> = self
+            .store
+            .nodes
+            .values()
+            .filter(|n| n.subgraph == SubgraphKind::Architecture)
+            .map(|n| (n.id, n.name.as_str()))
+            .collect();
+
+        let mut out: Vec<(NodeId, NodeId, f64)> = self
+            .store
+            .nodes
+            .values()
+            .filter(|n| n.subgraph == SubgraphKind::Implementation && !self.is_external_node(n.id))
+            .filter(|n| self.lift_target(n.id, &self.last_options).is_none())
+            .flat_map(|n| arch.iter().map(move |&(a, name)| (n.id, a, similarity(&n.name, name))))
+            .filter(|&(_, _, score)| score >= threshold && score > 0.0)
+            .collect();
+        out.sort_unstable_by(|x, y| y.2.total_cmp(&x.2).then(x.0.cmp(&y.0)).then(x.1.cmp(&y.1)));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::tokens;
+    use crate::core::graph::{Node, ReflexionGraph};
+    use crate::core::types::SubgraphKind;
+
+    #[test]
+    fn tokens_split_separators_and_camel_case() {
+        assert_eq!(tokens("billing::InvoiceService"), vec!["billing", "invoice", "service"]);
+        assert_eq!(tokens("HTTPClient_v2"), vec!["httpclient", "v2"]);
+        assert!(tokens("::").is_empty());
+    }
+
+    #[test]
+    fn suggest_mappings_ranks_unmapped_nodes_by_name_similarity() {
+        let mut g = ReflexionGraph::new();
+        let billing = g.add_node(Node::new("Billing", SubgraphKind::Architecture, None)).unwrap();
+        let ui = g.add_node(Node::new("UserInterface", SubgraphKind::Architecture, None)).unwrap();
+        let invoice = g.add_node(Node::new("billing_invoice", SubgraphKind::Implementation, None)).unwrap();
+        let exact = g.add_node(Node::new("billing", SubgraphKind::Implementation, None)).unwrap();
+        let mapped = g.add_node(Node::new("BillingCore", SubgraphKind::Implementation, None)).unwrap();
+        g.add_node(Node::new("zzz", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(mapped, billing).unwrap();
+
+        let suggestions = g.suggest_mappings(0.6);
+        let pairs: Vec<_> = suggestions.iter().map(|&(i, a, _)| (i, a)).collect();
+        assert_eq!(&pairs[..2], &[(exact, billing), (invoice, billing)]);
+        assert_eq!(suggestions[0].2, 1.0);
+        assert!(suggestions.iter().all(|&(i, a, s)| i != mapped && a != ui && s >= 0.6));
+        assert!(g.suggest_mappings(1.01).is_empty());
+    }
+}
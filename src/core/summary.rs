@@ -0,0 +1,126 @@
+// one-shot run summary for CLI output
+use std::fmt;
+use crate::core::types::{Counter, SubgraphKind};
+use crate::core::graph::ReflexionGraph;
+use crate::core::metrics::StateCounts;
+use crate::core::state::EdgeState;
+
+//how many module dependencies Summary lists as worst offenders
+const TOP_DIVERGENCES: usize = 5;
+
+//the "tl;dr" of the last analyze, see ReflexionGraph::summary. Display prints it as a
+//short multi-line report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Summary {
+    pub analyzed: bool, //false: the counts are defaults or stale
+    pub spec: StateCounts, //Architecture edges
+    pub implementation: StateCounts, //Implementation edges
+    pub violations: usize, //violations(), i.e. judged spec edges and divergent module dependencies
+    pub absent: usize,
+    pub unmapped: usize, //impl edges with an unmapped endpoint
+    pub top_divergences: Vec<(String, String, String, Counter)>, //(from, to, kind, impl edges), most supported first
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.analyzed { "" } else { " (not analyzed, results are stale)" };
+        writeln!(f, "reflexion summary{}", status)?;
+        writeln!(
+            f,
+            "  spec edges: {} ({} convergent, {} absent, {} multiplicity violations)",
+            self.spec.total(),
+            self.spec.convergent,
+            self.absent,
+            self.spec.multiplicity_violation
+        )?;
+        writeln!(
+            f,
+            "  impl edges: {} ({} convergent, {} divergent, {} allowed, {} unmapped)",
+            self.implementation.total(),
+            self.implementation.convergent,
+            self.implementation.divergent,
+            self.implementation.allowed,
+            self.unmapped
+        )?;
+        writeln!(f, "  violations: {}", self.violations)?;
+        if !self.top_divergences.is_empty() {
+            writeln!(f, "worst divergences:")?;
+            for (from, to, kind, support) in &self.top_divergences {
+                writeln!(f, "  {} -> {} [{}] x{}", from, to, kind, support)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ReflexionGraph {
+    //counts as in count_states_in and violations(), plus the divergent module dependencies
+    //with the most impl edges behind them (ties by name). works on a stale graph too:
+    //`analyzed` is false then, and Display says so.
+    pub fn summary(&self) -> Summary {
+        let mut divergent: Vec<(String, String, String, Counter)> = self
+            .edges_with_state_in(SubgraphKind::Propagated, EdgeState::Divergent)
+            .into_iter()
+            .map(|eid| {
+                let e = &self.store.edges[&eid];
+                (self.node_name(e.from).to_string(), self.node_name(e.to).to_string(), e.kind.to_string(), e.counter)
+            })
+            .collect();
+        divergent.sort_by(|a, b| b.3.cmp(&a.3).then_with(|| a.cmp(b)));
+        divergent.truncate(TOP_DIVERGENCES);
+
+        let spec = self.tally_states(Some(SubgraphKind::Architecture));
+        let implementation = self.tally_states(Some(SubgraphKind::Implementation));
+        Summary {
+            analyzed: self.is_analyzed(),
+            spec,
+            implementation,
+            violations: self.violation_ids().len(),
+            absent: spec.absent,
+            unmapped: implementation.unmapped,
+            top_divergences: divergent,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn summary_prints_counts_and_worst_divergences() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("Ui", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(ui, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(ui, db, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+        let u = g.add_node(Node::new("u", SubgraphKind::Implementation, None)).unwrap();
+        let d = g.add_node(Node::new("d", SubgraphKind::Implementation, None)).unwrap();
+        let x = g.add_node(Node::new("x", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(u, ui).unwrap();
+        g.set_mapping(d, db).unwrap();
+        g.add_edge(Edge::new(u, d, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(d, u, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(d, u, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(u, x, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.analyze(&AnalysisOptions::default());
+
+        let s = g.summary();
+        assert_eq!((s.violations, s.absent, s.unmapped), (2, 1, 1));
+        assert_eq!(s.top_divergences, vec![("Db".to_string(), "Ui".to_string(), "calls".to_string(), 2)]);
+        assert_eq!(
+            s.to_string(),
+            "reflexion summary\n\
+             \x20 spec edges: 2 (1 convergent, 1 absent, 0 multiplicity violations)\n\
+             \x20 impl edges: 4 (1 convergent, 2 divergent, 0 allowed, 1 unmapped)\n\
+             \x20 violations: 2\n\
+             worst divergences:\n\
+             \x20 Db -> Ui [calls] x2\n"
+        );
+
+        g.add_node(Node::new("late", SubgraphKind::Implementation, None)).unwrap();
+        assert!(g.summary().to_string().starts_with("reflexion summary (not analyzed"));
+    }
+}
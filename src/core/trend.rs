@@ -0,0 +1,98 @@
+// drift over time: when edges were observed
+use crate::core::types::{EdgeId, SubgraphKind};
+use crate::core::graph::{GraphError, ReflexionGraph};
+use crate::core::state::EdgeState;
+
+//one sample of architectural health, appended per commit for a drift chart
+//(serialized with io::json_writer::trend_to_json)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrendPoint {
+    pub revision: String, //commit hash, tag, whatever identifies the analyzed tree
+    pub convergence_ratio: f64, //convergent share of the classified spec edges, 1.0 without any
+    pub divergences: usize, //divergent module dependencies
+}
+
+impl ReflexionGraph {
+    //the current results as a TrendPoint for `revision`. spec edges still Specified (outside
+    //the analyzed variant) don't count towards the ratio. NotAnalyzed on a stale graph,
+    //whose sample would chart as perfectly healthy.
+    pub fn trend_point(&self, revision: &str) -> Result<TrendPoint, GraphError> {
+        self.require_analyzed()?;
+        let spec = self.tally_states(Some(SubgraphKind::Architecture));
+        let classified = spec.total() - spec.specified - spec.undefined;
+        let convergence_ratio = if classified == 0 { 1.0 } else { spec.convergent as f64 / classified as f64 };
+        Ok(TrendPoint {
+            revision: revision.to_string(),
+            convergence_ratio,
+            divergences: self.edges_with_state_in(SubgraphKind::Propagated, EdgeState::Divergent).len(),
+        })
+    }
+
+    //record that the current extractor run saw `edge`, at `tick` (a run counter, commit
+    //number or unix time, whatever the caller tracks). the first observation sets
+    //first_seen, every one moves last_seen forward. timestamps survive init_states and
+    //analyze and don't affect results, so observing doesn't invalidate them.
+    pub fn observe_edge(&mut self, edge: EdgeId, tick: u64) -> Result<(), GraphError> {
+        let e = self.store.edges.get_mut(&edge).ok_or(GraphError::EdgeNotFound(edge))?;
+        e.first_seen = Some(e.first_seen.map_or(tick, |t| t.min(tick)));
+        e.last_seen = Some(e.last_seen.map_or(tick, |t| t.max(tick)));
+        Ok(())
+    }
+
+    //edges first observed after `tick` ("which divergences came in since the release?"),
+    //sorted ids. edges that were never observed are left out.
+    pub fn edges_newer_than(&self, tick: u64) -> Vec<EdgeId> {
+        self.filter_edges(|e| e.first_seen.is_some_and(|t| t > tick))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TrendPoint;
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::{Edge, GraphError, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn observed_ticks_survive_analysis_and_select_new_edges() {
+        let mut g = ReflexionGraph::new();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        let old = g.add_edge(Edge::new(i, j, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        let new = g.add_edge(Edge::new(j, i, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        let never = g.add_edge(Edge::new(i, i, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+
+        g.observe_edge(old, 1).unwrap();
+        g.observe_edge(old, 2).unwrap();
+        g.observe_edge(new, 2).unwrap();
+        g.analyze(&AnalysisOptions::default());
+        assert!(g.is_analyzed());
+
+        let e = g.edge(old).unwrap();
+        assert_eq!((e.first_seen(), e.last_seen()), (Some(1), Some(2)));
+        assert_eq!(g.edges_newer_than(1), vec![new]);
+        assert_eq!(g.edges_newer_than(0), vec![old, new]);
+        assert_eq!(g.edge(never).unwrap().first_seen(), None);
+        assert_eq!(g.observe_edge(99, 3).unwrap_err(), GraphError::EdgeNotFound(99));
+    }
+
+    #[test]
+    fn trend_point_samples_convergence_and_divergences() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(b, a, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.set_mapping(i, a).unwrap();
+        g.set_mapping(j, b).unwrap();
+        g.add_edge(Edge::new(i, j, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(i, j, EdgeKind::depends_on(), SubgraphKind::Implementation)).unwrap();
+
+        assert_eq!(g.trend_point("r0"), Err(GraphError::NotAnalyzed));
+        g.analyze(&AnalysisOptions::default());
+        let point = g.trend_point("4f2a9c1").unwrap();
+        assert_eq!(point, TrendPoint { revision: "4f2a9c1".to_string(), convergence_ratio: 0.5, divergences: 1 });
+    }
+}
@@ -1,4 +1,5 @@
 use std::fmt;
+use crate::core::graph::GraphError;
 
 // enums + shared types
 pub type NodeId = u32;
@@ -12,6 +13,18 @@ pub enum SubgraphKind {
     Propagated,
 }
 
+impl SubgraphKind {
+    //reading a subgraph name from a file: the variant name, case-insensitive
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "architecture" => Some(SubgraphKind::Architecture),
+            "implementation" => Some(SubgraphKind::Implementation),
+            "propagated" => Some(SubgraphKind::Propagated),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct EdgeKind(String);
 
@@ -20,11 +33,27 @@ impl EdgeKind {
     pub const CONTAINS: &'static str = "contains";
     pub const CALLS: &'static str = "calls";
     pub const DEPENDS_ON: &'static str = "depends_on";
+    const BUILTINS: &'static [&'static str] = &[Self::CONTAINS, Self::CALLS, Self::DEPENDS_ON];
 
+    //blank kinds (empty or only whitespace, unicode included) never match a spec edge and
+    //would silently misclassify, so they are invalid for analysis: prefer try_new for
+    //untrusted input (extractor output, files)
     pub fn new<S: Into<String>>(s: S) -> Self {
         Self(s.into())
     }
 
+    pub fn try_new<S: Into<String>>(s: S) -> Result<Self, GraphError> {
+        let kind = Self::new(s);
+        if kind.is_blank() {
+            return Err(GraphError::InvalidEdgeKind);
+        }
+        Ok(kind)
+    }
+
+    pub fn is_blank(&self) -> bool {
+        self.0.trim().is_empty()
+    }
+
     // ergonomic constructors (so we can write EdgeKind::from(...))
     pub fn contains() -> Self { Self::new(Self::CONTAINS) }
     pub fn calls() -> Self { Self::new(Self::CALLS) }
@@ -33,6 +62,16 @@ impl EdgeKind {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    //the predefined kinds above, for seeding a kind registry or a UI dropdown
+    pub fn builtins() -> &'static [&'static str] {
+        Self::BUILTINS
+    }
+
+    //standard relation (one of builtins()) vs. a custom, extractor-specific one
+    pub fn is_builtin(&self) -> bool {
+        Self::BUILTINS.contains(&self.as_str())
+    }
 }
 
 //Conversions
@@ -83,5 +122,68 @@ impl NodeKind {
     pub fn custom<S: Into<String>>(s: S) -> Self {
         NodeKind::Custom(s.into())
     }
+
+    //lenient reading of a kind name from a file: case-insensitive, "Node" suffix optional
+    //("service", "ServiceNode" and "SERVICE" all give ServiceNode, "ui" gives UINode).
+    //anything else becomes Custom with the text kept as is.
+    pub fn parse(s: &str) -> Self {
+        let lower = s.trim().to_ascii_lowercase();
+        match lower.strip_suffix("node").unwrap_or(&lower) {
+            "architecture" => NodeKind::ArchitectureNode,
+            "implementation" => NodeKind::ImplementationNode,
+            "datastore" => NodeKind::DatastoreNode,
+            "service" => NodeKind::ServiceNode,
+            "ui" => NodeKind::UINode,
+            "module" => NodeKind::ModuleNode,
+            "class" => NodeKind::ClassNode,
+            "package" => NodeKind::PackageNode,
+            "function" => NodeKind::FunctionNode,
+            _ => NodeKind::Custom(s.to_string()),
+        }
+    }
 }
 
+//how much a maps_to entry can be trusted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MappingKind {
+    Confirmed, //set by a human (set_mapping), or reviewed
+    Proposed, //heuristic guess awaiting review (propose_mapping)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EdgeKind;
+    use crate::core::graph::GraphError;
+
+    #[test]
+    fn try_new_rejects_blank_kinds() {
+        assert_eq!(EdgeKind::try_new("").unwrap_err(), GraphError::InvalidEdgeKind);
+        assert_eq!(EdgeKind::try_new("  \t\n").unwrap_err(), GraphError::InvalidEdgeKind);
+        //no-break space and ideographic space are whitespace too
+        assert_eq!(EdgeKind::try_new("\u{00A0}\u{3000}").unwrap_err(), GraphError::InvalidEdgeKind);
+    }
+
+    #[test]
+    fn try_new_keeps_valid_kinds_verbatim() {
+        assert_eq!(EdgeKind::try_new("calls").unwrap(), EdgeKind::calls());
+        assert_eq!(EdgeKind::try_new(" reads ").unwrap().as_str(), " reads ");
+        assert!(EdgeKind::new("").is_blank());
+    }
+
+    #[test]
+    fn builtins_cover_the_predefined_kinds() {
+        assert_eq!(EdgeKind::builtins(), &["contains", "calls", "depends_on"]);
+        assert!(EdgeKind::depends_on().is_builtin());
+        assert!(!EdgeKind::from("reads_field").is_builtin());
+    }
+
+    #[test]
+    fn node_kind_parse_is_lenient_for_builtins() {
+        use super::NodeKind;
+
+        assert_eq!(NodeKind::parse("service"), NodeKind::ServiceNode);
+        assert_eq!(NodeKind::parse("ClassNode"), NodeKind::ClassNode);
+        assert_eq!(NodeKind::parse(" UI "), NodeKind::UINode);
+        assert_eq!(NodeKind::parse("Repository"), NodeKind::custom("Repository"));
+    }
+}
@@ -0,0 +1,79 @@
+// architecture variants: several specs ("current", "target") over one impl graph
+use crate::core::classify::AnalysisOptions;
+use crate::core::graph::{Edge, ReflexionGraph};
+use crate::core::store::GraphStore;
+
+impl<S: GraphStore> ReflexionGraph<S> {
+    //classify the implementation against one architecture variant: spec edges labeled
+    //with another variant (Edge::with_variant) neither absorb impl edges nor get judged,
+    //they stay Specified. unlabeled spec edges belong to every variant. plain analyze
+    //runs against all spec edges regardless of their label.
+    pub fn analyze_against(&mut self, options: &AnalysisOptions, variant: &str) {
+        self.run_analysis(options, Some(variant), &|_| true, &|_| true);
+    }
+
+    //the variant the current states belong to: None after a plain analyze (or before any)
+    pub fn analyzed_variant(&self) -> Option<&str> {
+        self.active_variant.as_deref()
+    }
+
+    //distinct variant labels used by spec edges, sorted
+    pub fn variants(&self) -> Vec<&str> {
+        let mut out: Vec<&str> = self.store.iter_edges().filter_map(Edge::variant).collect();
+        out.sort_unstable();
+        out.dedup();
+        out
+    }
+
+    pub(crate) fn in_active_variant(&self, spec: &Edge) -> bool {
+        match (&spec.variant, &self.active_variant) {
+            (Some(label), Some(active)) => label == active,
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+    use crate::core::state::EdgeState;
+
+    #[test]
+    fn analyze_against_judges_only_the_chosen_variant() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("Ui", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        let svc = g.add_node(Node::new("Service", SubgraphKind::Architecture, None)).unwrap();
+        let now = g.add_edge(Edge::new(ui, db, EdgeKind::calls(), SubgraphKind::Architecture).with_variant("current")).unwrap();
+        let goal = g.add_edge(Edge::new(ui, svc, EdgeKind::calls(), SubgraphKind::Architecture).with_variant("target")).unwrap();
+        let shared = g.add_edge(Edge::new(svc, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+
+        let u = g.add_node(Node::new("u", SubgraphKind::Implementation, None)).unwrap();
+        let d = g.add_node(Node::new("d", SubgraphKind::Implementation, None)).unwrap();
+        g.add_node(Node::new("s", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(u, ui).unwrap();
+        g.set_mapping(d, db).unwrap();
+        let call = g.add_edge(Edge::new(u, d, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        assert_eq!(g.variants(), vec!["current", "target"]);
+
+        g.analyze_against(&AnalysisOptions::default(), "current");
+        assert_eq!(g.analyzed_variant(), Some("current"));
+        assert_eq!(g.edge(call).unwrap().state(), EdgeState::Convergent);
+        assert_eq!(g.edge(now).unwrap().state(), EdgeState::Convergent);
+        assert_eq!(g.edge(goal).unwrap().state(), EdgeState::Specified);
+        assert_eq!(g.edge(shared).unwrap().state(), EdgeState::Absent);
+
+        g.analyze_against(&AnalysisOptions::default(), "target");
+        assert_eq!(g.analyzed_variant(), Some("target"));
+        assert_eq!(g.edge(call).unwrap().state(), EdgeState::Divergent);
+        assert_eq!(g.edge(now).unwrap().state(), EdgeState::Specified);
+        assert_eq!(g.edge(goal).unwrap().state(), EdgeState::Absent);
+
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(g.analyzed_variant(), None);
+        assert_eq!(g.edge(now).unwrap().state(), EdgeState::Convergent);
+        assert_eq!(g.edge(goal).unwrap().state(), EdgeState::Absent);
+    }
+}
@@ -0,0 +1,74 @@
+// lazy traversal along one relation
+use std::collections::HashSet;
+use crate::core::types::{EdgeKind, NodeId, SubgraphKind};
+use crate::core::graph::ReflexionGraph;
+use crate::core::store::GraphStore;
+
+//depth-first walk returned by ReflexionGraph::walk. nodes are produced one at a time,
+//so `.take(n)` or `.find(..)` stop the search early.
+pub struct Walk<'g, S: GraphStore> {
+    graph: &'g ReflexionGraph<S>,
+    kind: EdgeKind,
+    subgraph: SubgraphKind,
+    stack: Vec<NodeId>,
+    seen: HashSet<NodeId>,
+}
+
+impl<S: GraphStore> ReflexionGraph<S> {
+    //nodes reachable from `start` over `kind` edges of `subgraph` ("everything App
+    //transitively depends_on"), depth-first in edge insertion order, `start` first. every
+    //node comes once, so cycles are fine. an unknown start yields nothing.
+    pub fn walk(&self, start: NodeId, kind: EdgeKind, subgraph: SubgraphKind) -> Walk<'_, S> {
+        let stack = if self.store.get_node(start).is_some() { vec![start] } else { Vec::new() };
+        Walk { graph: self, kind, subgraph, stack, seen: HashSet::new() }
+    }
+}
+
+impl<S: GraphStore> Iterator for Walk<'_, S> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        while let Some(node) = self.stack.pop() {
+            if !self.seen.insert(node) {
+                continue;
+            }
+            let store = &self.graph.store;
+            //reversed, so the first edge is explored first
+            for eid in store.out_edges(node, self.subgraph).iter().rev() {
+                if let Some(e) = store.get_edge(*eid)
+                    && e.subgraph == self.subgraph
+                    && e.kind == self.kind
+                    && !self.seen.contains(&e.to)
+                {
+                    self.stack.push(e.to);
+                }
+            }
+            return Some(node);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn walk_follows_one_kind_depth_first_and_survives_cycles() {
+        let mut g = ReflexionGraph::new();
+        let [app, logic, db, log, cache] = ["App", "Logic", "Db", "Log", "Cache"].map(|name| g.add_node(Node::new(name, SubgraphKind::Architecture, None)).unwrap());
+        for (from, to) in [(app, logic), (logic, db), (db, app), (app, log)] {
+            g.add_edge(Edge::new(from, to, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+        }
+        g.add_edge(Edge::new(logic, cache, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+
+        let all: Vec<_> = g.walk(app, EdgeKind::depends_on(), SubgraphKind::Architecture).collect();
+        assert_eq!(all, vec![app, logic, db, log]);
+        let first: Vec<_> = g.walk(app, EdgeKind::depends_on(), SubgraphKind::Architecture).take(2).collect();
+        assert_eq!(first, vec![app, logic]);
+        assert_eq!(g.walk(logic, EdgeKind::calls(), SubgraphKind::Architecture).collect::<Vec<_>>(), vec![logic, cache]);
+        assert_eq!(g.walk(app, EdgeKind::depends_on(), SubgraphKind::Propagated).collect::<Vec<_>>(), vec![app]);
+        assert_eq!(g.walk(99, EdgeKind::depends_on(), SubgraphKind::Architecture).count(), 0);
+    }
+}
@@ -0,0 +1,642 @@
+// compact binary cache format (hand-rolled, the crate has no dependencies)
+//
+// little-endian, length-prefixed, every collection written in sorted id order (edges in
+// insertion order) so equal graphs give equal bytes. adjacency indexes are not stored,
+// from_bytes recomputes them.
+//
+// compatibility: the layout is tied to VERSION and from_bytes only reads its own version.
+// bump VERSION on any change to what gets written, appended fields and reordered records
+// included; a cache from another version is rejected and has to be rebuilt from source.
+// 2 = every field appended since the first cut (node kinds and locations, edge support
+// bounds, variants and observation times, groups, metadata), edges in insertion order.
+// 3 = min_confidence of the last run, the analyze_kinds filter and the kind pattern. the
+// pattern is written whether or not the regex feature is on; a build without it reads
+// the pattern and drops it, having no strict mode to apply it to.
+use std::collections::{HashMap, HashSet};
+use std::io;
+use crate::core::graph::{Edge, GraphError, Node, ReflexionGraph};
+use crate::core::state::{EdgeState, Phase, Severity};
+use crate::core::types::{EdgeId, EdgeKind, NodeId, NodeKind, SubgraphKind};
+
+const MAGIC: &[u8; 4] = b"RFXB";
+const VERSION: u8 = 3;
+
+const SUBGRAPHS: [SubgraphKind; 3] = [SubgraphKind::Architecture, SubgraphKind::Implementation, SubgraphKind::Propagated];
+//node kinds without payload, in tag order (tag 0 = no kind, last tag + 1 = Custom)
+const NODE_KINDS: [NodeKind; 9] = [
+    NodeKind::ArchitectureNode,
+    NodeKind::ImplementationNode,
+    NodeKind::DatastoreNode,
+    NodeKind::ServiceNode,
+    NodeKind::UINode,
+    NodeKind::ModuleNode,
+    NodeKind::ClassNode,
+    NodeKind::PackageNode,
+    NodeKind::FunctionNode,
+];
+const CUSTOM_NODE_KIND: u8 = NODE_KINDS.len() as u8 + 1;
+
+//phase bits of the flags byte: 1 = analyzed (the original flag), 8 / 16 for the partial runs
+fn phase_flags(phase: Phase) -> u8 {
+    match phase {
+        Phase::Built => 0,
+        Phase::Analyzed => 1,
+        Phase::Initialized => 8,
+        Phase::Propagated => 16,
+    }
+}
+
+fn corrupt(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("corrupt graph bytes: {}", what))
+}
+
+fn sorted<T: Copy + Ord>(ids: impl Iterator<Item = T>) -> Vec<T> {
+    let mut ids: Vec<T> = ids.collect();
+    ids.sort_unstable();
+    ids
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, v: u8) {
+        self.0.push(v);
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn len(&mut self, n: usize) {
+        self.u32(u32::try_from(n).expect("more than u32::MAX elements"));
+    }
+
+    fn str(&mut self, s: &str) {
+        self.len(s.len());
+        self.0.extend_from_slice(s.as_bytes());
+    }
+
+    fn opt_u32(&mut self, v: Option<u32>) {
+        match v {
+            Some(v) => {
+                self.u8(1);
+                self.u32(v);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn opt_u64(&mut self, v: Option<u64>) {
+        match v {
+            Some(v) => {
+                self.u8(1);
+                self.0.extend_from_slice(&v.to_le_bytes());
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn opt_str(&mut self, s: Option<&str>) {
+        match s {
+            Some(s) => {
+                self.u8(1);
+                self.str(s);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn subgraph(&mut self, sg: SubgraphKind) {
+        self.u8(SUBGRAPHS.iter().position(|&k| k == sg).expect("listed") as u8);
+    }
+
+    fn state(&mut self, state: EdgeState) {
+        self.u8(state.as_u8());
+    }
+
+    fn node_kind(&mut self, kind: Option<&NodeKind>) {
+        match kind {
+            None => self.u8(0),
+            Some(NodeKind::Custom(name)) => {
+                self.u8(CUSTOM_NODE_KIND);
+                self.str(name);
+            }
+            Some(k) => self.u8(NODE_KINDS.iter().position(|b| b == k).expect("listed") as u8 + 1),
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> io::Result<&[u8]> {
+        let end = self.pos.checked_add(n).filter(|&end| end <= self.bytes.len()).ok_or_else(|| corrupt("truncated"))?;
+        let out = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().expect("4 bytes")))
+    }
+
+    fn len(&mut self) -> io::Result<usize> {
+        Ok(self.u32()? as usize)
+    }
+
+    fn flag(&mut self) -> io::Result<bool> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(corrupt("bad option tag")),
+        }
+    }
+
+    fn str(&mut self) -> io::Result<String> {
+        let n = self.len()?;
+        String::from_utf8(self.take(n)?.to_vec()).map_err(|_| corrupt("string is not utf-8"))
+    }
+
+    fn opt_u32(&mut self) -> io::Result<Option<u32>> {
+        if self.flag()? { self.u32().map(Some) } else { Ok(None) }
+    }
+
+    fn opt_u64(&mut self) -> io::Result<Option<u64>> {
+        if self.flag()? {
+            Ok(Some(u64::from_le_bytes(self.take(8)?.try_into().expect("8 bytes"))))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn opt_str(&mut self) -> io::Result<Option<String>> {
+        if self.flag()? { self.str().map(Some) } else { Ok(None) }
+    }
+
+    fn subgraph(&mut self) -> io::Result<SubgraphKind> {
+        SUBGRAPHS.get(self.u8()? as usize).copied().ok_or_else(|| corrupt("unknown subgraph"))
+    }
+
+    fn state(&mut self) -> io::Result<EdgeState> {
+        EdgeState::from_u8(self.u8()?).ok_or_else(|| corrupt("unknown edge state"))
+    }
+
+    fn node_kind(&mut self) -> io::Result<Option<NodeKind>> {
+        match self.u8()? {
+            0 => Ok(None),
+            CUSTOM_NODE_KIND => Ok(Some(NodeKind::Custom(self.str()?))),
+            tag => NODE_KINDS.get(tag as usize - 1).cloned().map(Some).ok_or_else(|| corrupt("unknown node kind")),
+        }
+    }
+
+    fn ids(&mut self) -> io::Result<Vec<u32>> {
+        let n = self.len()?;
+        (0..n).map(|_| self.u32()).collect()
+    }
+}
+
+impl ReflexionGraph {
+    //the whole graph (nodes, edges with states and pins, mappings, analysis records and
+    //settings) as bytes for caching between runs. much smaller and faster to reload
+    //than a text export (tests/binary_cache_bench.rs: about a quarter of the ndjson
+    //size); the format is versioned, from_bytes rejects other versions.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = Writer(Vec::new());
+        w.0.extend_from_slice(MAGIC);
+        w.u8(VERSION);
+        w.u8(
+            phase_flags(self.phase())
+                | u8::from(self.last_options.ignore_proposed) << 1
+                | u8::from(self.last_options.inherit_mappings) << 2
+                | u8::from(self.last_options.exclude_test_only) << 5,
+        );
+        w.u32(self.next_node_id);
+        w.u32(self.next_edge_id);
+        w.u32(self.last_options.min_confidence.to_bits());
+
+        let node_ids = sorted(self.store.nodes.keys().copied());
+        w.len(node_ids.len());
+        for id in node_ids {
+            let n = &self.store.nodes[&id];
+            w.u32(n.id);
+            w.str(&n.name);
+            w.subgraph(n.subgraph);
+            w.opt_u32(n.parent);
+            w.len(n.children.len());
+            for &c in &n.children {
+                w.u32(c);
+            }
+            w.opt_str(n.description.as_deref());
+            w.node_kind(n.kind.as_ref());
+            w.u8(u8::from(n.external));
+            w.opt_str(n.location.as_deref());
+        }
+
+        //in insertion order, which decode restores from the record order
+        let edge_ids = self.edges_in_insertion_order();
+        w.len(edge_ids.len());
+        for id in edge_ids {
+            let e = &self.store.edges[&id];
+            w.u32(e.id);
+            w.u32(e.from);
+            w.u32(e.to);
+            w.str(e.kind.as_str());
+            w.subgraph(e.subgraph);
+            w.state(e.state);
+            w.u32(e.counter as u32);
+            w.opt_u32(e.confidence.map(f32::to_bits));
+            w.opt_str(e.description.as_deref());
+            match e.pinned {
+                Some(state) => {
+                    w.u8(1);
+                    w.state(state);
+                }
+                None => w.u8(0),
+            }
+            w.opt_u32(e.min_support.map(|n| n as u32));
+            w.opt_u32(e.max_support.map(|n| n as u32));
+            w.opt_str(e.variant.as_deref());
+            w.opt_u64(e.first_seen);
+            w.opt_u64(e.last_seen);
+            w.u8(u8::from(e.symmetric));
+            w.u8(u8::from(e.test_only));
+        }
+
+        let mapped = sorted(self.maps_to.keys().copied());
+        w.len(mapped.len());
+        for i in mapped {
+            w.u32(i);
+            w.u32(self.maps_to[&i]);
+            w.u8(u8::from(self.proposed.contains(&i)));
+        }
+
+        let mut kinds: Vec<&str> = self.structural_kinds.iter().map(EdgeKind::as_str).collect();
+        kinds.sort_unstable();
+        w.len(kinds.len());
+        for k in kinds {
+            w.str(k);
+        }
+
+        let keys = sorted(self.propagation_table.keys().copied());
+        w.len(keys.len());
+        for k in keys {
+            w.u32(k);
+            let support = sorted(self.propagation_table[&k].iter().copied());
+            w.len(support.len());
+            for s in support {
+                w.u32(s);
+            }
+        }
+
+        let keys = sorted(self.ambiguous_lifts.keys().copied());
+        w.len(keys.len());
+        for k in keys {
+            w.u32(k);
+            let candidates = &self.ambiguous_lifts[&k];
+            w.len(candidates.len());
+            for &c in candidates {
+                w.u32(c);
+            }
+        }
+
+        //propagate only ever records NodeNotFound
+        let missing: Vec<NodeId> = self
+            .integrity_errors
+            .iter()
+            .filter_map(|err| match err {
+                GraphError::NodeNotFound(id) => Some(*id),
+                _ => None,
+            })
+            .collect();
+        w.len(missing.len());
+        for id in missing {
+            w.u32(id);
+        }
+
+        let mut severities: Vec<(&str, Severity)> = self.severities.iter().map(|(k, &s)| (k.as_str(), s)).collect();
+        severities.sort_unstable();
+        w.len(severities.len());
+        for (kind, severity) in severities {
+            w.str(kind);
+            w.u8(u8::from(severity == Severity::Error));
+        }
+
+        let layered = sorted(self.layers.keys().copied());
+        w.len(layered.len());
+        for a in layered {
+            w.u32(a);
+            w.u32(self.layers[&a]);
+        }
+        w.opt_str(self.active_variant.as_deref());
+
+        //NodeKind has no order: sort the encoded kinds
+        let mut boundaries: Vec<Vec<u8>> = self
+            .propagation_boundaries
+            .iter()
+            .map(|k| {
+                let mut kw = Writer(Vec::new());
+                kw.node_kind(Some(k));
+                kw.0
+            })
+            .collect();
+        boundaries.sort_unstable();
+        w.len(boundaries.len());
+        for k in boundaries {
+            w.0.extend(k);
+        }
+        let levels = self.last_options.max_propagation_levels;
+        w.opt_u64((levels != usize::MAX).then_some(levels as u64));
+        let api = sorted(self.api_boundaries.iter().copied());
+        w.len(api.len());
+        for a in api {
+            w.u32(a);
+        }
+        let grouped = sorted(self.groups.keys().copied());
+        w.len(grouped.len());
+        for n in grouped {
+            w.u32(n);
+            w.str(&self.groups[&n]);
+        }
+        let tags: Vec<(&str, &str)> = self.meta_iter().collect();
+        w.len(tags.len());
+        for (key, value) in tags {
+            w.str(key);
+            w.str(value);
+        }
+        match &self.analyzed_kinds {
+            Some(selected) => {
+                w.u8(1);
+                let selected = sorted(selected.iter().map(EdgeKind::as_str));
+                w.len(selected.len());
+                for k in selected {
+                    w.str(k);
+                }
+            }
+            None => w.u8(0),
+        }
+        #[cfg(feature = "regex")]
+        w.opt_str(self.kind_pattern());
+        #[cfg(not(feature = "regex"))]
+        w.opt_str(None);
+
+        w.0
+    }
+
+    //inverse of to_bytes. fails with InvalidData on foreign, truncated or inconsistent
+    //input (unknown endpoint or parent ids), never with a panic.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<ReflexionGraph> {
+        let mut r = Reader { bytes, pos: 0 };
+        if r.take(4)? != MAGIC {
+            return Err(corrupt("not a reflexion graph"));
+        }
+        if r.u8()? != VERSION {
+            return Err(corrupt("unsupported format version"));
+        }
+
+        let mut g = ReflexionGraph::new();
+        let flags = r.u8()?;
+        g.last_options.ignore_proposed = flags & 2 != 0;
+        g.last_options.inherit_mappings = flags & 4 != 0;
+        g.last_options.exclude_test_only = flags & 32 != 0;
+        g.next_node_id = r.u32()?;
+        g.next_edge_id = r.u32()?;
+        g.last_options.min_confidence = f32::from_bits(r.u32()?);
+
+        for _ in 0..r.len()? {
+            let id = r.u32()?;
+            let name = r.str()?;
+            let subgraph = r.subgraph()?;
+            let mut node = Node::new(name, subgraph, r.opt_u32()?);
+            node.id = id;
+            node.children = r.ids()?;
+            node.description = r.opt_str()?;
+            node.kind = r.node_kind()?;
+            node.external = r.flag()?;
+            node.location = r.opt_str()?;
+            g.store.nodes.insert(id, node);
+        }
+        for n in g.store.nodes.values() {
+            if n.parent.iter().chain(&n.children).any(|p| !g.store.nodes.contains_key(p)) {
+                return Err(corrupt("unknown parent or child"));
+            }
+        }
+
+        for _ in 0..r.len()? {
+            let id = r.u32()?;
+            let (from, to) = (r.u32()?, r.u32()?);
+            if !g.store.nodes.contains_key(&from) || !g.store.nodes.contains_key(&to) {
+                return Err(corrupt("edge endpoint is not a node"));
+            }
+            let kind = EdgeKind::new(r.str()?);
+            let mut edge = Edge::new(from, to, kind, r.subgraph()?);
+            edge.id = id;
+            edge.state = r.state()?;
+            edge.counter = r.u32()? as i32;
+            edge.confidence = r.opt_u32()?.map(f32::from_bits);
+            edge.description = r.opt_str()?;
+            edge.pinned = if r.flag()? { Some(r.state()?) } else { None };
+            edge.min_support = r.opt_u32()?.map(|n| n as i32);
+            edge.max_support = r.opt_u32()?.map(|n| n as i32);
+            edge.variant = r.opt_str()?;
+            edge.first_seen = r.opt_u64()?;
+            edge.last_seen = r.opt_u64()?;
+            edge.symmetric = r.flag()?;
+            edge.test_only = r.flag()?;
+            if g.store.edges.insert(id, edge).is_some() {
+                return Err(corrupt("duplicate edge id"));
+            }
+            g.insertion_order.push(id);
+        }
+        g.rebuild_indexes();
+
+        for _ in 0..r.len()? {
+            let (i, a) = (r.u32()?, r.u32()?);
+            g.put_mapping(i, a);
+            if r.flag()? {
+                g.proposed.insert(i);
+            }
+        }
+
+        g.structural_kinds = (0..r.len()?).map(|_| r.str().map(EdgeKind::new)).collect::<io::Result<HashSet<_>>>()?;
+
+        for _ in 0..r.len()? {
+            let k: EdgeId = r.u32()?;
+            g.propagation_table.insert(k, r.ids()?.into_iter().collect());
+        }
+        let mut ambiguous: HashMap<EdgeId, Vec<EdgeId>> = HashMap::new();
+        for _ in 0..r.len()? {
+            let k = r.u32()?;
+            ambiguous.insert(k, r.ids()?);
+        }
+        g.ambiguous_lifts = ambiguous;
+        g.integrity_errors = r.ids()?.into_iter().map(GraphError::NodeNotFound).collect();
+        for _ in 0..r.len()? {
+            let kind = EdgeKind::new(r.str()?);
+            let severity = if r.flag()? { Severity::Error } else { Severity::Warning };
+            g.severities.insert(kind, severity);
+        }
+        for _ in 0..r.len()? {
+            let (a, layer) = (r.u32()?, r.u32()?);
+            g.layers.insert(a, layer);
+        }
+        g.active_variant = r.opt_str()?;
+        for _ in 0..r.len()? {
+            let kind = r.node_kind()?.ok_or_else(|| corrupt("missing boundary kind"))?;
+            g.propagation_boundaries.insert(kind);
+        }
+        if let Some(levels) = r.opt_u64()? {
+            g.last_options.max_propagation_levels = usize::try_from(levels).map_err(|_| corrupt("propagation levels out of range"))?;
+        }
+        g.api_boundaries = r.ids()?.into_iter().collect();
+        for _ in 0..r.len()? {
+            let (node, group) = (r.u32()?, r.str()?);
+            g.groups.insert(node, group);
+        }
+        for _ in 0..r.len()? {
+            let (key, value) = (r.str()?, r.str()?);
+            g.metadata.insert(key, value);
+        }
+        if r.flag()? {
+            g.analyzed_kinds = Some((0..r.len()?).map(|_| r.str().map(EdgeKind::new)).collect::<io::Result<HashSet<_>>>()?);
+        }
+        let pattern = r.opt_str()?;
+        #[cfg(feature = "regex")]
+        if let Some(pattern) = pattern {
+            g.set_kind_pattern(&pattern).map_err(|_| corrupt("invalid kind pattern"))?;
+        }
+        #[cfg(not(feature = "regex"))]
+        drop(pattern);
+
+        if r.pos != bytes.len() {
+            return Err(corrupt("trailing bytes"));
+        }
+        g.set_phase(match flags {
+            f if f & 1 != 0 => Phase::Analyzed,
+            f if f & 16 != 0 => Phase::Propagated,
+            f if f & 8 != 0 => Phase::Initialized,
+            _ => Phase::Built,
+        });
+        Ok(g)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::state::{EdgeState, Phase};
+    use crate::core::types::{EdgeKind, NodeKind, SubgraphKind};
+
+    #[test]
+    fn bytes_round_trip_keeps_results_and_ids() {
+        let mut g = ReflexionGraph::new();
+        let sys = g.add_node(Node::new("Sys", SubgraphKind::Architecture, None)).unwrap();
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, Some(sys)).with_description("ui + logic").with_kind(NodeKind::UINode)).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, Some(sys))).unwrap();
+        let spec = g.add_edge(Edge::new(app, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let a = g.add_node(Node::new("a", SubgraphKind::Implementation, None)).unwrap();
+        let d = g.add_node(Node::new("d", SubgraphKind::Implementation, None).with_kind(NodeKind::custom("Dao")).with_location("src/dao.rs")).unwrap();
+        g.set_mapping(a, app).unwrap();
+        g.propose_mapping(d, db).unwrap();
+        let call = g
+            .add_edge(Edge::new(a, d, EdgeKind::calls(), SubgraphKind::Implementation).with_confidence(0.75))
+            .unwrap();
+        g.add_edge(Edge::new(d, a, EdgeKind::from("reads \"cfg\""), SubgraphKind::Implementation)).unwrap();
+        g.assert_edge_state(d, a, &EdgeKind::from("reads \"cfg\""), EdgeState::Allowed).unwrap();
+        g.observe_edge(call, 1 << 40).unwrap();
+        g.set_propagation_boundary_kinds(&[NodeKind::FunctionNode, NodeKind::custom("Lambda")]);
+        g.analyze(&AnalysisOptions { min_confidence: 0.5, max_propagation_levels: 4, ..Default::default() });
+        g.set_meta("commit", "4f2a9c1");
+        g.set_group(d, "storage team").unwrap();
+
+        let bytes = g.to_bytes();
+        let back = ReflexionGraph::from_bytes(&bytes).unwrap();
+
+        assert!(back.is_analyzed());
+        assert_eq!(back.last_options, g.last_options);
+        assert_eq!(back.to_canonical(), g.to_canonical());
+        assert_eq!(back.edges_in_insertion_order(), g.edges_in_insertion_order());
+        g.init_states();
+        assert_eq!(ReflexionGraph::from_bytes(&g.to_bytes()).unwrap().phase(), Phase::Initialized);
+        assert_eq!(back.to_bytes(), bytes);
+        assert_eq!(back.edge(call).unwrap().confidence(), Some(0.75));
+        assert_eq!(back.edge(call).unwrap().first_seen(), Some(1 << 40));
+        assert_eq!(back.propagation_boundaries, g.propagation_boundaries);
+        assert_eq!(back.get_meta("commit"), Some("4f2a9c1"));
+        assert_eq!(back.group_of(d), Some("storage team"));
+        assert!(back.supporting_impl_edges(spec).unwrap().contains(&call));
+        assert_eq!(back.proposed_mappings(), vec![(d, db)]);
+        assert_eq!(back.node(sys).unwrap().children(), &[app, db]);
+        assert_eq!(back.node(app).unwrap().kind(), Some(&NodeKind::UINode));
+        assert_eq!(back.node(d).unwrap().kind(), Some(&NodeKind::custom("Dao")));
+        assert_eq!(back.fan_out(a, SubgraphKind::Implementation), 1);
+
+        //ids keep counting where the original left off
+        let mut back = back;
+        let next = back.add_node(Node::new("n", SubgraphKind::Implementation, None)).unwrap();
+        assert!(next > d);
+    }
+
+    #[test]
+    fn from_bytes_rejects_garbage() {
+        let mut g = ReflexionGraph::new();
+        g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let bytes = g.to_bytes();
+
+        assert!(ReflexionGraph::from_bytes(b"nope").is_err());
+        assert!(ReflexionGraph::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(ReflexionGraph::from_bytes(&trailing).is_err());
+        assert!(ReflexionGraph::from_bytes(&bytes).unwrap().node(1).is_some());
+    }
+
+    #[test]
+    fn from_bytes_rejects_other_format_versions() {
+        let mut g = ReflexionGraph::new();
+        g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let mut bytes = g.to_bytes();
+
+        bytes[4] = 1;
+        let err = ReflexionGraph::from_bytes(&bytes).err().unwrap();
+        assert!(err.to_string().contains("unsupported format version"));
+    }
+
+    #[test]
+    fn reanalyze_edge_after_a_reload_uses_the_cached_run_settings() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        let j = g.add_node(Node::new("j", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(i, a).unwrap();
+        g.set_mapping(j, b).unwrap();
+        let weak = g.add_edge(Edge::new(i, j, EdgeKind::calls(), SubgraphKind::Implementation).with_confidence(0.1)).unwrap();
+        let dep = g.add_edge(Edge::new(i, j, EdgeKind::depends_on(), SubgraphKind::Implementation)).unwrap();
+        let kinds = [EdgeKind::calls()].into_iter().collect();
+        g.analyze_kinds(&kinds, &AnalysisOptions { min_confidence: 0.5, ..Default::default() });
+        assert_eq!(g.edge(weak).unwrap().state(), EdgeState::Undefined);
+
+        let mut back = ReflexionGraph::from_bytes(&g.to_bytes()).unwrap();
+        assert_eq!(back.last_options, g.last_options);
+        assert_eq!(back.analyzed_kinds, g.analyzed_kinds);
+        assert_eq!(back.reanalyze_edge(weak).unwrap(), EdgeState::Undefined);
+        assert_eq!(back.reanalyze_edge(dep).unwrap(), EdgeState::Undefined);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn kind_pattern_survives_a_reload() {
+        let mut g = ReflexionGraph::new();
+        g.set_kind_pattern("^[a-z_]+$").unwrap();
+        let back = ReflexionGraph::from_bytes(&g.to_bytes()).unwrap();
+        assert_eq!(back.kind_pattern(), Some("^[a-z_]+$"));
+    }
+}
@@ -0,0 +1,238 @@
+// CSV import: headered node / edge / mapping tables, names instead of ids
+use std::collections::HashMap;
+use crate::core::graph::{Edge, Node, ReflexionGraph};
+use crate::core::types::{EdgeKind, NodeId, NodeKind, SubgraphKind};
+use crate::io::error::ParseError;
+
+//one parsed CSV table: header -> column index, plus (line number, fields) rows
+struct Table {
+    source: &'static str,
+    columns: HashMap<String, usize>,
+    rows: Vec<(usize, Vec<String>)>,
+}
+
+impl Table {
+    fn parse(source: &'static str, text: &str, required: &[&str]) -> Result<Table, ParseError> {
+        let mut lines = text.lines().enumerate().map(|(i, l)| (i + 1, l)).filter(|(_, l)| !l.trim().is_empty());
+        let (header_line, header) = lines.next().ok_or_else(|| ParseError::new(source, 1, "missing header"))?;
+        let columns: HashMap<String, usize> = split_csv_line(header)
+            .map_err(|msg| ParseError::new(source, header_line, msg))?
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| (name.trim().to_ascii_lowercase(), i))
+            .collect();
+        if let Some(missing) = required.iter().find(|c| !columns.contains_key(**c)) {
+            return Err(ParseError::new(source, header_line, format!("missing column '{}'", missing)));
+        }
+
+        let mut rows = Vec::new();
+        for (line, text) in lines {
+            let fields = split_csv_line(text).map_err(|msg| ParseError::new(source, line, msg))?;
+            rows.push((line, fields));
+        }
+        Ok(Table { source, columns, rows })
+    }
+
+    //trimmed value of a column in a row ("" for absent optional columns or short rows)
+    fn get<'r>(&self, row: &'r [String], column: &str) -> &'r str {
+        self.columns.get(column).and_then(|&i| row.get(i)).map_or("", |v| v.trim())
+    }
+
+    fn required<'r>(&self, line: usize, row: &'r [String], column: &str) -> Result<&'r str, ParseError> {
+        match self.get(row, column) {
+            "" => Err(ParseError::new(self.source, line, format!("empty '{}'", column))),
+            value => Ok(value),
+        }
+    }
+}
+
+//one CSV record: comma separated, fields may be double-quoted ("" = literal quote).
+//records spanning several lines are not supported.
+pub(crate) fn split_csv_line(line: &str) -> Result<Vec<String>, &'static str> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            (c, _) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quoted field");
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+impl ReflexionGraph {
+    //build a graph from CSV tables as data teams / extractors emit them:
+    // - nodes:    name, subgraph [, kind, parent, location]   (parent = name of a node of the same
+    //             subgraph, location = source file)
+    // - edges:    from, to, kind, subgraph          (names resolved in the edge's side of the model)
+    // - mappings: impl, arch
+    //column order is free, header names are case-insensitive, blank lines are skipped.
+    //node names must be unique per subgraph. the first problem found is returned with
+    //its table and line number.
+    pub fn from_csv(nodes_csv: &str, edges_csv: &str, mappings_csv: Option<&str>) -> Result<Self, ParseError> {
+        let mut g = ReflexionGraph::new();
+        let mut by_name: HashMap<(SubgraphKind, String), NodeId> = HashMap::new();
+
+        let nodes = Table::parse("nodes", nodes_csv, &["name", "subgraph"])?;
+        let mut parents: Vec<(usize, NodeId, SubgraphKind, &str)> = Vec::new();
+        for (line, row) in &nodes.rows {
+            let name = nodes.required(*line, row, "name")?;
+            let subgraph = parse_subgraph(&nodes, *line, row)?;
+            if subgraph == SubgraphKind::Propagated {
+                return Err(ParseError::new("nodes", *line, "nodes live in Architecture or Implementation"));
+            }
+            if by_name.contains_key(&(subgraph, name.to_string())) {
+                return Err(ParseError::new("nodes", *line, format!("duplicate node '{}'", name)));
+            }
+
+            let mut node = Node::new(name, subgraph, None);
+            let kind = nodes.get(row, "kind");
+            if !kind.is_empty() {
+                node = node.with_kind(NodeKind::parse(kind));
+            }
+            let location = nodes.get(row, "location");
+            if !location.is_empty() {
+                node = node.with_location(location);
+            }
+            let id = g.add_node(node).expect("no parent yet");
+            by_name.insert((subgraph, name.to_string()), id);
+            let parent = nodes.get(row, "parent");
+            if !parent.is_empty() {
+                parents.push((*line, id, subgraph, parent));
+            }
+        }
+        //second pass, so a parent may be listed after its children
+        for (line, child, subgraph, parent) in parents {
+            let parent = *by_name
+                .get(&(subgraph, parent.to_string()))
+                .ok_or_else(|| ParseError::new("nodes", line, format!("unknown parent '{}'", parent)))?;
+            g.set_parent(child, Some(parent)).map_err(|e| ParseError::new("nodes", line, e.to_string()))?;
+        }
+
+        let edges = Table::parse("edges", edges_csv, &["from", "to", "kind", "subgraph"])?;
+        for (line, row) in &edges.rows {
+            let subgraph = parse_subgraph(&edges, *line, row)?;
+            let side = match subgraph {
+                SubgraphKind::Implementation => SubgraphKind::Implementation,
+                SubgraphKind::Architecture | SubgraphKind::Propagated => SubgraphKind::Architecture,
+            };
+            let lookup = |column: &str| -> Result<NodeId, ParseError> {
+                let name = edges.required(*line, row, column)?;
+                by_name
+                    .get(&(side, name.to_string()))
+                    .copied()
+                    .ok_or_else(|| ParseError::new("edges", *line, format!("unknown {:?} node '{}'", side, name)))
+            };
+            let (from, to) = (lookup("from")?, lookup("to")?);
+            let kind = EdgeKind::try_new(edges.get(row, "kind")).map_err(|e| ParseError::new("edges", *line, e.to_string()))?;
+            g.add_edge(Edge::new(from, to, kind, subgraph)).expect("endpoints resolved above");
+        }
+
+        if let Some(text) = mappings_csv {
+            let mappings = Table::parse("mappings", text, &["impl", "arch"])?;
+            for (line, row) in &mappings.rows {
+                let resolve = |column: &str, side: SubgraphKind| -> Result<NodeId, ParseError> {
+                    let name = mappings.required(*line, row, column)?;
+                    by_name
+                        .get(&(side, name.to_string()))
+                        .copied()
+                        .ok_or_else(|| ParseError::new("mappings", *line, format!("unknown {:?} node '{}'", side, name)))
+                };
+                let impl_node = resolve("impl", SubgraphKind::Implementation)?;
+                let arch_node = resolve("arch", SubgraphKind::Architecture)?;
+                g.set_mapping(impl_node, arch_node).map_err(|e| ParseError::new("mappings", *line, e.to_string()))?;
+            }
+        }
+
+        Ok(g)
+    }
+}
+
+fn parse_subgraph(table: &Table, line: usize, row: &[String]) -> Result<SubgraphKind, ParseError> {
+    let text = table.required(line, row, "subgraph")?;
+    SubgraphKind::parse(text).ok_or_else(|| ParseError::new(table.source, line, format!("unknown subgraph '{}'", text)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_csv_line;
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::ReflexionGraph;
+    use crate::core::types::NodeKind;
+    use crate::io::error::ParseError;
+
+    const NODES: &str = "\
+name,subgraph,kind,parent,location
+Svc,Architecture,module,App,
+App,Architecture,,,
+Db,Architecture,datastore,,
+\"svc, main\",Implementation,class,,src/svc.rs
+db,Implementation,,,
+";
+    const EDGES: &str = "\
+from,to,kind,subgraph
+App,Db,calls,Architecture
+\"svc, main\",db,calls,Implementation
+";
+
+    #[test]
+    fn from_csv_resolves_names_hierarchy_and_mappings() {
+        let mut g = ReflexionGraph::from_csv(NODES, EDGES, Some("arch,impl\nSvc,\"svc, main\"\nDb,db\n")).unwrap();
+        g.analyze(&AnalysisOptions::default());
+        assert!(g.violations().unwrap().is_empty());
+
+        let svc = g.nodes().find(|n| n.name() == "Svc").unwrap();
+        assert_eq!(svc.kind(), Some(&NodeKind::ModuleNode));
+        assert_eq!(g.node(svc.parent().unwrap()).unwrap().name(), "App");
+        let main = g.nodes().find(|n| n.name() == "svc, main").unwrap();
+        assert_eq!(main.location(), Some("src/svc.rs"));
+        assert_eq!(svc.location(), None);
+
+        //unquoted, the comma splits the name
+        let err = ReflexionGraph::from_csv(NODES, EDGES, Some("impl,arch\nsvc, main,Svc\n")).unwrap_err();
+        assert_eq!(err, ParseError::new("mappings", 2, "unknown Implementation node 'svc'"));
+    }
+
+    #[test]
+    fn from_csv_reports_row_numbered_errors() {
+        let err = |nodes: &str, edges: &str| ReflexionGraph::from_csv(nodes, edges, None).unwrap_err();
+
+        assert_eq!(err("name\nA\n", EDGES), ParseError::new("nodes", 1, "missing column 'subgraph'"));
+        assert_eq!(err("name,subgraph\nA,Arch\n", EDGES), ParseError::new("nodes", 2, "unknown subgraph 'Arch'"));
+        assert_eq!(
+            err("name,subgraph\nA,Architecture\nA,architecture\n", EDGES),
+            ParseError::new("nodes", 3, "duplicate node 'A'")
+        );
+        assert_eq!(
+            err(NODES, "from,to,kind,subgraph\n\nApp,db,calls,Architecture\n"),
+            ParseError::new("edges", 3, "unknown Architecture node 'db'")
+        );
+        assert_eq!(
+            err(NODES, "from,to,kind,subgraph\nApp,Db, ,Architecture\n").message,
+            "Edge kind is empty or whitespace only"
+        );
+        assert_eq!(err(NODES, "from,to,kind,subgraph\n\"App,Db\n").message, "unterminated quoted field");
+    }
+
+    #[test]
+    fn split_csv_line_handles_quotes() {
+        assert_eq!(split_csv_line("a, b ,").unwrap(), vec!["a", " b ", ""]);
+        assert_eq!(split_csv_line("\"x, \"\"y\"\"\",z").unwrap(), vec!["x, \"y\"", "z"]);
+    }
+}
@@ -0,0 +1,297 @@
+// architecture diagrams (Graphviz DOT, Mermaid, PlantUML) of the last analyze
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use crate::core::graph::{Edge, Node, ReflexionGraph};
+use crate::core::state::EdgeState;
+use crate::core::types::{NodeId, NodeKind, SubgraphKind};
+
+//what a diagram shows, see ReflexionGraph::diagram
+struct Diagram<'g> {
+    nodes: Vec<&'g Node>, //node n<i> is nodes[i - 1]
+    index: HashMap<NodeId, usize>,
+    edges: Vec<&'g Edge>,
+}
+
+//PlantUML stereotype of a node kind: <<service>>, <<datastore>>, custom kinds verbatim
+fn stereotype(kind: &NodeKind) -> String {
+    match kind {
+        NodeKind::Custom(name) => name.clone(),
+        other => format!("{:?}", other).to_lowercase().trim_end_matches("node").to_string(),
+    }
+}
+
+impl ReflexionGraph {
+    //what a diagram shows, in emitting order: arch nodes sorted by (name, id) with their
+    //diagram id n1, n2, ..., then the spec edges and the propagated edges no spec edge
+    //covers (Divergent, Allowed), sorted by (subgraph, from name, to name, kind). edges
+    //with an endpoint outside the architecture (add_edge doesn't forbid them) are left
+    //out, there is no box to draw them between. ids are
+    //positions in that order, never NodeIds, so committed diagrams are byte-stable across
+    //runs and rebuilds of the same model.
+    fn diagram(&self) -> Diagram<'_> {
+        let mut nodes: Vec<&Node> = self.store.nodes.values().filter(|n| n.subgraph == SubgraphKind::Architecture).collect();
+        nodes.sort_unstable_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+        let index: HashMap<NodeId, usize> = nodes.iter().enumerate().map(|(i, n)| (n.id, i + 1)).collect();
+
+        let mut edges: Vec<(_, &Edge)> = self
+            .store
+            .edges
+            .values()
+            .filter(|e| match e.subgraph {
+                SubgraphKind::Architecture => true,
+                SubgraphKind::Propagated => e.state != EdgeState::Convergent,
+                SubgraphKind::Implementation => false,
+            })
+            .filter(|e| index.contains_key(&e.from) && index.contains_key(&e.to))
+            .map(|e| ((e.subgraph == SubgraphKind::Propagated, self.node_name(e.from), self.node_name(e.to), e.kind.as_str(), e.id), e))
+            .collect();
+        edges.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        Diagram {
+            nodes,
+            index,
+            edges: edges.into_iter().map(|(_, e)| e).collect(),
+        }
+    }
+
+    //Graphviz digraph of the architecture with the verdicts: Convergent green, Absent dashed
+    //orange, Divergent red, Allowed gray, multiplicity violations orange. node and edge
+    //descriptions become tooltips. render with `dot -Tsvg`.
+    pub fn to_dot(&self) -> String {
+        let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"));
+        let tooltip = |d: Option<&str>| d.map(|d| format!(", tooltip={}", quote(d))).unwrap_or_default();
+        let Diagram { nodes, index, edges } = self.diagram();
+
+        let mut out = String::from("digraph reflexion {\n  node [shape=box];\n");
+        for (i, n) in nodes.iter().enumerate() {
+            let _ = writeln!(out, "  n{} [label={}{}];", i + 1, quote(&n.name), tooltip(n.description.as_deref()));
+        }
+        for e in edges {
+            let style = match e.state {
+                EdgeState::Convergent => ", color=darkgreen",
+                EdgeState::Absent => ", color=orange, style=dashed",
+                EdgeState::MultiplicityViolation => ", color=orange",
+                EdgeState::Divergent => ", color=red",
+                EdgeState::Allowed | EdgeState::AllowedAbsent => ", color=gray",
+                EdgeState::Undefined | EdgeState::Specified | EdgeState::Unmapped => "",
+            };
+            let _ = writeln!(
+                out,
+                "  n{} -> n{} [label={}{}{}];",
+                index[&e.from],
+                index[&e.to],
+                quote(e.kind.as_str()),
+                tooltip(e.description.as_deref()),
+                style
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    //the same diagram as a Mermaid flowchart (renders in GitHub/GitLab markdown): edges are
+    //labeled with kind and state, Absent ones dotted, Divergent ones thick
+    pub fn to_mermaid(&self) -> String {
+        let label = |s: &str| s.replace('"', "#quot;");
+        let Diagram { nodes, index, edges } = self.diagram();
+
+        let mut out = String::from("flowchart LR\n");
+        for (i, n) in nodes.iter().enumerate() {
+            let _ = writeln!(out, "  n{}[\"{}\"]", i + 1, label(&n.name));
+        }
+        for e in edges {
+            let arrow = match e.state {
+                EdgeState::Absent | EdgeState::AllowedAbsent => "-.->",
+                EdgeState::Divergent => "==>",
+                _ => "-->",
+            };
+            let _ = writeln!(out, "  n{} {}|\"{} ({:?})\"| n{}", index[&e.from], arrow, label(e.kind.as_str()), e.state, index[&e.to]);
+        }
+        out
+    }
+
+    //the same diagram as a PlantUML component diagram: modules with submodules become
+    //packages around them, kinds become stereotypes, and arrows are styled by state
+    //(Convergent green, Absent dotted orange, Divergent dotted red, Allowed gray,
+    //multiplicity violations orange), labeled with kind and state
+    pub fn to_plantuml(&self) -> String {
+        let label = |s: &str| s.replace('"', "'");
+        let Diagram { nodes, index, edges } = self.diagram();
+        //children of each arch node (None = roots), in diagram order
+        let mut children: HashMap<Option<NodeId>, Vec<(usize, NodeId)>> = HashMap::new();
+        for (&id, &i) in &index {
+            children.entry(self.store.nodes[&id].parent).or_default().push((i, id));
+        }
+        for list in children.values_mut() {
+            list.sort_unstable();
+        }
+
+        let mut out = String::from("@startuml\n");
+        let mut stack: Vec<(usize, Option<NodeId>)> = children.get(&None).into_iter().flatten().rev().map(|&(_, id)| (1, Some(id))).collect();
+        //depth-first, a None entry closes the package opened at that depth
+        while let Some((depth, node)) = stack.pop() {
+            let pad = "  ".repeat(depth);
+            let Some(id) = node else {
+                let _ = writeln!(out, "{}}}", "  ".repeat(depth - 1));
+                continue;
+            };
+            let i = index[&id];
+            let kind = self.store.nodes[&id].kind.as_ref().map(|k| format!(" <<{}>>", stereotype(k))).unwrap_or_default();
+            match children.get(&Some(id)) {
+                Some(below) => {
+                    let _ = writeln!(out, "{}package \"{}\" as n{}{} {{", pad, label(&nodes[i - 1].name), i, kind);
+                    stack.push((depth + 1, None));
+                    stack.extend(below.iter().rev().map(|&(_, c)| (depth + 1, Some(c))));
+                }
+                None => {
+                    let _ = writeln!(out, "{}component \"{}\" as n{}{}", pad, label(&nodes[i - 1].name), i, kind);
+                }
+            }
+        }
+        for e in edges {
+            let arrow = match e.state {
+                EdgeState::Convergent => "-[#green]->",
+                EdgeState::Absent => ".[#orange].>",
+                EdgeState::MultiplicityViolation => "-[#orange]->",
+                EdgeState::Divergent => ".[#red].>",
+                EdgeState::Allowed => "-[#gray]->",
+                EdgeState::AllowedAbsent => ".[#gray].>",
+                EdgeState::Undefined | EdgeState::Specified | EdgeState::Unmapped => "-->",
+            };
+            let _ = writeln!(out, "  n{} {} n{} : {} ({:?})", index[&e.from], arrow, index[&e.to], label(e.kind.as_str()), e.state);
+        }
+        out.push_str("@enduml\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    //same model, built in the given order of impl edges (so ids and HashMap layout differ)
+    fn model(reversed: bool) -> ReflexionGraph {
+        let mut g = ReflexionGraph::new();
+        if reversed {
+            g.add_node(Node::new("pad", SubgraphKind::Implementation, None)).unwrap();
+        }
+        let ui = g.add_node(Node::new("Ui \"web\"", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        let log = g.add_node(Node::new("Log", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(ui, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(ui, log, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let [u, d] = [("u", ui), ("d", db)].map(|(name, arch)| {
+            let n = g.add_node(Node::new(name, SubgraphKind::Implementation, None)).unwrap();
+            g.set_mapping(n, arch).unwrap();
+            n
+        });
+        let mut calls = vec![(u, d), (d, u)];
+        if reversed {
+            calls.reverse();
+        }
+        for (from, to) in calls {
+            g.add_edge(Edge::new(from, to, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        }
+        g.analyze(&AnalysisOptions::default());
+        g
+    }
+
+    #[test]
+    fn diagrams_are_byte_stable_across_builds() {
+        let (a, b) = (model(false), model(true));
+        assert_eq!(a.to_dot(), b.to_dot());
+        assert_eq!(a.to_mermaid(), b.to_mermaid());
+
+        assert_eq!(
+            a.to_dot(),
+            "\
+digraph reflexion {
+  node [shape=box];
+  n1 [label=\"Db\"];
+  n2 [label=\"Log\"];
+  n3 [label=\"Ui \\\"web\\\"\"];
+  n3 -> n1 [label=\"calls\", color=darkgreen];
+  n3 -> n2 [label=\"calls\", color=orange, style=dashed];
+  n1 -> n3 [label=\"calls\", color=red];
+}
+"
+        );
+        assert_eq!(
+            a.to_mermaid(),
+            "\
+flowchart LR
+  n1[\"Db\"]
+  n2[\"Log\"]
+  n3[\"Ui #quot;web#quot;\"]
+  n3 -->|\"calls (Convergent)\"| n1
+  n3 -.->|\"calls (Absent)\"| n2
+  n1 ==>|\"calls (Divergent)\"| n3
+"
+        );
+    }
+
+    #[test]
+    fn plantuml_nests_modules_and_styles_arrows() {
+        use crate::core::types::NodeKind;
+
+        let mut g = model(false);
+        let sys = g.add_node(Node::new("System", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.nodes().find(|n| n.name() == "Db").unwrap().id();
+        let audit = g.add_node(Node::new("Audit", SubgraphKind::Architecture, Some(sys)).with_kind(NodeKind::ServiceNode)).unwrap();
+        g.set_parent(db, Some(sys)).unwrap();
+        g.add_edge(Edge::new(audit, db, EdgeKind::depends_on(), SubgraphKind::Architecture)).unwrap();
+        g.analyze(&AnalysisOptions::default());
+        assert_eq!(
+            g.to_plantuml(),
+            "\
+@startuml
+  component \"Log\" as n3
+  package \"System\" as n4 {
+    component \"Audit\" as n1 <<service>>
+    component \"Db\" as n2
+  }
+  component \"Ui 'web'\" as n5
+  n1 .[#orange].> n2 : depends_on (Absent)
+  n5 -[#green]-> n2 : calls (Convergent)
+  n5 .[#orange].> n3 : calls (Absent)
+  n2 .[#red].> n5 : calls (Divergent)
+@enduml
+"
+        );
+        assert_eq!(model(false).to_plantuml().lines().next(), Some("@startuml"));
+    }
+
+    #[test]
+    fn edges_leaving_the_architecture_are_not_drawn() {
+        let mut g = model(false);
+        let db = g.nodes().find(|n| n.name() == "Db").unwrap().id();
+        let code = g.nodes().find(|n| n.name() == "u").unwrap().id();
+        g.add_edge(Edge::new(db, code, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(code, db, EdgeKind::calls(), SubgraphKind::Propagated)).unwrap();
+        let clean = model(false);
+        assert_eq!(g.to_dot(), clean.to_dot());
+        assert_eq!(g.to_mermaid(), clean.to_mermaid());
+        assert_eq!(g.to_plantuml(), clean.to_plantuml());
+    }
+
+    #[test]
+    fn dot_shows_descriptions_as_tooltips() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("Ui", SubgraphKind::Architecture, None).with_description("web \"app\"")).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(ui, db, EdgeKind::calls(), SubgraphKind::Architecture).with_description("reads only\nvia the pool")).unwrap();
+
+        assert_eq!(
+            g.to_dot(),
+            "\
+digraph reflexion {
+  node [shape=box];
+  n1 [label=\"Db\"];
+  n2 [label=\"Ui\", tooltip=\"web \\\"app\\\"\"];
+  n2 -> n1 [label=\"calls\", tooltip=\"reads only\\nvia the pool\"];
+}
+"
+        );
+    }
+}
@@ -0,0 +1,77 @@
+// dependency structure matrix CSV, for DSM tools (Structure101, Lattix, ...)
+use std::collections::HashMap;
+use std::fmt::Write;
+use crate::core::graph::ReflexionGraph;
+use crate::core::state::EdgeState;
+use crate::core::types::{Counter, NodeId, SubgraphKind};
+
+pub(crate) fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+impl ReflexionGraph {
+    //square matrix of the arch modules in bfs_order: a header row of module names, then one
+    //row per module. cell (row, column) is the number of impl dependencies from the row
+    //module to the column module (sum over the propagated edges of the last analyze),
+    //negative when any of them is Divergent, empty when there are none.
+    pub fn to_dsm_csv(&self) -> String {
+        let modules = self.bfs_order(SubgraphKind::Architecture);
+        let mut cells: HashMap<(NodeId, NodeId), (Counter, bool)> = HashMap::new();
+        for eid in self.edges_in_subgraph(SubgraphKind::Propagated) {
+            let e = &self.store.edges[&eid];
+            let cell = cells.entry((e.from, e.to)).or_default();
+            cell.0 += e.counter;
+            cell.1 |= e.state == EdgeState::Divergent;
+        }
+
+        let mut out = String::new();
+        for &m in &modules {
+            write!(out, ",{}", csv_field(self.node_name(m))).expect("writing to a String cannot fail");
+        }
+        out.push('\n');
+        for &row in &modules {
+            out.push_str(&csv_field(self.node_name(row)));
+            for &col in &modules {
+                out.push(',');
+                if let Some(&(support, divergent)) = cells.get(&(row, col)) {
+                    let value = if divergent { -support } else { support };
+                    write!(out, "{}", value).expect("writing to a String cannot fail");
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn dsm_csv_has_signed_support_counts() {
+        let mut g = ReflexionGraph::new();
+        let ui = g.add_node(Node::new("Ui", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("Db, main", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(ui, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        let u = g.add_node(Node::new("u", SubgraphKind::Implementation, None)).unwrap();
+        let v = g.add_node(Node::new("v", SubgraphKind::Implementation, None)).unwrap();
+        let d = g.add_node(Node::new("d", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(u, ui).unwrap();
+        g.set_mapping(v, ui).unwrap();
+        g.set_mapping(d, db).unwrap();
+        g.add_edge(Edge::new(u, d, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(v, d, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(d, u, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.analyze(&AnalysisOptions::default());
+
+        //bfs_order: roots by name ("Db, main" < "Ui")
+        assert_eq!(g.to_dsm_csv(), ",\"Db, main\",Ui\n\"Db, main\",,-1\nUi,2,\n");
+    }
+}
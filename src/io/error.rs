@@ -0,0 +1,25 @@
+// errors of the text loaders
+use std::fmt;
+
+//a loader problem pinned to its place in the input: which file/section and which
+//1-based line (the header is line 1). unlike GraphError this carries text, so it's not Copy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub source: &'static str, //e.g. "nodes", "edges", "mappings"
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(source: &'static str, line: usize, message: impl Into<String>) -> Self {
+        Self { source, line, message: message.into() }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} line {}: {}", self.source, self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -0,0 +1,153 @@
+// JSON output (hand-rolled, the crate has no dependencies)
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use crate::core::graph::ReflexionGraph;
+use crate::core::types::EdgeId;
+use crate::core::trend::TrendPoint;
+
+//`s` as a JSON string literal, quotes included
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl ReflexionGraph {
+    //line-delimited JSON for jq or a log aggregator, one object per line. written line by
+    //line, so memory stays flat on huge graphs. every object has a "type", so consumers
+    //can filter with select(.type == "edge"):
+    //  {"type":"meta","tags":{"commit":"4f2a9c1",...}}  first, only with set_meta tags; keys sorted
+    //  {"type":"edge","from":"a","to":"b","kind":"calls","subgraph":"Implementation","state":"Divergent","counter":0,"severity":"Error","description":null}
+    //one edge line per edge, in edge id order. severity is null for edges that are not in
+    //a violation state, description for edges without one.
+    pub fn write_ndjson<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        if !self.metadata.is_empty() {
+            let tags: Vec<String> = self.meta_iter().map(|(k, v)| format!("{}:{}", json_string(k), json_string(v))).collect();
+            writeln!(w, "{{\"type\":\"meta\",\"tags\":{{{}}}}}", tags.join(","))?;
+        }
+        let mut ids: Vec<EdgeId> = self.store.edges.keys().copied().collect();
+        ids.sort_unstable();
+
+        for eid in ids {
+            let e = &self.store.edges[&eid];
+            let severity = if e.state.is_violation() {
+                format!("\"{:?}\"", self.severity_of(&e.kind))
+            } else {
+                "null".to_string()
+            };
+            writeln!(
+                w,
+                "{{\"type\":\"edge\",\"from\":{},\"to\":{},\"kind\":{},\"subgraph\":\"{:?}\",\"state\":\"{:?}\",\"counter\":{},\"severity\":{},\"description\":{}}}",
+                json_string(self.node_name(e.from)),
+                json_string(self.node_name(e.to)),
+                json_string(e.kind.as_str()),
+                e.subgraph,
+                e.state,
+                e.counter,
+                severity,
+                e.description.as_deref().map_or("null".to_string(), json_string)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+//a drift series as one JSON array, in the given order:
+//[{"revision":"4f2a9c1","convergence_ratio":0.75,"divergences":2},...]
+pub fn trend_to_json(points: &[TrendPoint]) -> String {
+    let items: Vec<String> = points
+        .iter()
+        .map(|p| {
+            format!(
+                "{{\"revision\":{},\"convergence_ratio\":{},\"divergences\":{}}}",
+                json_string(&p.revision),
+                p.convergence_ratio,
+                p.divergences
+            )
+        })
+        .collect();
+    format!("[{}]", items.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::json_string;
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn json_string_escapes_quotes_and_controls() {
+        assert_eq!(json_string("plain"), "\"plain\"");
+        assert_eq!(json_string("a\"b\\c\nd\u{1}"), "\"a\\\"b\\\\c\\nd\\u0001\"");
+    }
+
+    #[test]
+    fn write_ndjson_emits_one_object_per_edge() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B \"core\"", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture).with_description("cache\nwarmup")).unwrap();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        g.add_edge(Edge::new(i, i, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.analyze(&AnalysisOptions::default());
+
+        let mut out = Vec::new();
+        g.write_ndjson(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\
+{\"type\":\"edge\",\"from\":\"A\",\"to\":\"B \\\"core\\\"\",\"kind\":\"calls\",\"subgraph\":\"Architecture\",\"state\":\"Absent\",\"counter\":0,\"severity\":\"Error\",\"description\":\"cache\\nwarmup\"}
+{\"type\":\"edge\",\"from\":\"i\",\"to\":\"i\",\"kind\":\"calls\",\"subgraph\":\"Implementation\",\"state\":\"Unmapped\",\"counter\":0,\"severity\":null,\"description\":null}
+"
+        );
+    }
+
+    #[test]
+    fn write_ndjson_starts_with_meta_header_when_tagged() {
+        let mut g = ReflexionGraph::new();
+        let i = g.add_node(Node::new("i", SubgraphKind::Implementation, None)).unwrap();
+        g.add_edge(Edge::new(i, i, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.analyze(&AnalysisOptions::default());
+        g.set_meta("extractor", "rustdeps 0.3");
+        g.set_meta("commit", "4f2a9c1");
+        assert!(g.is_analyzed());
+
+        let mut out = Vec::new();
+        g.write_ndjson(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let mut lines = out.lines();
+        assert_eq!(lines.next(), Some("{\"type\":\"meta\",\"tags\":{\"commit\":\"4f2a9c1\",\"extractor\":\"rustdeps 0.3\"}}"));
+        assert!(lines.all(|l| l.starts_with("{\"type\":\"edge\",")));
+    }
+
+    #[test]
+    fn trend_to_json_serializes_the_series_in_order() {
+        use super::trend_to_json;
+        use crate::core::trend::TrendPoint;
+
+        let points = [
+            TrendPoint { revision: "a1".to_string(), convergence_ratio: 1.0, divergences: 0 },
+            TrendPoint { revision: "b\"2".to_string(), convergence_ratio: 0.75, divergences: 3 },
+        ];
+        assert_eq!(
+            trend_to_json(&points),
+            r#"[{"revision":"a1","convergence_ratio":1,"divergences":0},{"revision":"b\"2","convergence_ratio":0.75,"divergences":3}]"#
+        );
+        assert_eq!(trend_to_json(&[]), "[]");
+    }
+}
@@ -0,0 +1,157 @@
+// JUnit XML report, so CI test UIs show architecture conformance
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use crate::core::graph::ReflexionGraph;
+use crate::core::state::{EdgeState, Severity};
+use crate::core::types::SubgraphKind;
+
+pub(crate) fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+//one test case: name, failure type and message (None = passed), severity of its kind
+type Case = (String, Option<(String, &'static str)>, Severity);
+
+//why a test case failed, None if it passed
+fn failure_message(state: EdgeState) -> Option<&'static str> {
+    match state {
+        EdgeState::Absent => Some("specified dependency is not implemented"),
+        EdgeState::Divergent => Some("implemented dependency is not specified"),
+        EdgeState::MultiplicityViolation => Some("dependency is implemented too few or too many times"),
+        _ => None,
+    }
+}
+
+impl ReflexionGraph {
+    //JUnit XML of the last analyze: one test case per judged spec edge (passing when
+    //Convergent / AllowedAbsent) and one failing case per divergent module dependency,
+    //named "<from> -> <to> [<kind>]" and grouped into one suite per source module. impl
+    //edges breaching an API boundary (encapsulation_breaches) fail as "<impl from> ->
+    //<impl to> [<kind>]" in the suite of the module their source lifts to.
+    //violations of a Warning-severity kind don't fail: their case passes and carries the
+    //message as "warning: ..." in system-out. suites and cases are sorted by name, so the
+    //report is stable across runs.
+    pub fn to_junit(&self) -> String {
+        //suite -> (case name, failure type and message if it fails, severity)
+        let mut suites: BTreeMap<&str, Vec<Case>> = BTreeMap::new();
+        for eid in self.encapsulation_breaches() {
+            let e = &self.store.edges[&eid];
+            let module = self.lift_target(e.from, &self.last_options).map_or(self.node_name(e.from), |a| self.node_name(a));
+            let name = format!("{} -> {} [{}]", self.node_name(e.from), self.node_name(e.to), e.kind);
+            let failure = Some(("EncapsulationBreach".to_string(), "dependency bypasses the public API of the target module"));
+            suites.entry(module).or_default().push((name, failure, self.severity_of(&e.kind)));
+        }
+        for e in self.store.edges.values() {
+            let judged = match e.subgraph {
+                SubgraphKind::Architecture => e.state != EdgeState::Specified,
+                SubgraphKind::Propagated => e.state == EdgeState::Divergent,
+                SubgraphKind::Implementation => false,
+            };
+            if !judged {
+                continue;
+            }
+            let from = self.node_name(e.from);
+            let name = format!("{} -> {} [{}]", from, self.node_name(e.to), e.kind);
+            let failure = failure_message(e.state).map(|msg| (format!("{:?}", e.state), msg));
+            suites.entry(from).or_default().push((name, failure, self.severity_of(&e.kind)));
+        }
+
+        let total: usize = suites.values().map(Vec::len).sum();
+        let failed = |cases: &[Case]| cases.iter().filter(|(_, f, sv)| *sv == Severity::Error && f.is_some()).count();
+        let failures: usize = suites.values().map(|c| failed(c)).sum();
+
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(out, "<testsuites name=\"reflexion\" tests=\"{}\" failures=\"{}\">", total, failures);
+        for (suite, cases) in &mut suites {
+            cases.sort();
+            let suite = xml_escape(suite);
+            let _ = writeln!(out, "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">", suite, cases.len(), failed(cases));
+            for (name, failure, severity) in cases.iter() {
+                let _ = write!(out, "    <testcase classname=\"{}\" name=\"{}\"", suite, xml_escape(name));
+                match (failure, severity) {
+                    (Some((kind, msg)), Severity::Error) => {
+                        let _ = writeln!(out, ">\n      <failure type=\"{}\" message=\"{}\"/>\n    </testcase>", kind, msg);
+                    }
+                    (Some((_, msg)), Severity::Warning) => {
+                        let _ = writeln!(out, ">\n      <system-out>warning: {}</system-out>\n    </testcase>", msg);
+                    }
+                    (None, _) => out.push_str("/>\n"),
+                }
+            }
+            out.push_str("  </testsuite>\n");
+        }
+        out.push_str("</testsuites>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::classify::AnalysisOptions;
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::types::{EdgeKind, SubgraphKind};
+
+    #[test]
+    fn to_junit_reports_one_case_per_module_dependency() {
+        let mut g = ReflexionGraph::new();
+        let app = g.add_node(Node::new("App", SubgraphKind::Architecture, None)).unwrap();
+        let db = g.add_node(Node::new("Db<main>", SubgraphKind::Architecture, None)).unwrap();
+        let log = g.add_node(Node::new("Log", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(app, db, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.add_edge(Edge::new(app, log, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+
+        let a = g.add_node(Node::new("a", SubgraphKind::Implementation, None)).unwrap();
+        let d = g.add_node(Node::new("d", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(a, app).unwrap();
+        g.set_mapping(d, db).unwrap();
+        g.add_edge(Edge::new(a, d, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.add_edge(Edge::new(d, a, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        g.analyze(&AnalysisOptions::default());
+
+        assert_eq!(
+            g.to_junit(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="reflexion" tests="3" failures="2">
+  <testsuite name="App" tests="2" failures="1">
+    <testcase classname="App" name="App -&gt; Db&lt;main&gt; [calls]"/>
+    <testcase classname="App" name="App -&gt; Log [calls]">
+      <failure type="Absent" message="specified dependency is not implemented"/>
+    </testcase>
+  </testsuite>
+  <testsuite name="Db&lt;main&gt;" tests="1" failures="1">
+    <testcase classname="Db&lt;main&gt;" name="Db&lt;main&gt; -&gt; App [calls]">
+      <failure type="Divergent" message="implemented dependency is not specified"/>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+
+    #[test]
+    fn to_junit_lets_warning_kinds_pass() {
+        use crate::core::state::Severity;
+
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None)).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+        g.set_severity(EdgeKind::calls(), Severity::Warning);
+        g.analyze(&AnalysisOptions::default());
+
+        let xml = g.to_junit();
+        assert!(xml.contains("<testsuites name=\"reflexion\" tests=\"1\" failures=\"0\">"));
+        assert!(xml.contains("<system-out>warning: specified dependency is not implemented</system-out>"));
+    }
+}
@@ -0,0 +1,115 @@
+// name-based mapping file: one `impl_name => arch_name` line per mapping
+use std::collections::HashMap;
+use crate::core::types::{NodeId, SubgraphKind};
+use crate::core::graph::ReflexionGraph;
+use crate::io::error::ParseError;
+
+const ARROW: &str = "=>";
+
+impl ReflexionGraph {
+    //the mapping on its own, for version control next to the spec: sorted
+    //`impl_name => arch_name` lines. names instead of ids, so the file stays valid when
+    //the extractor regenerates the implementation graph with new ids.
+    pub fn export_mapping(&self) -> String {
+        self.mapping_matrix()
+            .into_iter()
+            .map(|(i, a)| format!("{} {} {}\n", i, ARROW, a))
+            .collect()
+    }
+
+    //apply an export_mapping file: names are resolved in their side of the model and
+    //each pair goes through set_mapping. lines that can't be applied (malformed, unknown
+    //or ambiguous name, set_mapping rejects the pair) are skipped and reported with their
+    //1-based line number; the others are applied. blank lines and `#` comments are ignored.
+    pub fn import_mapping(&mut self, text: &str) -> Vec<ParseError> {
+        let mut by_name: HashMap<(SubgraphKind, &str), Vec<NodeId>> = HashMap::new();
+        for n in self.store.nodes.values() {
+            by_name.entry((n.subgraph, n.name.as_str())).or_default().push(n.id);
+        }
+        let resolve = |side: SubgraphKind, name: &str| -> Result<NodeId, String> {
+            match by_name.get(&(side, name)).map(Vec::as_slice) {
+                Some(&[id]) => Ok(id),
+                Some(_) => Err(format!("ambiguous {:?} node '{}'", side, name)),
+                None => Err(format!("unknown {:?} node '{}'", side, name)),
+            }
+        };
+
+        let mut pairs = Vec::new();
+        let mut issues = Vec::new();
+        for (i, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((impl_name, arch_name)) = line.split_once(ARROW) else {
+                issues.push(ParseError::new("mappings", i + 1, format!("expected 'impl {} arch'", ARROW)));
+                continue;
+            };
+            let pair = resolve(SubgraphKind::Implementation, impl_name.trim())
+                .and_then(|impl_node| Ok((impl_node, resolve(SubgraphKind::Architecture, arch_name.trim())?)));
+            match pair {
+                Ok(pair) => pairs.push((i + 1, pair)),
+                Err(message) => issues.push(ParseError::new("mappings", i + 1, message)),
+            }
+        }
+
+        for (line, (impl_node, arch_node)) in pairs {
+            if let Err(e) = self.set_mapping(impl_node, arch_node) {
+                issues.push(ParseError::new("mappings", line, e.to_string()));
+            }
+        }
+        issues.sort_by_key(|issue| issue.line);
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::graph::{Node, ReflexionGraph};
+    use crate::core::types::SubgraphKind;
+
+    fn sides(g: &mut ReflexionGraph) {
+        for name in ["App", "Db"] {
+            g.add_node(Node::new(name, SubgraphKind::Architecture, None)).unwrap();
+        }
+        for name in ["app", "db", "util"] {
+            g.add_node(Node::new(name, SubgraphKind::Implementation, None)).unwrap();
+        }
+    }
+
+    #[test]
+    fn exported_mapping_imports_into_a_regenerated_graph() {
+        let mut g = ReflexionGraph::new();
+        sides(&mut g);
+        g.set_mapping(3, 1).unwrap();
+        g.set_mapping(4, 2).unwrap();
+        let text = g.export_mapping();
+        assert_eq!(text, "app => App\ndb => Db\n");
+
+        //same names, different ids
+        let mut fresh = ReflexionGraph::new();
+        fresh.add_node(Node::new("extra", SubgraphKind::Implementation, None)).unwrap();
+        sides(&mut fresh);
+        assert!(fresh.import_mapping(&text).is_empty());
+        assert_eq!(fresh.mapping_matrix(), g.mapping_matrix());
+    }
+
+    #[test]
+    fn import_mapping_reports_unresolved_lines_and_applies_the_rest() {
+        let mut g = ReflexionGraph::new();
+        sides(&mut g);
+        let text = "# comment\napp => App\n\nghost => Db\nutil => Nowhere\nno arrow here\ndb => Db\napp => Db\n";
+
+        let issues = g.import_mapping(text);
+        let report: Vec<String> = issues.iter().map(|e| e.to_string()).collect();
+        assert_eq!(report.len(), 4);
+        assert_eq!(report[0], "mappings line 4: unknown Implementation node 'ghost'");
+        assert_eq!(report[1], "mappings line 5: unknown Architecture node 'Nowhere'");
+        assert!(report[2].starts_with("mappings line 6: expected"));
+        assert!(report[3].starts_with("mappings line 8: "));
+        assert_eq!(g.mapping_matrix(), vec![
+            ("app".to_string(), "App".to_string()),
+            ("db".to_string(), "Db".to_string()),
+        ]);
+    }
+}
@@ -0,0 +1,10 @@
+pub mod error;
+pub mod csv_loader;
+pub mod mapping_file;
+pub mod spec_file;
+pub mod json_writer;
+pub mod junit;
+pub mod dsm;
+pub mod diagram;
+#[cfg(feature = "binary")]
+pub mod binary;
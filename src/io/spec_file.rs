@@ -0,0 +1,304 @@
+// architecture-only export: the reference model without any implementation
+use std::collections::HashMap;
+use crate::core::graph::{Edge, Node, ReflexionGraph};
+use crate::core::state::Severity;
+use crate::core::types::{EdgeKind, NodeId, NodeKind, SubgraphKind};
+use crate::io::csv_loader::split_csv_line;
+use crate::io::dsm::csv_field;
+use crate::io::error::ParseError;
+
+//name of a node kind as NodeKind::parse reads it back
+fn kind_name(kind: &NodeKind) -> String {
+    match kind {
+        NodeKind::Custom(name) => name.clone(),
+        other => format!("{:?}", other),
+    }
+}
+
+//import_spec reads one record per line, so line breaks (e.g. in a multi-line
+//description) are written as \n and \r, and backslashes doubled to keep that unambiguous
+fn escape(field: &str) -> String {
+    field.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+fn unescape(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('\\') => out.push('\\'),
+            //not written by export_spec: keep hand-written backslashes as they are
+            Some(other) => out.extend(['\\', other]),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn record(fields: &[&str]) -> String {
+    let mut line: Vec<String> = fields.iter().map(|f| csv_field(&escape(f))).collect();
+    while line.last().is_some_and(String::is_empty) {
+        line.pop();
+    }
+    line.join(",") + "\n"
+}
+
+impl ReflexionGraph {
+    //the intended architecture on its own, to publish without the (often proprietary) code:
+    //arch nodes, spec edges with their constraints, layers, severities and structural kinds,
+    //one CSV record per line (line breaks and backslashes in fields escaped as \n, \r and
+    //\\). no impl nodes, impl edges, mappings or results. records:
+    //  node,<name>,<parent>,<kind>,<description>
+    //  edge,<from>,<to>,<kind>,<variant>,<min support>,<max support>,<symmetric>,<description>
+    //  layer,<node>,<layer>
+    //  api,<node>
+    //  severity,<kind>,warning|error
+    //  structural,<kind>,<kind>,...
+    //nodes are referenced by name, so arch names must be unique for import_spec. parents
+    //come before their children; everything else is sorted.
+    pub fn export_spec(&self) -> String {
+        let mut nodes: Vec<(Vec<&str>, &Node)> = self
+            .store
+            .nodes
+            .values()
+            .filter(|n| n.subgraph == SubgraphKind::Architecture)
+            .map(|n| {
+                let mut path: Vec<&str> = self.self_and_ancestors(n.id).iter().map(|&a| self.node_name(a)).collect();
+                path.reverse();
+                (path, n)
+            })
+            .collect();
+        nodes.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut out = String::new();
+        for (_, n) in &nodes {
+            let parent = n.parent.map_or("", |p| self.node_name(p));
+            let kind = n.kind.as_ref().map(kind_name).unwrap_or_default();
+            out.push_str(&record(&["node", &n.name, parent, &kind, n.description.as_deref().unwrap_or("")]));
+        }
+
+        let mut edges: Vec<Vec<String>> = self
+            .store
+            .edges
+            .values()
+            .filter(|e| e.subgraph == SubgraphKind::Architecture)
+            .map(|e| {
+                vec![
+                    "edge".to_string(),
+                    self.node_name(e.from).to_string(),
+                    self.node_name(e.to).to_string(),
+                    e.kind.to_string(),
+                    e.variant.clone().unwrap_or_default(),
+                    e.min_support.map(|n| n.to_string()).unwrap_or_default(),
+                    e.max_support.map(|n| n.to_string()).unwrap_or_default(),
+                    if e.symmetric { "symmetric".to_string() } else { String::new() },
+                    e.description.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+        edges.sort();
+        for e in &edges {
+            out.push_str(&record(&e.iter().map(String::as_str).collect::<Vec<_>>()));
+        }
+
+        let mut layers: Vec<(&str, u32)> = self.layers.iter().map(|(&a, &l)| (self.node_name(a), l)).collect();
+        layers.sort_unstable();
+        for (name, layer) in layers {
+            out.push_str(&record(&["layer", name, &layer.to_string()]));
+        }
+
+        let mut api: Vec<&str> = self.api_boundaries.iter().map(|&a| self.node_name(a)).collect();
+        api.sort_unstable();
+        for name in api {
+            out.push_str(&record(&["api", name]));
+        }
+
+        let mut severities: Vec<(&str, Severity)> = self.severities.iter().map(|(k, &s)| (k.as_str(), s)).collect();
+        severities.sort_unstable_by_key(|&(k, _)| k);
+        for (kind, severity) in severities {
+            let severity = if severity == Severity::Warning { "warning" } else { "error" };
+            out.push_str(&record(&["severity", kind, severity]));
+        }
+
+        let mut structural: Vec<&str> = self.structural_kinds.iter().map(EdgeKind::as_str).collect();
+        structural.sort_unstable();
+        structural.insert(0, "structural");
+        out.push_str(&record(&structural));
+        out
+    }
+
+    //a fresh graph holding the spec of an export_spec text, ready for an implementation and
+    //a mapping to be loaded against it. fails on the first bad line (unknown record, unknown
+    //or duplicate node name, bad number). blank lines and `#` comments are ignored.
+    pub fn import_spec(text: &str) -> Result<ReflexionGraph, ParseError> {
+        let mut g = ReflexionGraph::new();
+        let mut by_name: HashMap<String, NodeId> = HashMap::new();
+
+        for (i, raw) in text.lines().enumerate() {
+            let line = i + 1;
+            let err = |message: String| ParseError::new("spec", line, message);
+            if raw.trim().is_empty() || raw.trim_start().starts_with('#') {
+                continue;
+            }
+            let fields: Vec<String> = split_csv_line(raw).map_err(|m| err(m.to_string()))?.iter().map(|f| unescape(f)).collect();
+            let field = |i: usize| fields.get(i).map_or("", |f| f.trim());
+            let node = |name: &str| by_name.get(name).copied().ok_or_else(|| err(format!("unknown node '{}'", name)));
+            let number = |i: usize| -> Result<Option<i32>, ParseError> {
+                match field(i) {
+                    "" => Ok(None),
+                    n => n.parse().map(Some).map_err(|_| err(format!("bad number '{}'", n))),
+                }
+            };
+
+            match field(0) {
+                "node" => {
+                    let name = field(1);
+                    if name.is_empty() || by_name.contains_key(name) {
+                        return Err(err(format!("empty or duplicate node name '{}'", name)));
+                    }
+                    let parent = match field(2) {
+                        "" => None,
+                        p => Some(node(p)?),
+                    };
+                    let mut n = Node::new(name, SubgraphKind::Architecture, parent);
+                    if !field(3).is_empty() {
+                        n = n.with_kind(NodeKind::parse(field(3)));
+                    }
+                    if !field(4).is_empty() {
+                        n = n.with_description(field(4));
+                    }
+                    let id = g.add_node(n).map_err(|e| err(e.to_string()))?;
+                    by_name.insert(name.to_string(), id);
+                }
+                "edge" => {
+                    let kind = EdgeKind::from(field(3));
+                    if kind.is_blank() {
+                        return Err(err("empty edge kind".to_string()));
+                    }
+                    let mut e = Edge::new(node(field(1))?, node(field(2))?, kind, SubgraphKind::Architecture);
+                    if !field(4).is_empty() {
+                        e = e.with_variant(field(4));
+                    }
+                    if let Some(min) = number(5)? {
+                        e = e.with_min_support(min);
+                    }
+                    if let Some(max) = number(6)? {
+                        e = e.with_max_support(max);
+                    }
+                    match field(7) {
+                        "" => {}
+                        "symmetric" => e = e.with_symmetric(),
+                        other => return Err(err(format!("expected 'symmetric', found '{}'", other))),
+                    }
+                    if !field(8).is_empty() {
+                        e = e.with_description(field(8));
+                    }
+                    g.add_edge(e).map_err(|e| err(e.to_string()))?;
+                }
+                "layer" => {
+                    let layer = field(2).parse().map_err(|_| err(format!("bad layer '{}'", field(2))))?;
+                    g.assign_layer(node(field(1))?, layer).map_err(|e| err(e.to_string()))?;
+                }
+                "api" => {
+                    g.set_api_boundary(node(field(1))?).map_err(|e| err(e.to_string()))?;
+                }
+                "severity" => {
+                    let severity = match field(2) {
+                        "warning" => Severity::Warning,
+                        "error" => Severity::Error,
+                        other => return Err(err(format!("unknown severity '{}'", other))),
+                    };
+                    g.set_severity(EdgeKind::from(field(1)), severity);
+                }
+                "structural" => {
+                    let kinds: Vec<EdgeKind> = fields[1..].iter().map(|k| EdgeKind::from(k.trim())).collect();
+                    g.set_structural_kinds(&kinds);
+                }
+                other => return Err(err(format!("unknown record '{}'", other))),
+            }
+        }
+        Ok(g)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::graph::{Edge, Node, ReflexionGraph};
+    use crate::core::state::Severity;
+    use crate::core::types::{EdgeKind, NodeKind, SubgraphKind};
+
+    #[test]
+    fn spec_round_trips_without_the_implementation() {
+        let mut g = ReflexionGraph::new();
+        let sys = g.add_node(Node::new("Sys", SubgraphKind::Architecture, None)).unwrap();
+        let ui = g.add_node(Node::new("Ui, web", SubgraphKind::Architecture, Some(sys)).with_kind(NodeKind::UINode)).unwrap();
+        let db = g.add_node(Node::new("Db", SubgraphKind::Architecture, Some(sys)).with_kind(NodeKind::custom("Store"))).unwrap();
+        g.add_edge(Edge::new(ui, db, EdgeKind::calls(), SubgraphKind::Architecture).with_max_support(3).with_description("reads only")).unwrap();
+        g.add_edge(Edge::new(db, ui, EdgeKind::from("notifies"), SubgraphKind::Architecture).with_variant("target").with_symmetric()).unwrap();
+        g.assign_layer(db, 0).unwrap();
+        g.set_api_boundary(ui).unwrap();
+        g.set_severity(EdgeKind::from("notifies"), Severity::Warning);
+        let code = g.add_node(Node::new("secret_impl", SubgraphKind::Implementation, None)).unwrap();
+        g.set_mapping(code, db).unwrap();
+
+        let text = g.export_spec();
+        assert_eq!(
+            text,
+            "\
+node,Sys
+node,Db,Sys,Store
+node,\"Ui, web\",Sys,UINode
+edge,Db,\"Ui, web\",notifies,target,,,symmetric
+edge,\"Ui, web\",Db,calls,,,3,,reads only
+layer,Db,0
+api,\"Ui, web\"
+severity,notifies,warning
+structural,contains
+"
+        );
+
+        let back = ReflexionGraph::import_spec(&text).unwrap();
+        assert_eq!(back.export_spec(), text);
+        assert!(back.nodes_in_subgraph(SubgraphKind::Implementation).is_empty());
+        assert_eq!(back.mapping_len(), 0);
+        let edge = back.edges().find(|e| e.kind().as_str() == "notifies").unwrap();
+        assert!(edge.is_symmetric());
+        assert_eq!(edge.variant(), Some("target"));
+    }
+
+    #[test]
+    fn import_spec_reports_the_bad_line() {
+        let err = ReflexionGraph::import_spec("node,A\n# comment\n\nedge,A,B,calls\n").unwrap_err();
+        assert_eq!((err.source, err.line), ("spec", 4));
+        assert!(err.message.contains("unknown node 'B'"));
+
+        assert_eq!(ReflexionGraph::import_spec("node,A\nnode,A\n").unwrap_err().line, 2);
+        assert_eq!(ReflexionGraph::import_spec("node,A\nedge,A,A,calls,,x\n").unwrap_err().line, 2);
+        assert_eq!(ReflexionGraph::import_spec("rule,A\n").unwrap_err().line, 1);
+    }
+
+    #[test]
+    fn multi_line_descriptions_stay_on_one_record() {
+        let mut g = ReflexionGraph::new();
+        let a = g.add_node(Node::new("A", SubgraphKind::Architecture, None).with_description("C:\\legacy")).unwrap();
+        let b = g.add_node(Node::new("B", SubgraphKind::Architecture, None)).unwrap();
+        g.add_edge(Edge::new(a, b, EdgeKind::calls(), SubgraphKind::Architecture).with_description("allowed because:\nlegacy")).unwrap();
+
+        let text = g.export_spec();
+        assert_eq!(text.lines().count(), 4);
+        assert!(text.contains("edge,A,B,calls,,,,,allowed because:\\nlegacy\n"));
+        assert!(text.contains("node,A,,,C:\\\\legacy\n"));
+
+        let back = ReflexionGraph::import_spec(&text).unwrap();
+        assert_eq!(back.export_spec(), text);
+        assert_eq!(back.edges().next().unwrap().description(), Some("allowed because:\nlegacy"));
+        let a = back.nodes().find(|n| n.name() == "A").unwrap();
+        assert_eq!(a.description(), Some("C:\\legacy"));
+    }
+}
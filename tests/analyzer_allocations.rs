@@ -0,0 +1,76 @@
+// steady-state allocation count of repeated analyze runs, plain vs Analyzer
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use reflexion_core::core::analyzer::Analyzer;
+use reflexion_core::core::classify::AnalysisOptions;
+use reflexion_core::core::graph::{Edge, Node, ReflexionGraph};
+use reflexion_core::core::types::{EdgeKind, SubgraphKind};
+
+//counts allocations of the current thread only, tests run in parallel
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static COUNTING: Counting = Counting;
+
+fn allocations_of(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+//modules m0..m9 in a chain, 20 impl nodes per module calling the next module
+fn chain() -> ReflexionGraph {
+    let mut g = ReflexionGraph::new();
+    let modules: Vec<_> = (0..10)
+        .map(|m| g.add_node(Node::new(format!("m{}", m), SubgraphKind::Architecture, None)).unwrap())
+        .collect();
+    for pair in modules.windows(2) {
+        g.add_edge(Edge::new(pair[0], pair[1], EdgeKind::calls(), SubgraphKind::Architecture)).unwrap();
+    }
+    let mut previous = Vec::new();
+    for &m in &modules {
+        let current: Vec<_> = (0..20)
+            .map(|i| {
+                let n = g.add_node(Node::new(format!("m{}::f{}", m, i), SubgraphKind::Implementation, None)).unwrap();
+                g.set_mapping(n, m).unwrap();
+                n
+            })
+            .collect();
+        for (&from, &to) in previous.iter().zip(&current) {
+            g.add_edge(Edge::new(from, to, EdgeKind::calls(), SubgraphKind::Implementation)).unwrap();
+        }
+        previous = current;
+    }
+    g
+}
+
+#[test]
+fn analyzer_allocates_less_in_steady_state() {
+    let options = AnalysisOptions::default();
+    let mut plain = chain();
+    let mut reused = chain();
+    let mut analyzer = Analyzer::new();
+    //warm up: first runs size every map and buffer
+    plain.analyze(&options);
+    analyzer.analyze(&mut reused, &options);
+
+    let before = allocations_of(|| plain.analyze(&options));
+    let after = allocations_of(|| analyzer.analyze(&mut reused, &options));
+    assert!(after < before, "steady-state allocations per analyze: plain {}, Analyzer {}", before, after);
+    assert_eq!(plain.to_canonical(), reused.to_canonical());
+}
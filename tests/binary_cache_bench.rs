@@ -0,0 +1,65 @@
+// binary cache vs the ndjson export on a 50k-edge graph. ignored by default (timing, not
+// correctness); run it in release for meaningful numbers:
+//   cargo test --release --test binary_cache_bench -- --ignored --nocapture
+#![cfg(feature = "binary")]
+use std::time::{Duration, Instant};
+use reflexion_core::core::classify::AnalysisOptions;
+use reflexion_core::core::graph::{Edge, Node, ReflexionGraph};
+use reflexion_core::core::types::{EdgeKind, SubgraphKind};
+
+const RUNS: u32 = 10;
+
+//50 modules with 200 spec edges between them, 5000 impl nodes mapped round-robin
+//and 50k distinct impl calls picked by a fixed LCG
+fn large() -> ReflexionGraph {
+    let mut g = ReflexionGraph::new();
+    let modules: Vec<_> = (0..50)
+        .map(|m| g.add_node(Node::new(format!("m{}", m), SubgraphKind::Architecture, None)).unwrap())
+        .collect();
+    for i in 0..200 {
+        let _ = g.add_edge(Edge::new(modules[i % 50], modules[(i * 7 + 3) % 50], EdgeKind::calls(), SubgraphKind::Architecture));
+    }
+    let code: Vec<_> = (0..5000)
+        .map(|i| g.add_node(Node::new(format!("crate::m{}::f{}", i % 97, i), SubgraphKind::Implementation, None)).unwrap())
+        .collect();
+    for (i, &n) in code.iter().enumerate() {
+        g.set_mapping(n, modules[i % 50]).unwrap();
+    }
+    let (mut x, mut added) = (1u64, 0);
+    while added < 50_000 {
+        x = x.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        let (from, to) = (code[(x >> 33) as usize % code.len()], code[(x >> 13) as usize % code.len()]);
+        if g.add_edge(Edge::new(from, to, EdgeKind::calls(), SubgraphKind::Implementation)).is_ok() {
+            added += 1;
+        }
+    }
+    g.analyze(&AnalysisOptions::default());
+    g
+}
+
+fn average(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        f();
+    }
+    start.elapsed() / RUNS
+}
+
+#[test]
+#[ignore]
+fn binary_cache_against_ndjson_on_50k_edges() {
+    let g = large();
+    let mut bytes = Vec::new();
+    let write_bytes = average(|| bytes = g.to_bytes());
+    let read_bytes = average(|| drop(ReflexionGraph::from_bytes(&bytes).unwrap()));
+    let mut json = Vec::new();
+    let write_json = average(|| {
+        json.clear();
+        g.write_ndjson(&mut json).unwrap();
+    });
+
+    println!("to_bytes     {:>9} bytes  {:?}", bytes.len(), write_bytes);
+    println!("from_bytes                   {:?}", read_bytes);
+    println!("write_ndjson {:>9} bytes  {:?}", json.len(), write_json);
+    assert!(bytes.len() < json.len());
+}